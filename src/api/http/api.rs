@@ -7,21 +7,36 @@
 // except according to those terms.
 
 // STD Dependencies -----------------------------------------------------------
+use std::panic;
 use std::thread;
-use std::cell::RefCell;
-use std::sync::{Arc, Mutex};
+use std::any::TypeId;
+use std::cell::{Cell, RefCell};
+use std::net::TcpListener;
+use std::io::{Error as IoError, ErrorKind};
+use std::sync::{Arc, Mutex, MutexGuard, Once, ONCE_INIT};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 
 // External Dependencies ------------------------------------------------------
-use hyper;
-use hyper::Client;
+use libc;
+use colored::*;
+use json::JsonValue;
 use hyper::method::Method;
+use hyper::version::HttpVersion;
 
 
 // Internal Dependencies ------------------------------------------------------
-use super::request::HttpRequest;
+use Options;
+use super::request::{HttpRequest, format_api_failure, format_api_stop_failure};
+use resource::http::{HttpHeader, HttpQueryString};
+use resource::graphql::graphql_body;
+use resource::jsonrpc::{JsonRpcCall, jsonrpc_call_body, jsonrpc_batch_body};
+use super::process::{HttpApiProcess, ApiProcess, spawn_process};
+use super::probe::{
+    HttpApiProbe, ProbeResult,
+    probe_api, probe_interval, probe_deadline
+};
 
 
 /// A trait for the description of a testable, HTTP based API.
@@ -62,12 +77,41 @@ pub trait HttpApi: Send + Copy + Default {
     ///
     /// The callback is executed in a background thread and should serve the
     /// API at the specified host.
-    fn start(&self);
+    ///
+    /// Does nothing by default, which is useful in case the API is served by
+    /// an external process via `HttpApi::process()`.
+    fn start(&self) {}
+
+    /// A callback for stopping the API server previously started via
+    /// `HttpApi::start()`.
+    ///
+    /// The callback should stop the server from accepting any further
+    /// connections and is invoked by `HttpApi::shutdown()` and
+    /// `HttpApi::restart()`, which then wait for the server to become
+    /// unavailable.
+    ///
+    /// Does nothing by default, in which case the server is not waited for.
+    fn stop(&self) {
+        default_stop();
+    }
+
+    /// Returns an external process which serves the API.
+    ///
+    /// When a process is returned it will be spawned instead of invoking
+    /// `HttpApi::start()` in a background thread.
+    ///
+    /// Defaults to `None`.
+    fn process(&self) -> Option<HttpApiProcess> {
+        None
+    }
 
     /// Returns the hostname of the API.
     fn hostname(&self) -> &'static str;
 
     /// Returns the port of the API.
+    ///
+    /// Use `HttpApi::ephemeral_port()` in order to have a free local port
+    /// allocated for the API.
     fn port(&self) -> u16;
 
     /// Returns the `hostname:port` combination of the API used for making
@@ -86,6 +130,20 @@ pub trait HttpApi: Send + Copy + Default {
         }
     }
 
+    /// Returns the HTTP protocol version used for requests against the API.
+    ///
+    /// With `HttpVersion::Http20` requests are send via cleartext HTTP/2 with
+    /// prior knowledge (h2c). WebSocket upgrades and event streams are always
+    /// performed via HTTP/1.1.
+    ///
+    /// `HttpVersion::Http09` and `HttpVersion::Http10` are not supported,
+    /// requests against such APIs are performed via HTTP/1.1 as well.
+    ///
+    /// Defaults to `HttpVersion::Http11`.
+    fn http_version(&self) -> HttpVersion {
+        HttpVersion::Http11
+    }
+
     /// Returns the fully qualified base URL of the API.
     fn url(&self) -> String {
         match self.port() {
@@ -108,6 +166,188 @@ pub trait HttpApi: Send + Copy + Default {
         Duration::from_millis(1000)
     }
 
+    /// Returns the readiness probe which is used to determine whether the API
+    /// has become available after it was started.
+    ///
+    /// By default a `HEAD` request is send to the base URL of the API and any
+    /// response is considered as ready.
+    ///
+    /// Unless the probe sets its own deadline, `HttpApi::timeout()` is used.
+    fn probe(&self) -> HttpApiProbe {
+        HttpApiProbe::new(Method::Head, "/")
+    }
+
+    /// Returns the path of the API's GraphQL endpoint.
+    ///
+    /// Defaults to `/graphql`.
+    fn graphql_path(&self) -> &'static str {
+        "/graphql"
+    }
+
+    /// Returns the path of the API's JSON-RPC endpoint.
+    ///
+    /// Defaults to `/rpc`.
+    fn jsonrpc_path(&self) -> &'static str {
+        "/rpc"
+    }
+
+    /// Returns the default headers which are send with every request against
+    /// the API.
+    ///
+    /// Individual requests can override these via `HttpRequest::with_header()`
+    /// or remove them via `HttpRequest::without_header()`.
+    ///
+    /// Defaults to no headers.
+    fn default_headers(&self) -> Vec<HttpHeader> {
+        Vec::new()
+    }
+
+    /// Returns the default query string parameters which are send with every
+    /// request against the API.
+    ///
+    /// Parameters which are also part of a request's own query string are
+    /// overridden by the latter. Individual requests can remove them via
+    /// `HttpRequest::without_query_param()`.
+    ///
+    /// Defaults to no parameters.
+    fn default_query(&self) -> HttpQueryString {
+        HttpQueryString::new(Vec::new())
+    }
+
+    /// Returns the default configuration options for every request against
+    /// the API.
+    ///
+    /// Individual requests can override these via `HttpRequest::with_options()`.
+    fn default_options(&self) -> Options {
+        Options::default()
+    }
+
+    /// A callback which is invoked before each individual `HttpRequest`
+    /// against the API is send.
+    ///
+    /// This can be used to put the API into a known state, e.g. by resetting
+    /// an in-memory database or clearing caches.
+    ///
+    /// Does nothing by default.
+    fn setup(&self) {}
+
+    /// A callback which is invoked after each individual `HttpRequest`
+    /// against the API has completed.
+    ///
+    /// The callback is invoked regardless of whether any of the request's
+    /// expectations failed.
+    ///
+    /// Does nothing by default.
+    fn teardown(&self) {}
+
+    /// Returns a free local port for use with `HttpApi::port()`.
+    ///
+    /// The port is allocated by the operating system when this method is
+    /// first called and stays the same for all further calls on the same
+    /// `HttpApi` implementation. This allows for multiple test binaries to run
+    /// concurrently without their API servers colliding on the same port.
+    ///
+    /// The port stays bound to a listener on `HttpApi::hostname()` until
+    /// `HttpApi::start()` takes it over via `HttpApi::ephemeral_listener()`,
+    /// so no other process can claim the port in the meantime.
+    ///
+    /// ```rust
+    /// # extern crate noir;
+    /// # use noir::HttpApi;
+    /// # #[derive(Copy, Clone, Default)]
+    /// # struct Api;
+    /// # impl HttpApi for Api {
+    /// #     fn hostname(&self) -> &'static str {
+    /// #         "localhost"
+    /// #     }
+    /// fn port(&self) -> u16 {
+    ///     Self::ephemeral_port()
+    /// }
+    /// # }
+    /// # fn main() {}
+    /// ```
+    fn ephemeral_port() -> u16 where Self: 'static {
+        ephemeral_port(Self::default().hostname(), TypeId::of::<Self>())
+    }
+
+    /// Returns the listener which is bound to the port allocated via
+    /// `HttpApi::ephemeral_port()`, for use with `HttpApi::start()`.
+    ///
+    /// The reserved listener is handed out only once, in case the API is
+    /// started again after `HttpApi::shutdown()` the port is bound anew.
+    ///
+    /// ```rust
+    /// # extern crate noir;
+    /// # extern crate hyper;
+    /// # use noir::HttpApi;
+    /// # use hyper::server::{Request, Response};
+    /// # use hyper::net::HttpListener;
+    /// # #[derive(Copy, Clone, Default)]
+    /// # struct Api;
+    /// # impl HttpApi for Api {
+    /// #     fn hostname(&self) -> &'static str {
+    /// #         "localhost"
+    /// #     }
+    /// #     fn port(&self) -> u16 {
+    /// #         Self::ephemeral_port()
+    /// #     }
+    /// fn start(&self) {
+    ///     let listener = Self::ephemeral_listener().unwrap();
+    ///     hyper::Server::new(HttpListener::from(listener)).handle(|_: Request, _: Response| {
+    ///         // Handle the request...
+    ///     }).unwrap();
+    /// }
+    /// # }
+    /// # fn main() {}
+    /// ```
+    fn ephemeral_listener() -> Result<TcpListener, IoError> where Self: 'static {
+        ephemeral_listener(Self::default().hostname(), TypeId::of::<Self>())
+    }
+
+    /// Stops the API server in case it is currently running.
+    ///
+    /// In-process servers are stopped via `HttpApi::stop()`, external
+    /// processes are killed.
+    ///
+    /// The next request against the API will start the server again.
+    ///
+    /// External processes which are still running at the end of the tests
+    /// are killed once the test binary exits.
+    ///
+    /// Returns an `API Failure` report in case the server was still available
+    /// after the API's probe deadline.
+    fn shutdown() -> Result<(), String> where Self: 'static {
+        shutdown(Self::default())
+    }
+
+    /// Stops the API server in case it is currently running and then starts
+    /// it again, waiting for it to become available.
+    ///
+    /// Returns the same `API Failure` report as a request would in case the
+    /// server did not become available in time, or the report of
+    /// `HttpApi::shutdown()` in case it did not stop.
+    fn restart() -> Result<(), String> where Self: 'static {
+        let api = Self::default();
+        try!(shutdown(api));
+        start(api).map_err(|failure| format_api_failure(&api, failure))
+    }
+
+    /// Returns the `stdout` output captured so far from the external process
+    /// which is currently serving the API.
+    ///
+    /// Returns an empty string for in-process servers.
+    fn stdout() -> String where Self: 'static {
+        process_output(Self::default(), |process| process.stdout())
+    }
+
+    /// Returns the `stderr` output captured so far from the external process
+    /// which is currently serving the API.
+    ///
+    /// Returns an empty string for in-process servers.
+    fn stderr() -> String where Self: 'static {
+        process_output(Self::default(), |process| process.stderr())
+    }
+
     /// Returns a `OPTIONS` request that will be performed against the API.
     fn options(path: &'static str) -> HttpRequest<Self> where Self: 'static {
         request(Self::default(), Method::Options, path)
@@ -159,6 +399,31 @@ pub trait HttpApi: Send + Copy + Default {
         request(Self::default(), Method::Extension(http_verb.to_string()), path)
     }
 
+    /// Returns a GraphQL `POST` request with the specified `query` and
+    /// `variables` that will be performed against `HttpApi::graphql_path()`.
+    fn graphql(query: &str, variables: JsonValue) -> HttpRequest<Self> where Self: 'static {
+        let api = Self::default();
+        request(api, Method::Post, api.graphql_path()).with_body(graphql_body(query, variables))
+    }
+
+    /// Returns a JSON-RPC 2.0 `POST` request which calls the remote procedure
+    /// `method` with the specified `params` and an `id` of `1`, that will be
+    /// performed against `HttpApi::jsonrpc_path()`.
+    fn jsonrpc(method: &str, params: JsonValue) -> HttpRequest<Self> where Self: 'static {
+        let api = Self::default();
+        request(api, Method::Post, api.jsonrpc_path()).with_body(
+            jsonrpc_call_body(&JsonRpcCall::new(1, method, params))
+        )
+    }
+
+    /// Returns a JSON-RPC 2.0 `POST` request which sends the specified `calls`
+    /// as a single batch, that will be performed against
+    /// `HttpApi::jsonrpc_path()`.
+    fn jsonrpc_batch(calls: Vec<JsonRpcCall>) -> HttpRequest<Self> where Self: 'static {
+        let api = Self::default();
+        request(api, Method::Post, api.jsonrpc_path()).with_body(jsonrpc_batch_body(&calls[..]))
+    }
+
 }
 
 
@@ -170,60 +435,280 @@ fn request<A: HttpApi + 'static>(
 
 ) -> HttpRequest<A> {
 
-    let mut api_timed_out = false;
-    if let Ok(started) = API_THREADS_STARTED.lock() {
+    let api_failure = start(api).err();
+    super::request::http_request(api, method, path, api_failure)
+
+}
+
+fn start<A: HttpApi + 'static>(api: A) -> Result<(), ApiFailure> {
+
+    let failure = if let Ok(started) = API_THREADS_STARTED.lock() {
 
         let host = api.host();
-        let mut thread_map = started.borrow_mut();
-        if !thread_map.contains_key(&host) {
+        let mut server_map = started.borrow_mut();
+        if !server_map.contains_key(&host) {
+
+            let server = if let Some(process) = api.process() {
+                kill_processes_on_exit();
+                match spawn_process(process) {
+                    Ok(process) => ApiServer::Process(process),
+                    Err(err) => return Err(ApiFailure {
+                        deadline: None,
+                        error: Some(format!(
+                            "{} {}",
+                            "Process could not be spawned:".yellow(),
+                            format!("{}", err).red().bold()
+                        )),
+                        output: None
+                    })
+                }
+
+            } else {
+                // Start server in the background
+                thread::spawn(move || {
+                    api.start();
+                });
+                ApiServer::Thread
+            };
+
+            // API server didn't start in time
+            if let Err(error) = wait_for_server(&api, true) {
+                Some((server, error))
+
+            // Insert into map
+            } else {
+                server_map.insert(host, server);
+                None
+            }
+
+        } else {
+            None
+        }
+
+    } else {
+        None
+    };
+
+    // The API's callbacks are invoked without holding the lock, the start
+    // failure is reported regardless of whether the server stopped in time
+    if let Some((mut server, error)) = failure {
+        let output = server.output();
+        server.stop(&api).ok();
+        Err(ApiFailure {
+            deadline: Some(probe_deadline(&api.probe(), &api)),
+            error: Some(format!(
+                "{} {}",
+                "Last readiness probe error:".yellow(),
+                error.red().bold()
+            )),
+            output: output
+        })
+
+    } else {
+        Ok(())
+    }
 
-            // Start server in the background
-            thread::spawn(move || {
-                api.start();
-            });
+}
 
-            let now = Instant::now();
-            let timeout = api.timeout();
+fn shutdown<A: HttpApi + 'static>(api: A) -> Result<(), String> {
 
-            // Wait for API server to be available
-            while now.elapsed() < timeout {
+    let mut server = None;
+    if let Ok(started) = API_THREADS_STARTED.lock() {
+        server = started.borrow_mut().remove(&api.host());
+    }
 
-                let mut client = Client::new();
+    // The API's callbacks are invoked without holding the lock
+    match server {
+        Some(mut server) => server.stop(&api).map_err(|_| {
+            format_api_stop_failure(&api, probe_deadline(&api.probe(), &api))
+        }),
+        None => Ok(())
+    }
 
-                // Windows has rather huge timeouts configured here by default
-                // so we want to avoid stalling the tests by reducing these
-                client.set_read_timeout(Some(Duration::from_millis(50)));
-                client.set_write_timeout(Some(Duration::from_millis(50)));
+}
 
-                match client.head(api.url().as_str()).send() {
-                    Err(hyper::Error::Io(_)) => { /* waiting for server */ },
-                    _ => break
+#[allow(unsafe_code)]
+fn kill_processes_on_exit() {
+    KILL_PROCESSES_ON_EXIT.call_once(|| {
+        // Test binaries exit via `std::process::exit()` which runs the
+        // handlers registered with atexit; the handler is not invoked when
+        // the binary is terminated by a signal, in which case the processes
+        // are left running
+        unsafe {
+            libc::atexit(kill_processes);
+        }
+    });
+}
+
+extern "C" fn kill_processes() {
+
+    // Other threads may still be running while the handler is invoked, so the
+    // lock is never waited for in order to not block the exit, and panics
+    // must not unwind across the C boundary
+    panic::catch_unwind(|| {
+        if let Ok(started) = API_THREADS_STARTED.try_lock() {
+
+            // Dropping the processes kills them
+            let mut server_map = started.borrow_mut();
+            let hosts: Vec<String> = server_map.iter().filter_map(|(host, server)| {
+                match *server {
+                    ApiServer::Thread => None,
+                    ApiServer::Process(_) => Some(host.clone())
                 }
 
-                thread::sleep(Duration::from_millis(10));
+            }).collect();
 
+            for host in hosts {
+                server_map.remove(&host);
             }
 
-            // API server didn't start in time
-            if now.elapsed() >= timeout {
-                api_timed_out = true;
+        }
+
+    }).ok();
+
+}
+
+fn stop_api<A: HttpApi>(api: &A) -> bool {
+    // Returns whether the API overrides the default stop callback
+    DEFAULT_STOP.with(|called| called.set(false));
+    api.stop();
+    !DEFAULT_STOP.with(|called| called.get())
+}
+
+fn default_stop() {
+    DEFAULT_STOP.with(|called| called.set(true));
+}
+
+fn process_output<A: HttpApi + 'static, F: Fn(&ApiProcess) -> String>(
+    api: A,
+    callback: F
+
+) -> String {
+    if let Ok(started) = API_THREADS_STARTED.lock() {
+        if let Some(&ApiServer::Process(ref process)) = started.borrow().get(&api.host()) {
+            return callback(process);
+        }
+    }
+    String::new()
+}
+
+fn ephemeral_port(hostname: &str, id: TypeId) -> u16 {
+    let ports = lock_ports();
+    let mut ports = ports.borrow_mut();
+    ports.entry(id).or_insert_with(|| {
+        TcpListener::bind((hostname, 0)).and_then(|listener| {
+            listener.local_addr().map(|addr| (addr.port(), Some(listener)))
+
+        }).expect("noir: Failed to allocate a free local port.")
+
+    }).0
+}
+
+fn ephemeral_listener(hostname: &str, id: TypeId) -> Result<TcpListener, IoError> {
+    let ports = lock_ports();
+    let mut ports = ports.borrow_mut();
+    match ports.get_mut(&id) {
+        Some(&mut (port, ref mut listener)) => match listener.take() {
+            Some(listener) => Ok(listener),
+            None => TcpListener::bind((hostname, port))
+        },
+        None => Err(IoError::new(
+            ErrorKind::NotFound,
+            "No ephemeral port was allocated for the API."
+        ))
+    }
+}
+
+fn lock_ports() -> MutexGuard<'static, RefCell<HashMap<TypeId, (u16, Option<TcpListener>)>>> {
+    // The port map is always left in a consistent state, so it can still be
+    // used after another thread panicked while holding the lock
+    match API_PORTS.lock() {
+        Ok(ports) => ports,
+        Err(err) => err.into_inner()
+    }
+}
+
+fn wait_for_server<A: HttpApi>(api: &A, available: bool) -> Result<(), String> {
+
+    let probe = api.probe();
+    let now = Instant::now();
+    let deadline = probe_deadline(&probe, api);
+    let mut last_error = String::new();
+
+    // Wait for API server to become (un)available
+    while now.elapsed() < deadline {
+
+        match probe_api(&probe, api) {
+            ProbeResult::Ready => if available {
+                return Ok(());
+            },
+            ProbeResult::Unavailable(error) => if available {
+                last_error = error;
 
-            // Insert into map
             } else {
-                thread_map.insert(host, true);
+                return Ok(());
+            },
+            ProbeResult::NotReady(error) => {
+                last_error = error;
             }
+        }
+
+        thread::sleep(probe_interval(&probe));
 
+    }
+
+    Err(last_error)
+
+}
+
+enum ApiServer {
+    Thread,
+    Process(ApiProcess)
+}
+
+impl ApiServer {
+
+    fn stop<A: HttpApi>(&mut self, api: &A) -> Result<(), String> {
+        match *self {
+            ApiServer::Thread => if stop_api(api) {
+                wait_for_server(api, false)
+
+            } else {
+                Ok(())
+            },
+            ApiServer::Process(ref mut process) => {
+                process.kill();
+                Ok(())
+            }
         }
+    }
 
+    fn output(&self) -> Option<String> {
+        match *self {
+            ApiServer::Thread => None,
+            ApiServer::Process(ref process) => Some(process.format_output())
+        }
     }
 
-    super::request::http_request(api, method, path, api_timed_out)
+}
 
+// Noir Internal --------------------------------------------------------------
+pub struct ApiFailure {
+    // Servers which could not be started are not waited for
+    pub deadline: Option<Duration>,
+    pub error: Option<String>,
+    pub output: Option<String>
 }
 
+static KILL_PROCESSES_ON_EXIT: Once = ONCE_INIT;
+
+thread_local!(static DEFAULT_STOP: Cell<bool> = Cell::new(false));
+
 lazy_static! {
-    static ref API_THREADS_STARTED: Arc<Mutex<RefCell<HashMap<String, bool>>>> = {
+    static ref API_THREADS_STARTED: Arc<Mutex<RefCell<HashMap<String, ApiServer>>>> = {
+        Arc::new(Mutex::new(RefCell::new(HashMap::new())))
+    };
+    static ref API_PORTS: Arc<Mutex<RefCell<HashMap<TypeId, (u16, Option<TcpListener>)>>>> = {
         Arc::new(Mutex::new(RefCell::new(HashMap::new())))
     };
 }
-
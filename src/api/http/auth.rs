@@ -0,0 +1,284 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::str;
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+use hyper::header::Headers;
+use openssl::crypto::hmac::hmac;
+use openssl::crypto::hash::{hash, Type};
+
+
+/// A HMAC-SHA256 based signature for requests made against a `HttpApi`.
+///
+/// The signature is computed over a canonical representation of the request's
+/// method, path (including the query string) and body once the request is
+/// send and then set as the value of a request header in lower case hex
+/// notation.
+///
+/// By default the canonical representation is
+/// `"<METHOD>\n<path>\n<hex encoded SHA256 of the body>"` and the signature
+/// is send in the `X-Signature` header.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # extern crate noir;
+/// use noir::HttpSignature;
+///
+/// # fn main() {
+/// let signature = HttpSignature::hmac_sha256("secret")
+///     .with_header("Authorization")
+///     .with_prefix("HMAC ")
+///     .with_canonicalization(|method, path, body| {
+///         format!("{} {} {}", method, path, body.len())
+///     });
+/// # }
+/// ```
+pub struct HttpSignature {
+    key: Vec<u8>,
+    header: String,
+    prefix: String,
+    canonicalization: Box<Fn(&str, &str, &[u8]) -> String>
+}
+
+impl HttpSignature {
+
+    /// Creates a new HMAC-SHA256 signature with the specified secret `key`.
+    pub fn hmac_sha256<K: Into<Vec<u8>>>(key: K) -> HttpSignature {
+        HttpSignature {
+            key: key.into(),
+            header: "X-Signature".to_string(),
+            prefix: String::new(),
+            canonicalization: Box::new(|method, path, body| {
+                format!("{}\n{}\n{}", method, path, hex(&hash(Type::SHA256, body)))
+            })
+        }
+    }
+
+    /// Sets the name of the request header which contains the signature.
+    pub fn with_header(mut self, name: &str) -> Self {
+        self.header = name.to_string();
+        self
+    }
+
+    /// Sets a prefix which is prepended to the signature in the request
+    /// header, e.g. a authorization scheme.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Sets a custom canonicalization function which is invoked with the
+    /// request's method, path and body and returns the data to be signed.
+    pub fn with_canonicalization<F: Fn(&str, &str, &[u8]) -> String + 'static>(
+        mut self,
+        canonicalization: F
+
+    ) -> Self {
+        self.canonicalization = Box::new(canonicalization);
+        self
+    }
+
+}
+
+
+// Noir Internal --------------------------------------------------------------
+pub fn sign_request(
+    signature: &HttpSignature,
+    headers: &mut Headers,
+    method: &str,
+    path: &str,
+    body: &[u8]
+) {
+
+    let data = (signature.canonicalization)(method, path, body);
+    let digest = hmac(Type::SHA256, &signature.key[..], data.as_bytes());
+
+    headers.set_raw(
+        signature.header.clone(),
+        vec![format!("{}{}", signature.prefix, hex(&digest)).into_bytes()]
+    );
+
+}
+
+pub fn validate_auth_challenge(
+    errors: &mut Vec<String>,
+    headers: &Headers,
+    scheme: &str,
+    realm: Option<&str>
+) {
+
+    let challenges = headers.get_raw("WWW-Authenticate").map(|values| {
+        values.iter().map(|value| {
+            String::from_utf8_lossy(&value[..]).into_owned()
+
+        }).collect::<Vec<String>>()
+
+    }).unwrap_or_else(Vec::new);
+
+    if challenges.is_empty() {
+        errors.push(format!(
+            "{} {} \"{}\" {} {}{} {}{}",
+            "Response".yellow(),
+            "header".yellow(),
+            "WWW-Authenticate".blue().bold(),
+            "was expected".yellow(),
+            "to be present".green().bold(),
+            ", but".yellow(),
+            "is missing".red().bold(),
+            ".".yellow()
+        ));
+
+    } else if !challenges.iter().flat_map(|c| parse_challenges(c)).any(|c| {
+        challenge_matches(&c, scheme, realm)
+
+    }) {
+
+        let expected = if let Some(realm) = realm {
+            format!("{} realm=\"{}\"", scheme, realm)
+
+        } else {
+            scheme.to_string()
+        };
+
+        errors.push(format!(
+            "{} {} \"{}\" {}\n\n        \"{}\"\n\n    {}\n\n        {}",
+            "Response".yellow(),
+            "header".yellow(),
+            "WWW-Authenticate".blue().bold(),
+            "does not contain the expected challenge:".yellow(),
+            expected.green().bold(),
+            "but got:".yellow(),
+            challenges.iter().map(|c| {
+                format!("\"{}\"", c.red().bold())
+
+            }).collect::<Vec<String>>().join(", ")
+        ));
+
+    }
+
+}
+
+// Helpers --------------------------------------------------------------------
+struct Challenge {
+    scheme: String,
+    params: Vec<(String, String)>
+}
+
+fn challenge_matches(challenge: &Challenge, scheme: &str, realm: Option<&str>) -> bool {
+
+    if challenge.scheme.to_lowercase() != scheme.to_lowercase() {
+        false
+
+    } else if let Some(realm) = realm {
+        challenge.params.iter().any(|&(ref key, ref value)| {
+            key == "realm" && value == realm
+        })
+
+    } else {
+        true
+    }
+
+}
+
+// A single header value can contain multiple challenges, each of which is
+// followed by its own comma separated list of parameters, e.g.
+// `Basic realm="a", Bearer realm="b", error="invalid_token"`
+fn parse_challenges(value: &str) -> Vec<Challenge> {
+
+    let mut challenges: Vec<Challenge> = Vec::new();
+
+    for element in split_unquoted(value, ',') {
+
+        let element = element.trim();
+        let token_end = element.find(|c: char| {
+            c.is_whitespace() || c == '='
+
+        }).unwrap_or_else(|| element.len());
+
+        let (token, rest) = element.split_at(token_end);
+        if token.is_empty() {
+            continue;
+
+        // Parameter of the preceding challenge
+        } else if rest.trim_left().starts_with('=') {
+            if let Some(challenge) = challenges.last_mut() {
+                challenge.params.push(parse_param(element));
+            }
+
+        // Start of a new challenge, optionally followed by its first parameter
+        } else {
+            let rest = rest.trim();
+            challenges.push(Challenge {
+                scheme: token.to_string(),
+                params: if rest.is_empty() {
+                    Vec::new()
+
+                } else {
+                    vec![parse_param(rest)]
+                }
+            });
+        }
+
+    }
+
+    challenges
+
+}
+
+fn parse_param(param: &str) -> (String, String) {
+
+    let mut pair = param.splitn(2, '=');
+    let key = pair.next().unwrap_or("").trim().to_lowercase();
+    let value = pair.next().unwrap_or("").trim();
+
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        (key, value[1..value.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\"))
+
+    } else {
+        (key, value.to_string())
+    }
+
+}
+
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+
+        } else if quoted && c == '\\' {
+            escaped = true;
+
+        } else if c == '"' {
+            quoted = !quoted;
+
+        } else if c == separator && !quoted {
+            parts.push(&value[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+
+    parts.push(&value[start..]);
+    parts
+
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:0>2x}", b)).collect::<Vec<String>>().join("")
+}
+
@@ -8,10 +8,16 @@
 
 // Modules --------------------------------------------------------------------
 mod api;
+mod auth;
+mod probe;
+mod process;
 mod request;
 
 
 // Exports --------------------------------------------------------------------
 pub use self::api::HttpApi;
-pub use self::request::HttpRequest;
+pub use self::auth::HttpSignature;
+pub use self::probe::HttpApiProbe;
+pub use self::process::HttpApiProcess;
+pub use self::request::{HttpRequest, REQUEST_LOCK};
 
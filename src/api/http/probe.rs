@@ -0,0 +1,208 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::io::{ErrorKind, Read};
+use std::time::Duration;
+
+
+// External Dependencies ------------------------------------------------------
+use hyper;
+use hyper::Client;
+use hyper::method::Method;
+use hyper::header::Headers;
+use hyper::status::StatusCode;
+use hyper::version::HttpVersion;
+
+
+// Internal Dependencies ------------------------------------------------------
+use HttpApi;
+use resource::http2::http2_request;
+
+
+/// A readiness probe which is used to determine whether a `HttpApi` has
+/// become available after it was started.
+///
+/// Returned from `HttpApi::probe()`.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # extern crate noir;
+/// # extern crate hyper;
+/// # use std::time::Duration;
+/// # use hyper::method::Method;
+/// # use hyper::status::StatusCode;
+/// use noir::{HttpApi, HttpApiProbe};
+///
+/// #[derive(Copy, Clone, Default)]
+/// struct Api;
+/// impl HttpApi for Api {
+///
+///     fn hostname(&self) -> &'static str {
+///         "localhost"
+///     }
+///
+///     fn port(&self) -> u16 {
+///         8080
+///     }
+///
+///     fn probe(&self) -> HttpApiProbe {
+///         HttpApiProbe::new(Method::Get, "/health")
+///             .expected_status(StatusCode::Ok)
+///             .expected_body(|body| body.contains("healthy"))
+///             .with_interval(Duration::from_millis(50))
+///             .with_deadline(Duration::from_millis(5000))
+///     }
+///
+/// }
+/// # fn main() {}
+/// ```
+pub struct HttpApiProbe {
+    method: Method,
+    path: String,
+    expected_status: Option<StatusCode>,
+    expected_body: Option<Box<Fn(&str) -> bool>>,
+    interval: Duration,
+    deadline: Option<Duration>
+}
+
+impl HttpApiProbe {
+
+    /// Creates a new probe which sends requests with the specified `method`
+    /// to the specified `path` of the API.
+    ///
+    /// By default, any response from the API is considered as ready.
+    pub fn new(method: Method, path: &str) -> HttpApiProbe {
+        HttpApiProbe {
+            method: method,
+            path: path.to_string(),
+            expected_status: None,
+            expected_body: None,
+            interval: Duration::from_millis(10),
+            deadline: None
+        }
+    }
+
+    /// Sets the response status which is required for the API to be
+    /// considered as ready.
+    pub fn expected_status(mut self, status_code: StatusCode) -> Self {
+        self.expected_status = Some(status_code);
+        self
+    }
+
+    /// Sets a predicate which the response body must satisfy for the API to be
+    /// considered as ready.
+    pub fn expected_body<F: Fn(&str) -> bool + 'static>(mut self, predicate: F) -> Self {
+        self.expected_body = Some(Box::new(predicate));
+        self
+    }
+
+    /// Sets the duration to wait in between individual probe requests.
+    ///
+    /// Defaults to `10ms`.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the maximum duration to wait for the API to become ready.
+    ///
+    /// Defaults to `HttpApi::timeout()`.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+}
+
+
+// Noir Internal --------------------------------------------------------------
+pub enum ProbeResult {
+    Ready,
+    NotReady(String),
+    Unavailable(String)
+}
+
+pub fn probe_interval(probe: &HttpApiProbe) -> Duration {
+    probe.interval
+}
+
+pub fn probe_deadline<A: HttpApi>(probe: &HttpApiProbe, api: &A) -> Duration {
+    probe.deadline.unwrap_or_else(|| api.timeout())
+}
+
+pub fn probe_api<A: HttpApi>(probe: &HttpApiProbe, api: &A) -> ProbeResult {
+
+    // Windows has rather huge timeouts configured here by default
+    // so we want to avoid stalling the tests by reducing these
+    let timeout = Duration::from_millis(50);
+
+    if api.http_version() == HttpVersion::Http20 {
+        return match http2_request(
+            api.host().as_str(),
+            &probe.method,
+            probe.path.as_str(),
+            &Headers::new(),
+            None,
+            timeout
+        ) {
+            Ok(response) => probe_response(probe, response.status, &mut &response.body[..]),
+            Err(ref err) if err.kind() == ErrorKind::InvalidData => {
+                ProbeResult::NotReady(format!("{}", err))
+            },
+            Err(err) => ProbeResult::Unavailable(format!("{}", err))
+        };
+    }
+
+    let mut client = Client::new();
+    client.set_read_timeout(Some(timeout));
+    client.set_write_timeout(Some(timeout));
+
+    let request = client.request(
+        probe.method.clone(),
+        api.url_with_path(probe.path.as_str()).as_str()
+    );
+
+    match request.send() {
+        Ok(mut response) => {
+            let status = response.status;
+            probe_response(probe, status, &mut response)
+        },
+        Err(hyper::Error::Io(err)) => ProbeResult::Unavailable(format!("{}", err)),
+        Err(err) => ProbeResult::NotReady(format!("{}", err))
+    }
+
+}
+
+fn probe_response(probe: &HttpApiProbe, status: StatusCode, body: &mut Read) -> ProbeResult {
+
+    if let Some(expected) = probe.expected_status {
+        if status != expected {
+            return ProbeResult::NotReady(format!(
+                "Expected status \"{}\" but got \"{}\".",
+                expected,
+                status
+            ));
+        }
+    }
+
+    if let Some(predicate) = probe.expected_body.as_ref() {
+        let mut data = Vec::new();
+        body.read_to_end(&mut data).ok();
+        if !predicate(String::from_utf8_lossy(&data[..]).as_ref()) {
+            return ProbeResult::NotReady(
+                "Response body did not satisfy the expected predicate.".to_string()
+            );
+        }
+    }
+
+    ProbeResult::Ready
+
+}
+
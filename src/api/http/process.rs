@@ -0,0 +1,264 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::thread;
+use std::io::{Error, Read};
+use std::sync::{Arc, Mutex};
+use std::process::{Child, Command, Stdio};
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::http::HttpProxy;
+use mock::{RedisServer, SmtpServer};
+
+
+/// A description of an external command which serves a `HttpApi`.
+///
+/// Returned from `HttpApi::process()` in order to test an API which is build
+/// as a separate binary.
+///
+/// The process is spawned once the API is first requested, its `stdout` and
+/// `stderr` are captured and it is killed again via `HttpApi::shutdown()` or
+/// once the test binary exits.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # extern crate noir;
+/// use noir::{HttpApi, HttpApiProcess};
+///
+/// #[derive(Copy, Clone, Default)]
+/// struct Api;
+/// impl HttpApi for Api {
+///
+///     fn hostname(&self) -> &'static str {
+///         "localhost"
+///     }
+///
+///     fn port(&self) -> u16 {
+///         8080
+///     }
+///
+///     fn process(&self) -> Option<HttpApiProcess> {
+///         Some(HttpApiProcess::new("target/debug/service")
+///             .with_args(vec!["--verbose"])
+///             .with_env("PORT", "8080"))
+///     }
+///
+/// }
+/// # fn main() {}
+/// ```
+pub struct HttpApiProcess {
+    program: String,
+    args: Vec<String>,
+    envs: Vec<(String, String)>,
+    current_dir: Option<String>
+}
+
+impl HttpApiProcess {
+
+    /// Creates a new process description for the specified `program`.
+    pub fn new<S: Into<String>>(program: S) -> HttpApiProcess {
+        HttpApiProcess {
+            program: program.into(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            current_dir: None
+        }
+    }
+
+    /// Adds one additional argument which is passed to the program.
+    pub fn with_arg<S: Into<String>>(mut self, arg: S) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds additional arguments which are passed to the program.
+    pub fn with_args<S: Into<String>>(mut self, args: Vec<S>) -> Self {
+        for arg in args {
+            self.args.push(arg.into());
+        }
+        self
+    }
+
+    /// Sets an environment variable for the program.
+    pub fn with_env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Routes all outgoing HTTP requests of the program through the noir
+    /// `HttpProxy` by setting its `HTTP_PROXY` and `HTTPS_PROXY` environment
+    /// variables.
+    pub fn with_proxy(mut self) -> Self {
+        let url = HttpProxy::url();
+        for key in &["HTTP_PROXY", "http_proxy", "HTTPS_PROXY", "https_proxy"] {
+            self.envs.push((key.to_string(), url.clone()));
+        }
+        self
+    }
+
+    /// Makes the program trust the certificate authority of the noir
+    /// `HttpProxy` by setting its `SSL_CERT_FILE` environment variable.
+    ///
+    /// Note: `SSL_CERT_FILE` replaces the system's trusted certificates, so
+    /// the program will only accept TLS connections which are intercepted by
+    /// the proxy.
+    pub fn with_proxy_ca_certificate(mut self) -> Self {
+        self.envs.push((
+            "SSL_CERT_FILE".to_string(),
+            HttpProxy::ca_certificate_path()
+        ));
+        self
+    }
+
+    /// Makes the program send its mails to the noir `SmtpServer` by setting
+    /// its `SMTP_HOST` and `SMTP_PORT` environment variables.
+    pub fn with_smtp(mut self) -> Self {
+        self.envs.push(("SMTP_HOST".to_string(), SmtpServer::hostname().to_string()));
+        self.envs.push(("SMTP_PORT".to_string(), SmtpServer::port().to_string()));
+        self
+    }
+
+    /// Makes the program connect to the noir `RedisServer` by setting its
+    /// `REDIS_URL` environment variable.
+    pub fn with_redis(mut self) -> Self {
+        self.envs.push(("REDIS_URL".to_string(), RedisServer::url()));
+        self
+    }
+
+    /// Sets the working directory of the program.
+    pub fn with_current_dir<S: Into<String>>(mut self, dir: S) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+}
+
+
+// Noir Internal --------------------------------------------------------------
+pub struct ApiProcess {
+    program: String,
+    child: Child,
+    stdout: Arc<Mutex<Vec<u8>>>,
+    stderr: Arc<Mutex<Vec<u8>>>
+}
+
+impl ApiProcess {
+
+    pub fn kill(&mut self) {
+        // The process might have already exited on its own
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+
+    pub fn stdout(&self) -> String {
+        captured_output(&self.stdout)
+    }
+
+    pub fn stderr(&self) -> String {
+        captured_output(&self.stderr)
+    }
+
+    pub fn format_output(&self) -> String {
+        format!(
+            "{} \"{}\" {}\n\n        {}\n\n    {} \"{}\" {}\n\n        {}",
+            "Process".yellow(),
+            self.program.cyan(),
+            "stdout dump:".yellow(),
+            format_lines(self.stdout()),
+            "Process".yellow(),
+            self.program.cyan(),
+            "stderr dump:".yellow(),
+            format_lines(self.stderr())
+        )
+    }
+
+}
+
+impl Drop for ApiProcess {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+pub fn spawn_process(process: HttpApiProcess) -> Result<ApiProcess, Error> {
+
+    let mut command = Command::new(process.program.as_str());
+    command.args(&process.args[..])
+           .stdin(Stdio::null())
+           .stdout(Stdio::piped())
+           .stderr(Stdio::piped());
+
+    for &(ref key, ref value) in &process.envs {
+        command.env(key, value);
+    }
+
+    if let Some(dir) = process.current_dir.as_ref() {
+        command.current_dir(dir);
+    }
+
+    let mut child = try!(command.spawn());
+    let stdout = capture_output(child.stdout.take());
+    let stderr = capture_output(child.stderr.take());
+
+    Ok(ApiProcess {
+        program: process.program,
+        child: child,
+        stdout: stdout,
+        stderr: stderr
+    })
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn capture_output<R: Read + Send + 'static>(reader: Option<R>) -> Arc<Mutex<Vec<u8>>> {
+
+    let output = Arc::new(Mutex::new(Vec::new()));
+
+    if let Some(mut reader) = reader {
+
+        let target = output.clone();
+        thread::spawn(move || {
+            let mut buffer = [0; 1024];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(size) => if let Ok(mut target) = target.lock() {
+                        target.extend_from_slice(&buffer[..size]);
+                    }
+                }
+            }
+        });
+
+    }
+
+    output
+
+}
+
+fn captured_output(output: &Arc<Mutex<Vec<u8>>>) -> String {
+    match output.lock() {
+        Ok(output) => String::from_utf8_lossy(&output[..]).into_owned(),
+        Err(_) => String::new()
+    }
+}
+
+fn format_lines(output: String) -> String {
+    output.lines().map(|line| {
+        line.purple().bold().to_string()
+
+    }).collect::<Vec<String>>().join("\n        ")
+}
+
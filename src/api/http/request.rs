@@ -7,24 +7,46 @@
 // except according to those terms.
 
 // STD Dependencies -----------------------------------------------------------
+use std::mem;
+use std::time::Duration;
+use std::net::TcpStream;
 use std::sync::{Arc, Mutex};
+use std::io::{Error as IoError, ErrorKind, Read, Write};
 
 
 // External Dependencies ------------------------------------------------------
 use colored::*;
+use url::form_urlencoded;
+use json::JsonValue;
 use hyper::{Client, Error};
 use hyper::method::Method;
 use hyper::client::Response;
 use hyper::status::StatusCode;
-use hyper::header::{Header, Headers, HeaderFormat, ContentType};
+use hyper::version::HttpVersion;
+use hyper::header::{
+    Header, Headers, HeaderFormat, ContentType,
+    Authorization, Basic, Bearer
+};
 
 
 // Internal Dependencies ------------------------------------------------------
 use HttpApi;
 use Options;
-use mock::{MockResponse, MockProvider, ResponseProvider};
+use mock::{MockResponse, MockProvider, ResponseProvider, MockCandidate, lock_response};
+use util::{format_duration, format_mismatch};
+use super::api::ApiFailure;
+use super::auth::{HttpSignature, sign_request, validate_auth_challenge};
 use resource::http::util;
-use resource::http::{HttpHeader, HttpBody, HttpQueryString};
+use resource::http::{HttpHeader, HttpBody, HttpQueryString, HttpResource, HttpBufferedResource};
+use resource::graphql::{GraphQlExpectation, graphql_validate};
+use resource::jsonrpc::{JsonRpcExpectation, jsonrpc_validate};
+use resource::http2::{Http2Response, http2_request};
+use resource::smtp::SmtpMail;
+use resource::sse::{EventStream, event_stream_timeout, event_stream_validate};
+use resource::websocket::{
+    WebSocket, WebSocketHandshake,
+    websocket_key, websocket_accept, websocket_session, websocket_validate
+};
 
 /// A HTTP request for API testing.
 ///
@@ -97,7 +119,7 @@ pub struct HttpRequest<A: HttpApi> {
     path: String,
     options: Options,
 
-    api_timed_out: bool,
+    api_failure: Option<ApiFailure>,
     dump_response: bool,
 
     provided_responses: Vec<Box<MockResponse + 'static>>,
@@ -105,6 +127,8 @@ pub struct HttpRequest<A: HttpApi> {
 
     request_headers: Headers,
     request_body: Option<HttpBody>,
+    query_params: Vec<(String, String)>,
+    signature: Option<HttpSignature>,
 
     expected_status: Option<StatusCode>,
     expected_headers: Headers,
@@ -112,6 +136,12 @@ pub struct HttpRequest<A: HttpApi> {
     compare_exact: bool,
 
     unexpected_headers: Vec<String>,
+    expected_challenge: Option<(String, Option<String>)>,
+    expected_graphql: Vec<GraphQlExpectation>,
+    expected_jsonrpc: Vec<JsonRpcExpectation>,
+
+    websocket: Option<WebSocket>,
+    event_stream: Option<EventStream>,
 
     run_on_drop: bool
 }
@@ -136,15 +166,77 @@ impl<A: HttpApi> HttpRequest<A> {
         self
     }
 
+    /// Removes a header which would otherwise be send with the request.
+    ///
+    /// This can be used to remove any of the headers provided by
+    /// `HttpApi::default_headers()`.
+    pub fn without_header<H: Header + HeaderFormat>(mut self) -> Self {
+        self.request_headers.remove::<H>();
+        self
+    }
+
+    /// Sets the `Authorization` header of the request to use HTTP Basic
+    /// authentication with the specified credentials.
+    pub fn with_basic_auth(self, username: &str, password: Option<&str>) -> Self {
+        self.with_header(Authorization(Basic {
+            username: username.to_string(),
+            password: password.map(|p| p.to_string())
+        }))
+    }
+
+    /// Sets the `Authorization` header of the request to use the specified
+    /// Bearer token.
+    pub fn with_bearer_token(self, token: &str) -> Self {
+        self.with_header(Authorization(Bearer {
+            token: token.to_string()
+        }))
+    }
+
+    /// Sends an API key in the request header with the specified `name`.
+    pub fn with_api_key_header(mut self, name: &str, key: &str) -> Self {
+        self.request_headers.set_raw(name.to_string(), vec![key.as_bytes().to_vec()]);
+        self
+    }
+
+    /// Sends an API key as the query string parameter with the specified
+    /// `name`.
+    ///
+    /// The parameter is appended to the request's query string once the
+    /// request is send.
+    pub fn with_api_key_query(mut self, name: &str, key: &str) -> Self {
+        self.query_params.retain(|&(ref param, _)| param != name);
+        self.query_params.push((name.to_string(), key.to_string()));
+        self
+    }
+
+    /// Signs the request with the specified signature.
+    ///
+    /// The signature is computed once the request is send, after its path,
+    /// query string and body have been finalized.
+    pub fn with_signature(mut self, signature: HttpSignature) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
     /// Sets the request's query string.
     ///
     /// This will override any existing query string previously set or derived
     /// from the request's path.
+    ///
+    /// Parameters provided by `HttpApi::default_query()` will still be
+    /// appended unless they are part of the query string.
     pub fn with_query(mut self, query: HttpQueryString) -> Self {
         self.path = util::path_with_query(self.path.as_str(), query);
         self
     }
 
+    /// Removes a query string parameter provided by `HttpApi::default_query()`
+    /// from the request.
+    pub fn without_query_param(mut self, name: &str) -> Self {
+        self.query_params.retain(|&(ref key, _)| key != name);
+        self
+    }
+
     /// Sets the request body.
     ///
     /// Also sets the `Content-Type` header of the request based on the type
@@ -203,6 +295,23 @@ impl<A: HttpApi> HttpRequest<A> {
         self
     }
 
+    /// Sets the expected authentication challenge of the response.
+    ///
+    /// This also sets the expected response status to `401 Unauthorized`.
+    ///
+    /// ### Test Failure
+    ///
+    /// If the `WWW-Authenticate` header is either missing from the response or
+    /// contains no challenge with the specified `scheme` and - if set - `realm`.
+    pub fn expected_auth_challenge(mut self, scheme: &str, realm: Option<&str>) -> Self {
+        self.expected_status = Some(StatusCode::Unauthorized);
+        self.expected_challenge = Some((
+            scheme.to_string(),
+            realm.map(|r| r.to_string())
+        ));
+        self
+    }
+
     /// Sets additional headers that should be present on the response.
     ///
     /// Use the `headers![...]` macro to easily create a vector containing
@@ -238,6 +347,14 @@ impl<A: HttpApi> HttpRequest<A> {
     /// This allows for simpler and more fine grained assertions against JSON
     /// responses.
     ///
+    /// ##### application/xml and text/xml
+    ///
+    /// XML documents are compared structurally, ignoring namespace prefixes
+    /// and the order of attributes. Child elements with the same name are
+    /// compared in order, but __additional attributes and child elements on
+    /// response elements are ignored__, as is their text when the expected
+    /// element has none.
+    ///
     /// ##### All other mime types
     ///
     /// These are compared on a byte by byte basis.
@@ -267,6 +384,12 @@ impl<A: HttpApi> HttpRequest<A> {
     /// In contrast to `HttpResponse::expected_body()` __additional keys on
     /// response objects are compared and will fail the test__.
     ///
+    /// ##### application/xml and text/xml
+    ///
+    /// In contrast to `HttpResponse::expected_body()` __additional attributes,
+    /// child elements and text on response elements are compared and will fail
+    /// the test__.
+    ///
     /// ##### All other mime types
     ///
     /// See `HttpResponse::expected_body()`.
@@ -280,6 +403,87 @@ impl<A: HttpApi> HttpRequest<A> {
         self
     }
 
+    /// Sets the expected GraphQL `data` of the response at the specified
+    /// `path`.
+    ///
+    /// The path consists of dot separated object keys and array indices, e.g.
+    /// `user.friends.0.name`, an empty path refers to the whole `data` object.
+    /// The value is compared in the same way as a JSON body set via
+    /// `HttpRequest::expected_body()`.
+    ///
+    /// ### Test Failure
+    ///
+    /// If the path does not exist within `data` or the value at the path does
+    /// not match the expected one.
+    pub fn expected_graphql_data(mut self, path: &str, value: JsonValue) -> Self {
+        self.expected_graphql.push(GraphQlExpectation::Data(path.to_string(), value));
+        self
+    }
+
+    /// Expects the GraphQL response to not contain any `errors`.
+    ///
+    /// ### Test Failure
+    ///
+    /// If the response contains one or more errors.
+    pub fn expected_no_graphql_errors(mut self) -> Self {
+        self.expected_graphql.push(GraphQlExpectation::NoErrors);
+        self
+    }
+
+    /// Expects the GraphQL response to contain an error with the specified
+    /// `message`.
+    ///
+    /// ### Test Failure
+    ///
+    /// If none of the returned errors has the message.
+    pub fn expected_graphql_error(mut self, message: &str) -> Self {
+        self.expected_graphql.push(GraphQlExpectation::ErrorMessage(message.to_string()));
+        self
+    }
+
+    /// Expects the GraphQL response to contain an error with the specified
+    /// `extensions.code`.
+    ///
+    /// ### Test Failure
+    ///
+    /// If none of the returned errors has the code.
+    pub fn expected_graphql_error_code(mut self, code: &str) -> Self {
+        self.expected_graphql.push(GraphQlExpectation::ErrorCode(code.to_string()));
+        self
+    }
+
+    /// Sets the expected `result` of the JSON-RPC call with the specified
+    /// `id`.
+    ///
+    /// The response to the call is looked up by its `id`, so responses to
+    /// batch requests can be returned in any order. The result is compared in
+    /// the same way as a JSON body set via `HttpRequest::expected_body()`.
+    ///
+    /// ### Test Failure
+    ///
+    /// If there is no response for the `id`, the response contains an error or
+    /// its result does not match the expected one.
+    pub fn expected_jsonrpc_result<I: Into<JsonValue>>(mut self, id: I, result: JsonValue) -> Self {
+        self.expected_jsonrpc.push(JsonRpcExpectation::Result(id.into(), result));
+        self
+    }
+
+    /// Sets the expected `error` object of the JSON-RPC call with the specified
+    /// `id`, e.g. `object!{ "code" => -32601 }`.
+    ///
+    /// The response to the call is looked up by its `id`, so responses to
+    /// batch requests can be returned in any order. The error is compared in
+    /// the same way as a JSON body set via `HttpRequest::expected_body()`.
+    ///
+    /// ### Test Failure
+    ///
+    /// If there is no response for the `id`, the response contains a result or
+    /// its error does not match the expected one.
+    pub fn expected_jsonrpc_error<I: Into<JsonValue>>(mut self, id: I, error: JsonValue) -> Self {
+        self.expected_jsonrpc.push(JsonRpcExpectation::Error(id.into(), error));
+        self
+    }
+
     /// Provides additional mocked responses from endpoints for the time of the
     /// currently executing request.
     ///
@@ -311,15 +515,65 @@ impl<A: HttpApi> HttpRequest<A> {
         self
     }
 
+    /// Upgrades the request to a WebSocket connection and holds the specified
+    /// `websocket` conversation with the API.
+    ///
+    /// The response is expected to have a status of `101 Switching Protocols`
+    /// unless another status is set via `HttpRequest::expected_status()`.
+    /// Expected headers are validated against the handshake response, an
+    /// expected body is ignored.
+    ///
+    /// # Test Failure
+    ///
+    /// If the handshake fails or the received frames do not match the
+    /// frames expected by the `WebSocket`.
+    pub fn with_websocket(mut self, websocket: WebSocket) -> Self {
+        self.websocket = Some(websocket);
+        self
+    }
+
+    /// Reads the response as a `text/event-stream` and validates the first
+    /// events against the events expected by the `EventStream`.
+    ///
+    /// Events are read incrementally, each event must be received within
+    /// the event timeout of the stream. The connection is closed once all
+    /// expected events have been received. Expected headers and the status
+    /// are validated against the response, an expected body is ignored.
+    ///
+    /// # Test Failure
+    ///
+    /// If an expected event is not received in time or does not match the
+    /// received event.
+    pub fn with_event_stream(mut self, event_stream: EventStream) -> Self {
+        self.event_stream = Some(event_stream);
+        self
+    }
+
+    /// Sets up an expected mail which must be sent to the noir `SmtpServer`
+    /// during the request.
+    ///
+    /// # Test Failure
+    ///
+    /// If no matching mail is received or the received mail does not fulfill
+    /// the expectations of the `SmtpMail`.
+    ///
+    /// Mails received without a matching expectation are reported as
+    /// unexpected.
+    pub fn expected_mail(mut self, mail: SmtpMail) -> Self {
+        self.provided_responses.push(Box::new(mail));
+        self
+    }
+
     /// Provides additional mocks which will be active for the time of the
     /// currently executing request.
     ///
     /// Use the `mocks![...]` macro to easily create a vector containing
     /// concrete types of the `MockProvider` trait for use with this method.
-    pub fn mocks<T: MockProvider + 'static>(mut self, mocks: Vec<T>) -> Self {
-        for res in mocks {
-            self.provided_mocks.push(Box::new(res));
-        }
+    ///
+    /// See `MockEnv`, `MockTempDir` and `MockClock` for the built-in mock
+    /// providers.
+    pub fn mocks(mut self, mut mocks: Vec<Box<MockProvider>>) -> Self {
+        self.provided_mocks.append(&mut mocks);
         self
     }
 
@@ -334,31 +588,22 @@ impl<A: HttpApi> HttpRequest<A> {
     fn run(&mut self) -> Result<(), String> {
 
         // Handle cases where the API test server did not start in time
-        if self.api_timed_out {
-            return Err(format!(
-                "\n{} {} \"{}\" {} {}{}\n\n",
-                "API Failure:".red().bold(),
-                "Server for".yellow(),
-                self.api.url().as_str().cyan(),
-                "did not respond within".yellow(),
-                "1000ms".green().bold(),
-                ".".yellow()
-            ));
+        if let Some(failure) = self.api_failure.take() {
+            return Err(format_api_failure(&self.api, failure));
         }
 
         // Limit ourselves to one test at a time in order to ensure correct
         // handling of mocked requests and responses
-        #[cfg_attr(feature = "clippy", allow(if_let_redundant_pattern_matching))]
-        let (errors, error_count, suppressed_count) = if let Ok(_) = REQUEST_LOCK.lock() {
-            self.send()
-
-        } else {
-            (vec![format!(
-                "{} {}",
-                "Internal Error:".red().bold(),
-                "Request lock failed.".yellow()
-
-            )], 1, 0)
+        //
+        // The lock is poisoned by requests which panicked, since those still
+        // tear down their API and mocks and reset the provided responses it
+        // is safe to continue with the next request.
+        let (errors, error_count, suppressed_count) = match REQUEST_LOCK.lock() {
+            Ok(_lock) => self.send(),
+            Err(err) => {
+                let _lock = err.into_inner();
+                self.send()
+            }
         };
 
         if !errors.is_empty() {
@@ -413,43 +658,137 @@ impl<A: HttpApi> HttpRequest<A> {
 
     fn send(&mut self) -> (Vec<String>, usize, usize) {
 
+        // Tear down the API and all mocks which were set up and reset the
+        // provided responses, even in case the request panics
+        let mut teardown = RequestTeardown {
+            api: self.api,
+            api_setup: false,
+            mocks: mem::replace(&mut self.provided_mocks, Vec::new()),
+            setup_count: 0,
+            torn_down: false
+        };
+
         // Provide responses to request interceptors
         ResponseProvider::provide(
             self.provided_responses.drain(0..).collect()
         );
 
-        for mock in &mut self.provided_mocks {
-            mock.setup();
-        }
+        teardown.setup_api();
+        teardown.setup_mocks();
 
         // Set up hyper client
         let mut client = Client::new();
         client.set_read_timeout(Some(self.options.api_request_timeout));
         client.set_write_timeout(Some(self.options.api_request_timeout));
 
+        // Event streams are never completed by the API, so the connection is
+        // closed after the expected events have been read
+        if self.event_stream.is_some() {
+            if self.request_headers.get_raw("Accept").is_none() {
+                self.request_headers.set_raw("Accept", vec![b"text/event-stream".to_vec()]);
+            }
+            if self.request_headers.get_raw("Connection").is_none() {
+                self.request_headers.set_raw("Connection", vec![b"close".to_vec()]);
+            }
+        }
+
         // Send request and validate response
-        let errors = match self.http_request(&mut client) {
-            Ok(response) => self.validate_response(response),
-            Err(_) => (vec![format!(
-                "{} {} {}{}",
-                "API Failure:".red().bold(),
-                "No response within".yellow(),
-                "1000ms".green().bold(),
-                ".".yellow()
-
-            )], 1, 0)
+        let timeout = self.options.api_request_timeout;
+        let response = if let Some(websocket) = self.websocket.take() {
+            self.websocket_request(websocket).map_err(|err| {
+                format_io_failure("WebSocket handshake failed:", err, timeout)
+            })
+
+        } else if let Some(event_stream) = self.event_stream.take() {
+            match self.http_request(&mut client) {
+                Ok(response) => Ok(self.validate_event_stream(response, event_stream)),
+                Err(err) => Err(format_http_failure(err, timeout))
+            }
+
+        } else if self.api.http_version() == HttpVersion::Http20 {
+            match self.http2_request() {
+                Ok(response) => {
+                    let status = response.status;
+                    Ok(self.validate_response(response, status, HttpVersion::Http20))
+                },
+                Err(err) => Err(format_io_failure("Request failed:", err, timeout))
+            }
+
+        } else {
+            match self.http_request(&mut client) {
+                Ok(response) => {
+                    let (status, version) = (response.status, response.version);
+                    Ok(self.validate_response(response, status, version))
+                },
+                Err(err) => Err(format_http_failure(err, timeout))
+            }
+        };
+
+        let (mut errors, mut error_count, suppressed_count) = match response {
+            Ok(response) => response,
+            Err(failure) => (vec![failure], 1, 0)
         };
 
-        for mock in &mut self.provided_mocks {
-            mock.teardown();
+        // Validate mock expectations once all mocks have been torn down
+        for mock in teardown.teardown_mocks() {
+            let mock_errors = mock.validate();
+            if !mock_errors.is_empty() {
+                let header = mock.validate_header(mock_errors.len());
+                let offset = errors.len() + 1;
+                error_count += mock_errors.len();
+                errors.push(format_response_errors(
+                    "Mock Failure:",
+                    header,
+                    offset,
+                    mock_errors
+                ));
+            }
         }
 
-        errors
+        (errors, error_count, suppressed_count)
 
     }
 
     fn http_request(&mut self, client: &mut Client) -> Result<Response, Error> {
 
+        let body = self.prepare_request();
+        let request = client.request(
+            self.method.clone(),
+            self.api.url_with_path(self.path.as_str()).as_str()
+
+        ).headers(
+            self.request_headers.clone()
+        );
+
+        if let Some(body) = body.as_ref() {
+            request.body(&body[..]).send()
+
+        } else {
+            request.send()
+        }
+
+    }
+
+    fn http2_request(&mut self) -> Result<Http2Response, IoError> {
+        let body = self.prepare_request();
+        http2_request(
+            self.api.host().as_str(),
+            &self.method,
+            self.path.as_str(),
+            &self.request_headers,
+            body.as_ref().map(|b| &b[..]),
+            self.options.api_request_timeout
+        )
+    }
+
+    fn prepare_request(&mut self) -> Option<Vec<u8>> {
+
+        // Append default and additional query string parameters
+        self.path = util::path_with_default_query(
+            self.path.as_str(),
+            &self.query_params[..]
+        );
+
         let (content_mime, body) = if let Some(body) = self.request_body.take() {
             util::http_body_into_parts(body)
 
@@ -466,24 +805,68 @@ impl<A: HttpApi> HttpRequest<A> {
             }
         }
 
-        let request = client.request(
-            self.method.clone(),
-            self.api.url_with_path(self.path.as_str()).as_str()
+        // Sign the request after its path and body have been finalized
+        if let Some(signature) = self.signature.as_ref() {
+            sign_request(
+                signature,
+                &mut self.request_headers,
+                format!("{}", self.method).as_str(),
+                self.path.as_str(),
+                body.as_ref().map_or(&[][..], |b| &b[..])
+            );
+        }
 
-        ).headers(
-            self.request_headers.clone()
-        );
+        body
 
-        if let Some(body) = body.as_ref() {
-            request.body(&body[..]).send()
+    }
+
+    fn validate_response<R: HttpResource>(
+        &mut self,
+        mut response: R,
+        status: StatusCode,
+        version: HttpVersion
+
+    ) -> (Vec<String>, usize, usize) {
+
+        // GraphQL and JSON-RPC expectations require the body to be read more
+        // than once
+        if !self.expected_graphql.is_empty() || !self.expected_jsonrpc.is_empty() {
+
+            let buffered = HttpBufferedResource::new(&mut response);
+            let mut errors = Vec::new();
+
+            if !self.expected_graphql.is_empty() {
+                errors.append(&mut graphql_validate(
+                    &self.expected_graphql[..],
+                    buffered.body(),
+                    self.options.json_compare_depth
+                ));
+            }
+
+            if !self.expected_jsonrpc.is_empty() {
+                errors.append(&mut jsonrpc_validate(
+                    &self.expected_jsonrpc[..],
+                    buffered.body(),
+                    self.options.json_compare_depth
+                ));
+            }
+
+            self.validate_resource(buffered, status, version, errors)
 
         } else {
-            request.send()
+            self.validate_resource(response, status, version, Vec::new())
         }
 
     }
 
-    fn validate_response(&mut self, mut response: Response) -> (Vec<String>, usize, usize) {
+    fn validate_resource<R: HttpResource>(
+        &mut self,
+        mut response: R,
+        status: StatusCode,
+        version: HttpVersion,
+        mut additional_errors: Vec<String>
+
+    ) -> (Vec<String>, usize, usize) {
 
         // Request dumping
         let mut errors = Vec::new();
@@ -491,12 +874,12 @@ impl<A: HttpApi> HttpRequest<A> {
             util::dump_http_resource(
                 &mut errors,
                 &mut response,
-                "Response"
+                "Response",
+                Some(version)
             );
         }
 
         // Validate Response
-        let status = response.status;
         errors.append(&mut util::validate_http_resource(
             "Response",
             self.expected_status,
@@ -509,6 +892,146 @@ impl<A: HttpApi> HttpRequest<A> {
             &self.options
         ));
 
+        if let Some((ref scheme, ref realm)) = self.expected_challenge {
+            validate_auth_challenge(
+                &mut errors,
+                response.headers(),
+                scheme.as_str(),
+                realm.as_ref().map(|r| r.as_str())
+            );
+        }
+
+        errors.append(&mut additional_errors);
+        self.validate_with_requests(errors)
+
+    }
+
+    fn validate_event_stream(
+        &mut self,
+        mut response: Response,
+        event_stream: EventStream
+
+    ) -> (Vec<String>, usize, usize) {
+
+        let status = response.status;
+        let mut errors = util::validate_http_resource(
+            "Response",
+            self.expected_status,
+            &self.expected_headers,
+            &mut self.unexpected_headers,
+            &None,
+            &mut response,
+            Some(status),
+            self.compare_exact,
+            &self.options
+        );
+
+        if status.is_success() {
+
+            // The event timeout only applies once the headers were received
+            response.get_ref().set_read_timeout(Some(event_stream_timeout(
+                &event_stream,
+                self.options.api_request_timeout
+
+            ))).ok();
+
+            errors.append(&mut event_stream_validate(
+                &event_stream,
+                &mut response,
+                &self.options
+            ));
+        }
+
+        // Disconnect from the stream before validating any requests
+        drop(response);
+
+        self.validate_with_requests(errors)
+
+    }
+
+    fn websocket_request(&mut self, websocket: WebSocket) -> Result<(Vec<String>, usize, usize), IoError> {
+
+        let body = self.prepare_request();
+
+        let mut stream = try!(TcpStream::connect(self.api.host().as_str()));
+        try!(stream.set_read_timeout(Some(self.options.api_request_timeout)));
+        try!(stream.set_write_timeout(Some(self.options.api_request_timeout)));
+
+        let key = websocket_key();
+        let mut headers = self.request_headers.clone();
+        headers.set_raw("Host", vec![self.api.host().into_bytes()]);
+        headers.set_raw("Upgrade", vec![b"websocket".to_vec()]);
+        headers.set_raw("Connection", vec![b"Upgrade".to_vec()]);
+        headers.set_raw("Sec-WebSocket-Key", vec![key.clone().into_bytes()]);
+        headers.set_raw("Sec-WebSocket-Version", vec![b"13".to_vec()]);
+
+        if let Some(body) = body.as_ref() {
+            headers.set_raw("Content-Length", vec![format!("{}", body.len()).into_bytes()]);
+        }
+
+        try!(stream.write_all(format!(
+            "{} {} HTTP/1.1\r\n{}\r\n",
+            self.method,
+            self.path,
+            headers
+
+        ).as_bytes()));
+
+        if let Some(body) = body {
+            try!(stream.write_all(&body[..]));
+        }
+
+        // Read the handshake byte by byte in order to not consume any frames
+        let mut head = Vec::new();
+        let mut byte = [0; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            try!(stream.read_exact(&mut byte));
+            head.push(byte[0]);
+        }
+
+        let mut handshake = try!(WebSocketHandshake::parse(&head[..]));
+        let status = handshake.status;
+
+        let mut errors = util::validate_http_resource(
+            "Response",
+            Some(self.expected_status.unwrap_or(StatusCode::SwitchingProtocols)),
+            &self.expected_headers,
+            &mut self.unexpected_headers,
+            &None,
+            &mut handshake,
+            Some(status),
+            self.compare_exact,
+            &self.options
+        );
+
+        if status == StatusCode::SwitchingProtocols {
+
+            let expected = websocket_accept(key.as_str());
+            let actual = handshake.header("Sec-WebSocket-Accept").unwrap_or("");
+            if actual != expected {
+                errors.push(format!(
+                    "{} {}",
+                    "Response".yellow(),
+                    format_mismatch(
+                        "header \"Sec-WebSocket-Accept\"",
+                        expected.as_str(),
+                        actual
+                    )
+                ));
+
+            } else {
+                websocket_session(&websocket, &self.options).run(&mut stream, true);
+                errors.append(&mut websocket_validate(&websocket));
+            }
+
+        }
+
+        Ok(self.validate_with_requests(errors))
+
+    }
+
+    fn validate_with_requests(&mut self, mut errors: Vec<String>) -> (Vec<String>, usize, usize) {
+
         // Validate Resource Requests
         let (mut response_errors, total_error_count) = self.validate_requests(errors.len());
 
@@ -546,17 +1069,22 @@ impl<A: HttpApi> HttpRequest<A> {
 
         // Validate requests to all provided and unprovided responses
         for (
-            mut response,
+            response,
             response_index,
-            request_index
+            response_rank,
+            request_rank
 
         ) in ResponseProvider::provided_responses() {
 
-            let errors = response.validate(response_index, request_index);
+            // Ranks are only used for validating the order of the response,
+            // errors are numbered in the order the responses were provided
+            let mut response = lock_response(&response);
+            let errors = response.validate(response_rank, request_rank);
             if !errors.is_empty() {
                 let header = response.validate_header(errors.len());
                 total_error_count += errors.len();
                 response_errors.push(format_response_errors(
+                    "Request Failure:",
                     header,
                     index_offset + response_index + 1,
                     errors
@@ -565,16 +1093,13 @@ impl<A: HttpApi> HttpRequest<A> {
 
         }
 
-        for mut request in ResponseProvider::additional_requests() {
+        for (mut request, candidates) in ResponseProvider::additional_requests() {
             if let Some(error) = request.validate() {
-                response_errors.push(error);
+                response_errors.push(format_candidates(error, candidates));
                 total_error_count += 1;
             }
         }
 
-        // Reset the global response provider for the next test
-        ResponseProvider::reset();
-
         (response_errors, total_error_count)
 
     }
@@ -600,14 +1125,90 @@ impl<A: HttpApi> Drop for HttpRequest<A> {
 
 
 // Helper ---------------------------------------------------------------------
+struct RequestTeardown<A: HttpApi> {
+    api: A,
+    api_setup: bool,
+    mocks: Vec<Box<MockProvider + 'static>>,
+    setup_count: usize,
+    torn_down: bool
+}
+
+impl<A: HttpApi> RequestTeardown<A> {
+
+    fn setup_api(&mut self) {
+        self.api.setup();
+        self.api_setup = true;
+    }
+
+    fn setup_mocks(&mut self) {
+        for mock in &mut self.mocks {
+            mock.setup();
+            self.setup_count += 1;
+        }
+    }
+
+    fn teardown_mocks(&mut self) -> &mut [Box<MockProvider + 'static>] {
+        if !self.torn_down {
+            self.torn_down = true;
+            for mock in self.mocks.iter_mut().take(self.setup_count) {
+                mock.teardown();
+            }
+        }
+        &mut self.mocks[..]
+    }
+
+}
+
+impl<A: HttpApi> Drop for RequestTeardown<A> {
+    fn drop(&mut self) {
+        self.teardown_mocks();
+        if self.api_setup {
+            self.api.teardown();
+        }
+
+        // Reset the global response provider for the next request
+        ResponseProvider::reset();
+    }
+}
+
+fn format_http_failure(err: Error, timeout: Duration) -> String {
+    match err {
+        Error::Io(err) => format_io_failure("Request failed:", err, timeout),
+        err => format_failure("Invalid response:", format!("{}", err))
+    }
+}
+
+fn format_io_failure(title: &str, err: IoError, timeout: Duration) -> String {
+    match err.kind() {
+        // Read timeouts are reported differently across platforms
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => format!(
+            "{} {} {}{}",
+            "API Failure:".red().bold(),
+            "No response within".yellow(),
+            format_duration(timeout).green().bold(),
+            ".".yellow()
+        ),
+        _ => format_failure(title, format!("{}", err))
+    }
+}
+
+fn format_failure(title: &str, error: String) -> String {
+    format!(
+        "{} {} {}",
+        "API Failure:".red().bold(),
+        title.yellow(),
+        error.red().bold()
+    )
+}
+
 fn format_response_errors(
-    header: String, offset: usize, errors: Vec<String>
+    title: &str, header: String, offset: usize, errors: Vec<String>
 
 ) -> String {
 
     let mut formatted = format!(
         "{} {}",
-        "Request Failure:".red().bold(),
+        title.red().bold(),
         header
     );
 
@@ -642,28 +1243,118 @@ fn format_response_errors(
 }
 
 
+fn format_candidates(error: String, candidates: Vec<MockCandidate>) -> String {
+
+    if candidates.is_empty() {
+        return error;
+    }
+
+    let mut formatted = format!(
+        "{}\n\n    {}",
+        error,
+        "Closest provided response(s):".yellow()
+    );
+
+    for (header, reasons) in candidates {
+        formatted.push_str(format!("\n\n    - {}", header).as_str());
+        for reason in reasons {
+            formatted.push_str(format!("\n\n        - {}", reason).as_str());
+        }
+    }
+
+    formatted
+
+}
+
+
 // Internal -------------------------------------------------------------------
+pub fn format_api_failure<A: HttpApi>(api: &A, failure: ApiFailure) -> String {
+
+    let mut report = match failure.deadline {
+        Some(deadline) => format!(
+            "\n{} {} \"{}\" {} {}{}\n",
+            "API Failure:".red().bold(),
+            "Server for".yellow(),
+            api.url().as_str().cyan(),
+            "did not respond within".yellow(),
+            format_duration(deadline).green().bold(),
+            ".".yellow()
+        ),
+        None => format!(
+            "\n{} {} \"{}\" {}\n",
+            "API Failure:".red().bold(),
+            "Server for".yellow(),
+            api.url().as_str().cyan(),
+            "could not be started.".yellow()
+        )
+    };
+
+    // Last readiness probe or process error
+    if let Some(error) = failure.error {
+        report.push_str(format!("\n    {}\n", error).as_str());
+    }
+
+    // Output captured from external API processes
+    if let Some(output) = failure.output {
+        report.push_str(format!("\n    {}\n", output).as_str());
+    }
+
+    // Padding
+    report.push_str("\n");
+
+    report
+
+}
+
+pub fn format_api_stop_failure<A: HttpApi>(api: &A, deadline: Duration) -> String {
+    format!(
+        "\n{} {} \"{}\" {} {}{}\n\n",
+        "API Failure:".red().bold(),
+        "Server for".yellow(),
+        api.url().as_str().cyan(),
+        "did not stop within".yellow(),
+        format_duration(deadline).green().bold(),
+        ".".yellow()
+    )
+}
+
 pub fn http_request<A: HttpApi>(
     api: A,
     method: Method,
     path: &'static str,
-    api_timed_out: bool
+    api_failure: Option<ApiFailure>
 
 ) -> HttpRequest<A> {
+
+    // Default headers from the API
+    let mut request_headers = Headers::new();
+    for header in api.default_headers() {
+        let (name, value) = util::http_header_into_tuple(header);
+        request_headers.set_raw(name, vec![value]);
+    }
+
+    // Default query string parameters from the API
+    let query_params = form_urlencoded::parse(
+        api.default_query().to_string().as_bytes()
+
+    ).into_owned().collect();
+
     HttpRequest {
         api: api,
         method: method,
         path: path.to_string(),
-        options: Default::default(),
+        options: api.default_options(),
 
-        api_timed_out: api_timed_out,
+        api_failure: api_failure,
         dump_response: false,
 
         provided_responses: Vec::new(),
         provided_mocks: Vec::new(),
 
-        request_headers: Headers::new(),
+        request_headers: request_headers,
         request_body: None,
+        query_params: query_params,
+        signature: None,
 
         expected_status: None,
         expected_headers: Headers::new(),
@@ -671,13 +1362,20 @@ pub fn http_request<A: HttpApi>(
         compare_exact: false,
 
         unexpected_headers: Vec::new(),
+        expected_challenge: None,
+        expected_graphql: Vec::new(),
+        expected_jsonrpc: Vec::new(),
+
+        websocket: None,
+        event_stream: None,
 
         run_on_drop: true
     }
 }
 
 lazy_static! {
-    static ref REQUEST_LOCK: Arc<Mutex<()>> = {
+    // Also taken by unit tests which use the global response provider
+    pub static ref REQUEST_LOCK: Arc<Mutex<()>> = {
         Arc::new(Mutex::new(()))
     };
 }
@@ -22,7 +22,12 @@ extern crate url;
 #[macro_use]
 extern crate json;
 extern crate rand;
+extern crate regex;
+extern crate flate2;
+extern crate openssl;
+extern crate openssl_sys;
 extern crate hyper;
+extern crate libc;
 extern crate colored;
 extern crate httparse;
 #[macro_use]
@@ -40,14 +45,27 @@ mod util;
 
 // Exports --------------------------------------------------------------------
 pub use api::options::Options;
-pub use api::http::{HttpApi, HttpRequest};
+pub use api::http::{
+    HttpApi, HttpApiProbe, HttpApiProcess, HttpRequest, HttpSignature
+};
 pub use mock::{
-    MockResponse, MockRequest, MockProvider,
-    MockResponseProvider, MockRequestResponse
+    MockResponse, MockRequest, MockProvider, MockOrder, MockFault,
+    MockResponseProvider, MockRequestResponse,
+    MockUpgrade, MockConnection,
+    MockEnv, MockTempDir, MockClock,
+    MockRedis, RedisServer,
+    SmtpServer
 };
+pub use mock::http::HttpProxy;
+pub use resource::jsonrpc::JsonRpcCall;
+pub use resource::smtp::{SmtpMail, SmtpMessage};
+pub use resource::sse::{EventStream, ServerEvent};
+pub use resource::websocket::WebSocket;
 pub use resource::http::{
     HttpEndpoint,
     HttpResponse,
+    HttpReply,
+    HttpRequest as HttpInterceptedRequest,
     HttpHeader,
     HttpBody,
     HttpQueryString,
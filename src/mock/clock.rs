@@ -0,0 +1,137 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::MockProvider;
+
+
+/// A macro for reading the current time in a way that can be controlled by
+/// tests.
+///
+/// During testing (`#[cfg(test)]`) the macro returns the time of the
+/// currently active `MockClock`, falling back to the system clock if no
+/// clock is active.
+///
+/// Outside of testing the macro will be compiled out and reduce itself to a
+/// normal `std::time::SystemTime::now()` call.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # #[macro_use] extern crate noir;
+/// # fn main() {
+/// let now = system_time!();
+/// # }
+/// ```
+#[macro_export]
+macro_rules! system_time {
+    () => {
+        if cfg!(test) {
+            $crate::mock::clock::mocked_system_time()
+
+        } else {
+            ::std::time::SystemTime::now()
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn mocked_system_time() -> SystemTime {
+    match ACTIVE_CLOCK.lock() {
+        Ok(clock) => match *clock {
+            Some(ref time) => *time.lock().unwrap(),
+            None => SystemTime::now()
+        },
+        _ => SystemTime::now()
+    }
+}
+
+
+/// A mock provider for a virtual clock which is read via the `system_time!()`
+/// macro for the time of a request.
+///
+/// The clock can be cloned before it is passed to the request, and advanced
+/// from the test while the request is running.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # #[macro_use] extern crate noir;
+/// use std::time::Duration;
+/// use noir::MockClock;
+///
+/// # fn main() {
+/// let clock = MockClock::from_unix_timestamp(1_000_000);
+/// clock.advance(Duration::from_secs(60));
+///
+/// let mocks = mocks![clock.clone()];
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct MockClock {
+    time: Arc<Mutex<SystemTime>>
+}
+
+impl MockClock {
+
+    /// Creates a new clock which starts at the specified `time`.
+    pub fn new(time: SystemTime) -> MockClock {
+        MockClock {
+            time: Arc::new(Mutex::new(time))
+        }
+    }
+
+    /// Creates a new clock which starts at the specified number of seconds
+    /// since the unix epoch.
+    pub fn from_unix_timestamp(seconds: u64) -> MockClock {
+        MockClock::new(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    /// Returns the current time of the clock.
+    pub fn now(&self) -> SystemTime {
+        *self.time.lock().unwrap()
+    }
+
+    /// Sets the current time of the clock.
+    pub fn set(&self, time: SystemTime) {
+        *self.time.lock().unwrap() = time;
+    }
+
+    /// Advances the clock by the specified `duration`.
+    pub fn advance(&self, duration: Duration) {
+        let mut time = self.time.lock().unwrap();
+        *time = *time + duration;
+    }
+
+}
+
+impl MockProvider for MockClock {
+
+    fn setup(&mut self) {
+        *ACTIVE_CLOCK.lock().unwrap() = Some(self.time.clone());
+    }
+
+    fn teardown(&mut self) {
+        *ACTIVE_CLOCK.lock().unwrap() = None;
+    }
+
+}
+
+
+// Statics --------------------------------------------------------------------
+lazy_static! {
+    static ref ACTIVE_CLOCK: Arc<Mutex<Option<Arc<Mutex<SystemTime>>>>> = {
+        Arc::new(Mutex::new(None))
+    };
+}
+
@@ -0,0 +1,95 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::env;
+use std::ffi::OsString;
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::MockProvider;
+
+
+/// A mock provider which overrides environment variables for the time of a
+/// request.
+///
+/// The previous values of all overridden variables are restored once the
+/// request has completed.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # #[macro_use] extern crate noir;
+/// use noir::MockEnv;
+///
+/// # fn main() {
+/// let mocks = mocks![
+///     MockEnv::new().with_var("API_KEY", "secret").without_var("DEBUG")
+/// ];
+/// # }
+/// ```
+pub struct MockEnv {
+    vars: Vec<(String, Option<String>)>,
+    previous: Vec<(String, Option<OsString>)>
+}
+
+impl MockEnv {
+
+    /// Creates a new, empty environment mock.
+    pub fn new() -> MockEnv {
+        MockEnv {
+            vars: Vec::new(),
+            previous: Vec::new()
+        }
+    }
+
+    /// Sets the environment variable `key` to `value` during the request.
+    pub fn with_var<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.vars.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Removes the environment variable `key` during the request.
+    pub fn without_var<K: Into<String>>(mut self, key: K) -> Self {
+        self.vars.push((key.into(), None));
+        self
+    }
+
+}
+
+impl Default for MockEnv {
+    fn default() -> MockEnv {
+        MockEnv::new()
+    }
+}
+
+impl MockProvider for MockEnv {
+
+    fn setup(&mut self) {
+        for &(ref key, ref value) in &self.vars {
+            self.previous.push((key.to_string(), env::var_os(key)));
+            match *value {
+                Some(ref value) => env::set_var(key, value),
+                None => env::remove_var(key)
+            }
+        }
+    }
+
+    fn teardown(&mut self) {
+        // Restore in reverse order so repeated keys end up with their
+        // original value
+        for (key, value) in self.previous.drain(0..).rev() {
+            match value {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key)
+            }
+        }
+    }
+
+}
+
@@ -8,21 +8,35 @@
 
 // STD Dependencies -----------------------------------------------------------
 use std::cmp;
+use std::mem;
+use std::thread;
+use std::cell::Cell;
 use std::time::Duration;
-use std::error::Error as ErrorTrait;
+use std::collections::VecDeque;
 use std::net::{SocketAddr, Shutdown};
 use std::io::{Error, ErrorKind, Write, Read};
 
 
 // External Dependencies ------------------------------------------------------
 use hyper;
-use httparse;
 use hyper::net::{NetworkConnector, NetworkStream};
 
 
 // Internal Dependencies ------------------------------------------------------
-use mock::MockResponseProvider;
-use resource::http::HttpRequest;
+use mock::{MockFault, MockRequest, ResponseProvider};
+use self::parser::{ParsedRequest, MalformedHttpRequest, parse_request};
+
+
+// Modules --------------------------------------------------------------------
+mod parser;
+mod proxy;
+mod server;
+mod tls;
+
+
+// Re-Exports -----------------------------------------------------------------
+pub use self::proxy::HttpProxy;
+pub use self::server::loopback_port;
 
 
 /// A macro for intercepting `hyper::Client::new()` calls made during tests.
@@ -89,20 +103,73 @@ struct MockStream {
     host: String,
     port: u16,
     request: Vec<u8>,
+    responses: VecDeque<(Result<Vec<u8>, Error>, MockFault)>,
     response: Result<Vec<u8>, Error>,
-    response_index: usize
+    response_index: usize,
+    fault: MockFault,
+    read_timeout: Cell<Option<Duration>>
 }
 
 impl MockStream {
+
     pub fn new(host: &str, port: u16) -> MockStream {
         MockStream {
             host: host.to_string(),
             port: port,
             request: Vec::new(),
+            responses: VecDeque::new(),
             response: Ok(Vec::new()),
-            response_index: 0
+            response_index: 0,
+            fault: MockFault::default(),
+            read_timeout: Cell::new(None)
         }
     }
+
+    fn respond(&mut self, request: Box<MockRequest>) -> Result<(), Error> {
+
+        // Intercepted streams cannot be taken over by upgrades
+        let (response, fault, _) = try!(ResponseProvider::request(request));
+
+        // Pipelined requests are answered in order once the previous response
+        // has been read completely
+        self.responses.push_back((response, fault.unwrap_or_else(MockFault::default)));
+        Ok(())
+
+    }
+
+    fn next_response(&mut self) {
+        let completed = match self.response {
+            Ok(ref response) => self.response_index == response.len(),
+            Err(_) => false
+        };
+        if completed {
+            if let Some((response, fault)) = self.responses.pop_front() {
+                self.response = response;
+                self.response_index = 0;
+                self.fault = fault;
+            }
+        }
+    }
+
+    fn wait_for_delay(&mut self) -> Result<(), Error> {
+        if let Some(delay) = self.fault.delay.take() {
+            match self.read_timeout.get() {
+                // Time out the read but keep the remaining delay for any
+                // subsequent reads
+                Some(timeout) if timeout < delay => {
+                    thread::sleep(timeout);
+                    self.fault.delay = Some(delay - timeout);
+                    return Err(Error::new(
+                        ErrorKind::WouldBlock,
+                        "noir: Mocked read timed out."
+                    ));
+                },
+                _ => thread::sleep(delay)
+            }
+        }
+        Ok(())
+    }
+
 }
 
 impl Write for MockStream {
@@ -114,29 +181,32 @@ impl Write for MockStream {
 
     fn flush(&mut self) -> Result<(), Error> {
 
-        let mut headers = [httparse::EMPTY_HEADER; 16];
-        let mut req = httparse::Request::new(&mut headers);
-
-        match req.parse(&self.request[..]) {
-            Ok(httparse::Status::Complete(size)) => {
-
-                let request = Box::new(HttpRequest::new(
-                    self.host.to_string(),
-                    self.port,
-                    req,
-                    self.request[size..].to_vec()
-                ));
-
-                match MockResponseProvider::response_from_request(request) {
-                    Ok(response) => {
-                        self.response = response;
-                        Ok(())
-                    },
-                    Err(err) => Err(err)
-                }
+        // Respond to all complete requests, incomplete ones stay buffered until
+        // the remaining data has been written
+        loop {
+            match parse_request(self.host.as_str(), self.port, &self.request[..]) {
+                ParsedRequest::Complete(request, size) => {
+                    self.request.drain(0..size);
+                    try!(self.respond(Box::new(request)));
+                },
+                ParsedRequest::Incomplete => return Ok(()),
+                ParsedRequest::Invalid(error) => {
 
-            },
-            _ => unreachable!()
+                    self.request.clear();
+
+                    // Track the malformed request for reporting
+                    ResponseProvider::request(Box::new(
+                        MalformedHttpRequest::new(self.host.as_str(), self.port, error)
+
+                    )).ok();
+
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "noir: Malformed request."
+                    ));
+
+                }
+            }
         }
 
     }
@@ -145,28 +215,41 @@ impl Write for MockStream {
 
 impl Read for MockStream {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        match self.response.as_ref() {
-            Ok(response) => {
 
-                let bytes_available = response.len() - self.response_index;
-                let bytes_to_read = buf.len();
-                let bytes_read = cmp::min(bytes_to_read, bytes_available);
+        self.next_response();
+        try!(self.wait_for_delay());
 
-                let bytes = &response[
-                    self.response_index..self.response_index + bytes_read
-                ];
+        // Errors are only returned once and then count as completed, so that
+        // any pipelined responses behind them can still be read
+        let response = try!(mem::replace(&mut self.response, Ok(Vec::new())));
 
-                for (index, b) in bytes.iter().enumerate() {
-                    buf[index] = *b;
-                }
+        // Interrupt the stream once the fault's limit is reached
+        let response_len = self.fault.limit.map_or(response.len(), |limit| {
+            cmp::min(limit, response.len())
+        });
+
+        let bytes_available = response_len - self.response_index;
+        let bytes_read = cmp::min(buf.len(), bytes_available);
+
+        for (index, b) in response[
+            self.response_index..self.response_index + bytes_read
 
-                self.response_index += bytes_read;
+        ].iter().enumerate() {
+            buf[index] = *b;
+        }
+
+        self.response_index += bytes_read;
 
-                Ok(bytes_read)
+        let interrupted = bytes_available == 0 && response_len < response.len();
+        self.response = Ok(response);
 
+        match self.fault.error {
+            Some(kind) if interrupted => {
+                Err(Error::new(kind, "noir: Mocked stream interrupted."))
             },
-            Err(err) => Err(Error::new(err.kind(), err.description()))
+            _ => Ok(bytes_read)
         }
+
     }
 }
 
@@ -176,7 +259,8 @@ impl NetworkStream for MockStream {
         Err(Error::new(ErrorKind::NotConnected, "noir: Address not mocked."))
     }
 
-    fn set_read_timeout(&self, _: Option<Duration>) -> Result<(), Error> {
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        self.read_timeout.set(timeout);
         Ok(())
     }
 
@@ -190,3 +274,102 @@ impl NetworkStream for MockStream {
 
 }
 
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+
+    use std::io::{Error, ErrorKind, Read, Write};
+    use api::http::REQUEST_LOCK;
+    use mock::{MockResponse, ResponseProvider};
+    use resource::http::HttpEndpoint;
+    use super::MockStream;
+
+    #[derive(Copy, Clone)]
+    struct Example;
+    impl HttpEndpoint for Example {
+
+        fn hostname(&self) -> &'static str {
+            "example.com"
+        }
+
+        fn port(&self) -> u16 {
+            443
+        }
+
+    }
+
+    #[test]
+    fn test_pipelined_responses() {
+
+        let _lock = match REQUEST_LOCK.lock() {
+            Ok(lock) => lock,
+            Err(err) => err.into_inner()
+        };
+
+        ResponseProvider::provide(vec![
+            Box::new(Example.get("/one").with_body("One")) as Box<MockResponse>,
+            Box::new(Example.get("/two").with_body("Two")) as Box<MockResponse>
+        ]);
+
+        let mut stream = MockStream::new("example.com", 443);
+        stream.write_all(
+            b"GET /one HTTP/1.1\r\nHost: example.com\r\n\r\nGET /two HTTP/1.1\r\nHost: example.com\r\n\r\n"
+
+        ).unwrap();
+        stream.flush().unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        ResponseProvider::provided_responses();
+        ResponseProvider::additional_requests();
+        ResponseProvider::reset();
+
+        let one = response.find("One").expect("First response is missing.");
+        let two = response.find("Two").expect("Second response is missing.");
+        assert_eq!(response.matches("HTTP/1.1 200 OK").count(), 2);
+        assert!(one < two);
+
+    }
+
+    #[test]
+    fn test_pipelined_responses_after_error() {
+
+        let _lock = match REQUEST_LOCK.lock() {
+            Ok(lock) => lock,
+            Err(err) => err.into_inner()
+        };
+
+        ResponseProvider::provide(vec![
+            Box::new(Example.get("/one").with_error(
+                Error::new(ErrorKind::ConnectionReset, "Connection reset")
+
+            )) as Box<MockResponse>,
+            Box::new(Example.get("/two").with_body("Two")) as Box<MockResponse>
+        ]);
+
+        let mut stream = MockStream::new("example.com", 443);
+        stream.write_all(
+            b"GET /one HTTP/1.1\r\nHost: example.com\r\n\r\nGET /two HTTP/1.1\r\nHost: example.com\r\n\r\n"
+
+        ).unwrap();
+        stream.flush().unwrap();
+
+        let mut buffer = [0; 64];
+        let error = stream.read(&mut buffer).unwrap_err();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        ResponseProvider::provided_responses();
+        ResponseProvider::additional_requests();
+        ResponseProvider::reset();
+
+        assert_eq!(error.kind(), ErrorKind::ConnectionReset);
+        assert!(response.contains("Two"), "Second response is missing.");
+
+    }
+
+}
@@ -0,0 +1,261 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::str;
+use std::any::Any;
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+use httparse;
+use url::Url;
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::MockRequest;
+use resource::http::HttpRequest;
+
+
+// Noir Internal --------------------------------------------------------------
+pub enum ParsedRequest {
+    Incomplete,
+    Complete(HttpRequest, usize),
+    Invalid(String)
+}
+
+pub fn parse_request(hostname: &str, port: u16, data: &[u8]) -> ParsedRequest {
+
+    // Grow the header storage until all headers of the request fit
+    let mut header_count = 16;
+    loop {
+
+        let mut headers = vec![httparse::EMPTY_HEADER; header_count];
+        let mut req = httparse::Request::new(&mut headers);
+
+        match req.parse(data) {
+            Ok(httparse::Status::Complete(size)) => {
+
+                let body = request_body(&req, &data[size..]);
+                return match body {
+                    Ok(Some((body, body_size))) => {
+                        match HttpRequest::new(hostname.to_string(), port, req, body) {
+                            Ok(mut request) => {
+                                into_origin_form(&mut request);
+                                ParsedRequest::Complete(request, size + body_size)
+                            },
+                            Err(err) => ParsedRequest::Invalid(err)
+                        }
+                    },
+                    Ok(None) => ParsedRequest::Incomplete,
+                    Err(err) => ParsedRequest::Invalid(err)
+                };
+
+            },
+            Ok(httparse::Status::Partial) => return ParsedRequest::Incomplete,
+            Err(httparse::Error::TooManyHeaders) => header_count *= 2,
+            Err(err) => return ParsedRequest::Invalid(format!("{}", err))
+        }
+
+    }
+
+}
+
+/// A request which could not be parsed by the mock transport.
+pub struct MalformedHttpRequest {
+    url: String,
+    error: String
+}
+
+impl MalformedHttpRequest {
+    pub fn new(hostname: &str, port: u16, error: String) -> MalformedHttpRequest {
+        MalformedHttpRequest {
+            url: match port {
+                443 => format!("https://{}", hostname),
+                80 => format!("http://{}", hostname),
+                _ => format!("http://{}:{}", hostname, port)
+            },
+            error: error
+        }
+    }
+}
+
+impl MockRequest for MalformedHttpRequest {
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+
+    fn validate(&mut self) -> Option<String> {
+        Some(format!(
+            "{} {} {} \"{}\"{} {}{}",
+            "Request Failure:".red().bold(),
+            "Malformed".yellow(),
+            "request to".yellow(),
+            self.url.cyan(),
+            ":".yellow(),
+            self.error.red().bold(),
+            ".".yellow()
+        ))
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn request_body(
+    req: &httparse::Request,
+    data: &[u8]
+
+) -> Result<Option<(Vec<u8>, usize)>, String> {
+
+    let header = |name: &str| {
+        req.headers.iter().find(|header| {
+            header.name.to_lowercase() == name
+
+        }).and_then(|header| str::from_utf8(header.value).ok())
+    };
+
+    if let Some(encoding) = header("transfer-encoding") {
+        if encoding.to_lowercase().contains("chunked") {
+            return decode_chunked_body(data);
+        }
+    }
+
+    if let Some(length) = header("content-length") {
+        let length = try!(length.trim().parse::<usize>().map_err(|_| {
+            format!("Invalid Content-Length \"{}\"", length)
+        }));
+
+        if data.len() < length {
+            Ok(None)
+
+        } else {
+            Ok(Some((data[..length].to_vec(), length)))
+        }
+
+    } else {
+        // Requests without any framing have no body
+        Ok(Some((Vec::new(), 0)))
+    }
+
+}
+
+fn decode_chunked_body(data: &[u8]) -> Result<Option<(Vec<u8>, usize)>, String> {
+
+    let mut body = Vec::new();
+    let mut offset = 0;
+
+    loop {
+
+        let line_end = match find_line_end(&data[offset..]) {
+            Some(index) => offset + index,
+            None => return Ok(None)
+        };
+
+        // Ignore any chunk extensions
+        let line = String::from_utf8_lossy(&data[offset..line_end]).to_string();
+        let size_hex = line.split(';').next().unwrap_or("").trim().to_string();
+        let size = try!(usize::from_str_radix(size_hex.as_str(), 16).map_err(|_| {
+            format!("Invalid chunk size \"{}\"", size_hex)
+        }));
+
+        offset = line_end + 2;
+
+        // Skip any trailers until the final empty line
+        if size == 0 {
+            loop {
+                let line_end = match find_line_end(&data[offset..]) {
+                    Some(index) => offset + index,
+                    None => return Ok(None)
+                };
+
+                let is_empty = line_end == offset;
+                offset = line_end + 2;
+
+                if is_empty {
+                    return Ok(Some((body, offset)));
+                }
+            }
+        }
+
+        // Sizes which exceed the address space can never be received
+        let end = try!(offset.checked_add(size).and_then(|end| {
+            end.checked_add(2)
+
+        }).ok_or_else(|| format!("Invalid chunk size \"{}\"", size_hex)));
+
+        if data.len() < end {
+            return Ok(None);
+        }
+
+        body.extend_from_slice(&data[offset..end - 2]);
+
+        if &data[end - 2..end] != b"\r\n" {
+            return Err("Missing chunk terminator".to_string());
+        }
+
+        offset = end;
+
+    }
+
+}
+
+fn into_origin_form(request: &mut HttpRequest) {
+
+    // Requests made via a proxy use the absolute URL of their target
+    if !request.path.starts_with("http://") && !request.path.starts_with("https://") {
+        return;
+    }
+
+    if let Ok(url) = Url::parse(request.path.as_str()) {
+        if let (Some(hostname), Some(port)) = (url.host_str(), url.port_or_known_default()) {
+            request.hostname = hostname.to_string();
+            request.port = port;
+        }
+        request.path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string()
+        };
+    }
+
+}
+
+fn find_line_end(data: &[u8]) -> Option<usize> {
+    data.windows(2).position(|w| w == b"\r\n")
+}
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+
+    use super::decode_chunked_body;
+
+    #[test]
+    fn test_chunked_body() {
+        assert_eq!(
+            decode_chunked_body(b"5\r\nHello\r\n6;ext=1\r\n World\r\n0\r\n\r\n"),
+            Ok(Some((b"Hello World".to_vec(), 32)))
+        );
+        assert_eq!(decode_chunked_body(b"5\r\nHel"), Ok(None));
+    }
+
+    #[test]
+    fn test_chunked_body_size_overflow() {
+        assert_eq!(
+            decode_chunked_body(b"ffffffffffffffff\r\nHello\r\n0\r\n\r\n"),
+            Err("Invalid chunk size \"ffffffffffffffff\"".to_string())
+        );
+    }
+
+}
@@ -0,0 +1,211 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::str;
+use std::fs;
+use std::env;
+use std::thread;
+use std::io::{Error, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+
+// Internal Dependencies ------------------------------------------------------
+use super::server::{serve_connection, serve_stream};
+use super::tls::{ca_certificate_pem, certificate_pem, temp_path};
+
+
+/// A local HTTP forward proxy which routes all requests made through it to the
+/// responses provided for the corresponding `HttpEndpoint`.
+///
+/// Requests are matched against the endpoints by the host and port of their
+/// original URL, which allows an API to be tested without changing any of its
+/// upstream URLs. Both absolute-form requests and `CONNECT` tunnels are
+/// supported.
+///
+/// TLS connections made through `CONNECT` tunnels, as well as those made
+/// directly to `HttpEndpoint::loopback_tls_url()`, are intercepted with a
+/// certificate which is generated for the hostname of the endpoint on demand.
+/// These certificates are signed by a certificate authority that is generated
+/// once and shared by all processes through a file in the temporary
+/// directory. In order for the API to accept them, it needs to trust the
+/// authority stored at `HttpProxy::ca_certificate_path()`.
+///
+/// The proxy is started on first use and kept running for all tests.
+///
+/// APIs which are spawned via `HttpApiProcess` can be configured to use the
+/// proxy with `HttpApiProcess::with_proxy()`.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # extern crate noir;
+/// use noir::HttpProxy;
+///
+/// # fn main() {
+/// std::env::set_var("HTTP_PROXY", HttpProxy::url());
+/// # }
+/// ```
+pub struct HttpProxy;
+
+impl HttpProxy {
+
+    /// Returns the port of the proxy on the local loopback interface.
+    pub fn port() -> u16 {
+        *PROXY_PORT
+    }
+
+    /// Returns the url of the proxy, suitable for use with the `HTTP_PROXY`
+    /// environment variable.
+    pub fn url() -> String {
+        format!("http://127.0.0.1:{}", HttpProxy::port())
+    }
+
+    /// Returns the PEM encoded certificate which is used to intercept TLS
+    /// connections to the specified `hostname`.
+    pub fn certificate(hostname: &str) -> String {
+        String::from_utf8(certificate_pem(hostname).0).unwrap()
+    }
+
+    /// Returns the PEM encoded certificate of the authority which signs all
+    /// of the certificates used to intercept TLS connections.
+    pub fn ca_certificate() -> String {
+        String::from_utf8(ca_certificate_pem()).unwrap()
+    }
+
+    /// Returns the path of a PEM file which contains the certificate of the
+    /// authority returned by `HttpProxy::ca_certificate()`.
+    ///
+    /// The file is written once per process to a fixed path in the temporary
+    /// directory and is suitable for use as a trusted CA bundle, e.g. via the
+    /// `SSL_CERT_FILE` environment variable.
+    ///
+    /// # Panics
+    ///
+    /// If the file cannot be written.
+    pub fn ca_certificate_path() -> String {
+        CA_CERTIFICATE_PATH.clone()
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn write_ca_certificate() -> String {
+
+    // All processes share the same authority, so the file is replaced
+    // atomically in order to never expose a partially written certificate
+    let path = env::temp_dir().join("noir-proxy-ca.pem");
+    let tmp_path = temp_path("noir-proxy-ca", "pem");
+
+    fs::File::create(&tmp_path).and_then(|mut file| {
+        file.write_all(&ca_certificate_pem()[..])
+
+    }).and_then(|_| fs::rename(&tmp_path, &path)).expect(
+        "noir: Failed to write certificate file."
+    );
+
+    path.to_string_lossy().to_string()
+
+}
+
+fn start_proxy() -> u16 {
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect(
+        "noir: Failed to bind proxy server."
+    );
+
+    let local_port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                thread::spawn(move || {
+                    serve_proxy_connection(stream).ok();
+                });
+            }
+        }
+    });
+
+    local_port
+
+}
+
+fn serve_proxy_connection(mut stream: TcpStream) -> Result<(), Error> {
+
+    let mut request = Vec::new();
+
+    // Wait for the head of the first request
+    let head_size = match try!(read_head(&mut stream, &mut request)) {
+        Some(size) => size,
+        None => return Ok(())
+    };
+
+    let target = connect_target(&request[..head_size]);
+    let (hostname, port) = match target {
+        Some(target) => target,
+
+        // Absolute-form requests are routed by their URL
+        None => return serve_connection(stream, request, "", 0)
+    };
+
+    try!(stream.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n"));
+    request.drain(0..head_size);
+    serve_stream(stream, request, hostname.as_str(), port)
+
+}
+
+fn read_head(stream: &mut TcpStream, request: &mut Vec<u8>) -> Result<Option<usize>, Error> {
+
+    let mut buffer = [0; 4096];
+    loop {
+
+        if let Some(index) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            return Ok(Some(index + 4));
+        }
+
+        match try!(stream.read(&mut buffer)) {
+            0 => return Ok(None),
+            size => request.extend_from_slice(&buffer[..size])
+        }
+
+    }
+
+}
+
+fn connect_target(head: &[u8]) -> Option<(String, u16)> {
+
+    let head = match str::from_utf8(head) {
+        Ok(head) => head,
+        Err(_) => return None
+    };
+
+    let mut parts = head.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("CONNECT"), Some(authority)) => {
+            let mut split = authority.rsplitn(2, ':');
+            match (split.next(), split.next()) {
+                (Some(port), Some(hostname)) => {
+                    port.parse::<u16>().ok().map(|port| {
+                        (hostname.to_string(), port)
+                    })
+                },
+                _ => Some((authority.to_string(), 443))
+            }
+        },
+        _ => None
+    }
+
+}
+
+
+// Statics --------------------------------------------------------------------
+lazy_static! {
+    static ref PROXY_PORT: u16 = start_proxy();
+    static ref CA_CERTIFICATE_PATH: String = write_ca_certificate();
+}
+
@@ -0,0 +1,206 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::cmp;
+use std::thread;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+
+// External Dependencies ------------------------------------------------------
+use hyper::header::{Connection, ConnectionOption};
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::{MockFault, ResponseProvider};
+use super::parser::{ParsedRequest, MalformedHttpRequest, parse_request};
+use super::tls::{BufferedStream, accept_tls};
+
+
+// Noir Internal --------------------------------------------------------------
+pub fn loopback_port(hostname: &'static str, port: u16) -> u16 {
+
+    // The port map is always left in a consistent state, so it can still be
+    // used after another thread panicked while holding the lock
+    let servers = match LOOPBACK_SERVERS.lock() {
+        Ok(servers) => servers,
+        Err(err) => err.into_inner()
+    };
+
+    // Servers are started once per endpoint and kept running for all tests
+    let mut servers = servers.borrow_mut();
+    *servers.entry(format!("{}:{}", hostname, port)).or_insert_with(|| {
+        start_server(hostname, port)
+    })
+
+}
+
+pub fn serve_stream(
+    mut stream: TcpStream,
+    mut request: Vec<u8>,
+    hostname: &str,
+    port: u16
+
+) -> Result<(), Error> {
+
+    if request.is_empty() {
+        let mut buffer = [0; 4096];
+        match try!(stream.read(&mut buffer)) {
+            0 => return Ok(()),
+            size => request.extend_from_slice(&buffer[..size])
+        }
+    }
+
+    // TLS handshakes start with a handshake record, these are intercepted
+    // with a generated certificate for the endpoint's hostname
+    if request[0] == 0x16 {
+        let stream = BufferedStream::new(request, stream);
+        match accept_tls(stream, hostname) {
+            Ok(stream) => serve_connection(stream, Vec::new(), hostname, port),
+            Err(err) => {
+                ResponseProvider::request(Box::new(MalformedHttpRequest::new(
+                    hostname,
+                    port,
+                    format!("TLS handshake failed ({})", err)
+
+                ))).ok();
+                Err(err)
+            }
+        }
+
+    } else {
+        serve_connection(stream, request, hostname, port)
+    }
+
+}
+
+pub fn serve_connection<S: Read + Write>(
+    mut stream: S,
+    mut request: Vec<u8>,
+    hostname: &str,
+    port: u16
+
+) -> Result<(), Error> {
+
+    let mut buffer = [0; 4096];
+
+    loop {
+
+        // Respond to all complete requests on the connection
+        loop {
+            match parse_request(hostname, port, &request[..]) {
+                ParsedRequest::Complete(req, size) => {
+
+                    request.drain(0..size);
+
+                    let close = req.header::<Connection>().map_or(false, |c| {
+                        c.contains(&ConnectionOption::Close)
+                    });
+
+                    match try!(ResponseProvider::request(Box::new(req))) {
+                        (Ok(response), fault, upgrade) => {
+                            try!(write_response(&mut stream, &response[..], fault));
+
+                            // Upgraded connections are served by the response
+                            if let Some(mut upgrade) = upgrade {
+                                return upgrade.serve(&mut stream);
+
+                            } else if close {
+                                return Ok(());
+                            }
+                        },
+                        // Responses with errors close the connection
+                        (Err(err), _, _) => return Err(err)
+                    }
+                },
+                ParsedRequest::Incomplete => break,
+                ParsedRequest::Invalid(error) => {
+                    ResponseProvider::request(Box::new(
+                        MalformedHttpRequest::new(hostname, port, error)
+
+                    )).ok();
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "noir: Malformed request."
+                    ));
+                }
+            }
+        }
+
+        match try!(stream.read(&mut buffer)) {
+            0 => return Ok(()),
+            size => request.extend_from_slice(&buffer[..size])
+        }
+
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn start_server(hostname: &'static str, port: u16) -> u16 {
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect(
+        "noir: Failed to bind loopback server."
+    );
+
+    let local_port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                thread::spawn(move || {
+                    serve_stream(stream, Vec::new(), hostname, port).ok();
+                });
+            }
+        }
+    });
+
+    local_port
+
+}
+
+fn write_response<S: Read + Write>(
+    stream: &mut S,
+    response: &[u8],
+    fault: Option<MockFault>
+
+) -> Result<(), Error> {
+
+    let fault = fault.unwrap_or_else(MockFault::default);
+    if let Some(delay) = fault.delay {
+        thread::sleep(delay);
+    }
+
+    // Interrupted responses close the connection once their limit is reached
+    if let Some(limit) = fault.limit {
+        try!(stream.write_all(&response[..cmp::min(limit, response.len())]));
+        try!(stream.flush());
+        Err(Error::new(
+            fault.error.unwrap_or(ErrorKind::ConnectionAborted),
+            "noir: Mocked stream interrupted."
+        ))
+
+    } else {
+        try!(stream.write_all(response));
+        stream.flush()
+    }
+
+}
+
+
+// Statics --------------------------------------------------------------------
+lazy_static! {
+    static ref LOOPBACK_SERVERS: Arc<Mutex<RefCell<HashMap<String, u16>>>> = {
+        Arc::new(Mutex::new(RefCell::new(HashMap::new())))
+    };
+}
+
@@ -0,0 +1,282 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::cmp;
+use std::env;
+use std::fs;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::io::{Error, ErrorKind, Read, Write};
+
+
+// External Dependencies ------------------------------------------------------
+use rand;
+use openssl::crypto::hash::Type;
+use openssl::crypto::pkey::PKey;
+use openssl::ssl::{SslContext, SslMethod, SslStream};
+use openssl::x509::{X509, X509Generator};
+use openssl::x509::extension::{Extension, KeyUsageOption};
+use openssl_sys::{X509_get_subject_name, X509_set_issuer_name, X509_sign, EVP_sha256};
+
+
+// Noir Internal --------------------------------------------------------------
+pub fn ca_certificate_pem() -> Vec<u8> {
+    CERTIFICATE_AUTHORITY.0.clone()
+}
+
+pub fn temp_path(name: &str, extension: &str) -> PathBuf {
+    env::temp_dir().join(format!(
+        "{}-{:016x}.{}",
+        name,
+        rand::random::<u64>(),
+        extension
+    ))
+}
+
+pub fn certificate_pem(hostname: &str) -> (Vec<u8>, Vec<u8>) {
+
+    let certificates = match CERTIFICATES.lock() {
+        Ok(certificates) => certificates,
+        Err(err) => err.into_inner()
+    };
+
+    // Certificates are generated on demand once per hostname and re-used for
+    // all connections
+    let mut certificates = certificates.borrow_mut();
+    certificates.entry(hostname.to_string()).or_insert_with(|| {
+        generate_certificate(hostname)
+
+    }).clone()
+
+}
+
+pub fn accept_tls<S: Read + Write>(
+    stream: S,
+    hostname: &str
+
+) -> Result<SslStream<S>, Error> {
+
+    let (cert_pem, key_pem) = certificate_pem(hostname);
+    let cert = try!(X509::from_pem(&mut &cert_pem[..]).map_err(tls_error));
+    let pkey = try!(PKey::private_key_from_pem(&mut &key_pem[..]).map_err(tls_error));
+
+    let mut context = try!(SslContext::new(SslMethod::Sslv23).map_err(tls_error));
+    try!(context.set_certificate(&cert).map_err(tls_error));
+    try!(context.set_private_key(&pkey).map_err(tls_error));
+
+    SslStream::accept(&context, stream).map_err(tls_error)
+
+}
+
+/// A stream which returns already buffered data before reading from the
+/// underlying stream.
+pub struct BufferedStream<S: Read + Write> {
+    buffer: Vec<u8>,
+    offset: usize,
+    stream: S
+}
+
+impl<S: Read + Write> BufferedStream<S> {
+    pub fn new(buffer: Vec<u8>, stream: S) -> BufferedStream<S> {
+        BufferedStream {
+            buffer: buffer,
+            offset: 0,
+            stream: stream
+        }
+    }
+}
+
+impl<S: Read + Write> Read for BufferedStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if self.offset < self.buffer.len() {
+            let size = cmp::min(buf.len(), self.buffer.len() - self.offset);
+            buf[..size].copy_from_slice(&self.buffer[self.offset..self.offset + size]);
+            self.offset += size;
+            Ok(size)
+
+        } else {
+            self.stream.read(buf)
+        }
+    }
+}
+
+impl<S: Read + Write> Write for BufferedStream<S> {
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.stream.flush()
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn load_certificate_authority() -> (Vec<u8>, Vec<u8>) {
+
+    // The authority is shared by all processes via a fixed file in the
+    // temporary directory, so that its certificate only needs to be trusted
+    // once and no new files are left behind by every test run
+    let path = env::temp_dir().join("noir-proxy-ca.key");
+    if let Some(authority) = read_certificate_authority(&path) {
+        return authority;
+    }
+
+    let (cert_pem, key_pem) = generate_certificate_authority();
+    let mut pem = cert_pem.clone();
+    pem.extend_from_slice(&key_pem[..]);
+
+    // The fully written file is hard linked into place, which fails in case
+    // another process claimed the path first; its authority is used instead
+    let tmp_path = temp_path("noir-proxy-ca", "key");
+    fs::File::create(&tmp_path).and_then(|mut file| {
+        file.write_all(&pem[..])
+
+    }).expect("noir: Failed to write certificate authority file.");
+
+    // Unparseable files are replaced
+    let linked = fs::hard_link(&tmp_path, &path).is_ok() || (
+        read_certificate_authority(&path).is_none()
+            && fs::rename(&tmp_path, &path).is_ok()
+    );
+
+    fs::remove_file(&tmp_path).ok();
+
+    if linked {
+        (cert_pem, key_pem)
+
+    } else {
+        read_certificate_authority(&path).expect(
+            "noir: Failed to read certificate authority file."
+        )
+    }
+
+}
+
+fn read_certificate_authority(path: &Path) -> Option<(Vec<u8>, Vec<u8>)> {
+
+    let mut pem = Vec::new();
+    if fs::File::open(path).and_then(|mut file| file.read_to_end(&mut pem)).is_err() {
+        return None;
+    }
+
+    match (X509::from_pem(&mut &pem[..]), PKey::private_key_from_pem(&mut &pem[..])) {
+        (Ok(cert), Ok(pkey)) => {
+            let mut cert_pem = Vec::new();
+            let mut key_pem = Vec::new();
+            cert.write_pem(&mut cert_pem).unwrap();
+            pkey.write_pem(&mut key_pem).unwrap();
+            Some((cert_pem, key_pem))
+        },
+        _ => None
+    }
+
+}
+
+fn generate_certificate_authority() -> (Vec<u8>, Vec<u8>) {
+
+    let generator = X509Generator::new()
+        .set_bitlength(2048)
+        .set_valid_period(3650)
+        .add_name("CN".to_string(), "noir Proxy CA".to_string())
+        .add_extension(Extension::OtherStr(
+            "basicConstraints".to_string(),
+            "critical,CA:TRUE".to_string()
+        ))
+        .add_extension(Extension::KeyUsage(vec![
+            KeyUsageOption::KeyCertSign,
+            KeyUsageOption::CRLSign
+        ]))
+        .set_sign_hash(Type::SHA256);
+
+    let (cert, pkey) = generator.generate().expect(
+        "noir: Failed to generate TLS certificate authority."
+    );
+
+    let mut cert_pem = Vec::new();
+    let mut key_pem = Vec::new();
+    cert.write_pem(&mut cert_pem).unwrap();
+    pkey.write_pem(&mut key_pem).unwrap();
+
+    (cert_pem, key_pem)
+
+}
+
+#[allow(unsafe_code)]
+fn generate_certificate(hostname: &str) -> (Vec<u8>, Vec<u8>) {
+
+    let generator = X509Generator::new()
+        .set_bitlength(2048)
+        .set_valid_period(365)
+        .add_name("CN".to_string(), hostname.to_string())
+        // Directly addressed loopback servers are reached via their IP
+        .add_extension(Extension::OtherStr(
+            "subjectAltName".to_string(),
+            format!("DNS:{},IP:127.0.0.1", hostname)
+        ))
+        .set_sign_hash(Type::SHA256);
+
+    let (cert, pkey) = generator.generate().expect(
+        "noir: Failed to generate TLS certificate."
+    );
+
+    let ca_cert = X509::from_pem(&mut &CERTIFICATE_AUTHORITY.0[..]).unwrap();
+    let ca_pkey = PKey::private_key_from_pem(&mut &CERTIFICATE_AUTHORITY.1[..]).unwrap();
+
+    // The generator only creates self signed certificates and the openssl
+    // crate offers no API for signing with another key, so the issuer is
+    // replaced with the authority and the certificate is signed again.
+    //
+    // All handles are owned by the certificates and keys above which outlive
+    // the calls, the subject name is copied by X509_set_issuer_name.
+    let signed = unsafe {
+        X509_set_issuer_name(
+            cert.get_handle(),
+            X509_get_subject_name(ca_cert.get_handle())
+
+        ) == 1 && X509_sign(
+            cert.get_handle(),
+            ca_pkey.get_handle(),
+            EVP_sha256()
+
+        ) > 0
+    };
+
+    if !signed {
+        panic!("noir: Failed to sign TLS certificate.");
+    }
+
+    let mut cert_pem = Vec::new();
+    let mut key_pem = Vec::new();
+    cert.write_pem(&mut cert_pem).unwrap();
+    pkey.write_pem(&mut key_pem).unwrap();
+
+    (cert_pem, key_pem)
+
+}
+
+fn tls_error<E: ::std::error::Error>(err: E) -> Error {
+    Error::new(ErrorKind::Other, format!("noir: TLS error ({}).", err))
+}
+
+
+// Statics --------------------------------------------------------------------
+lazy_static! {
+    static ref CERTIFICATE_AUTHORITY: (Vec<u8>, Vec<u8>) = {
+        load_certificate_authority()
+    };
+    static ref CERTIFICATES: Arc<Mutex<RefCell<HashMap<String, (Vec<u8>, Vec<u8>)>>>> = {
+        Arc::new(Mutex::new(RefCell::new(HashMap::new())))
+    };
+}
+
@@ -8,17 +8,34 @@
 
 // STD Dependencies -----------------------------------------------------------
 use std::any::Any;
-use std::io::Error;
+use std::time::Duration;
+use std::io::{Error, ErrorKind, Read, Write};
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
 
 
 // Internal Dependencies ------------------------------------------------------
 #[doc(hidden)]
 pub mod http;
+#[doc(hidden)]
+#[macro_use]
+pub mod clock;
+mod env;
 mod provider;
+mod redis;
+mod smtp;
+mod tempdir;
 
 
 // Exports --------------------------------------------------------------------
-pub use self::provider::ResponseProvider;
+pub use self::clock::MockClock;
+pub use self::env::MockEnv;
+pub use self::redis::{MockRedis, RedisServer};
+pub use self::smtp::SmtpServer;
+pub use self::tempdir::MockTempDir;
+pub use self::provider::{ResponseProvider, MockCandidate, lock_response};
 
 
 /// A trait for implementation of a response provided to a concrete type of
@@ -40,25 +57,115 @@ pub trait MockResponse: Send {
     /// `false` once `MockResponse::respond()` was called.
     fn matches(&self, &Box<MockRequest>) -> bool;
 
+    /// Compare the response against a `MockRequest` for which no matching
+    /// response was found and return a description of the response together
+    /// with the reasons why it did not match.
+    ///
+    /// The responses with the fewest reasons are listed as the closest
+    /// candidates in the error message of the unmatched request.
+    ///
+    /// Responses which should not be listed as candidates for the request
+    /// (e.g. because they belong to another endpoint) should return `None`.
+    ///
+    /// Defaults to `None`.
+    fn mismatches(&self, &Box<MockRequest>) -> Option<(String, Vec<String>)> {
+        None
+    }
+
     /// Called when the implementation of `MockResponse::matches` return `true`.
     ///
     /// Should return a `Result` with either the response body or a `std::io::Error`.
     fn respond(&mut self, Box<MockRequest>) -> MockRequestResponse;
 
+    /// Called after `MockResponse::respond()`, return a fault which should be
+    /// injected into the stream of the last response.
+    ///
+    /// Defaults to `None`.
+    fn fault(&self) -> Option<MockFault> {
+        None
+    }
+
+    /// Called after `MockResponse::respond()`, return a protocol which takes
+    /// over the connection once the last response has been sent.
+    ///
+    /// Upgrades are only performed by mocked endpoints which are served via
+    /// local sockets, i.e. loopback servers and the `HttpProxy`.
+    ///
+    /// Defaults to `None`.
+    fn upgrade(&mut self) -> Option<Box<MockUpgrade>> {
+        None
+    }
+
     /// If the response has a matching `MockRequest`, compare the two and return
     /// a vector of error messages listing any differences.
     ///
     /// ### Test Failure
     ///
     /// If the return vector contains any error messages.
+    ///
+    /// The indices passed in are relative to the other responses which share
+    /// the same `MockResponse::order()` and were fetched during the test.
     fn validate(&mut self, response_index: usize, request_index: usize) -> Vec<String>;
 
+    /// Return the order in which the response is expected to be fetched
+    /// relative to the other responses provided to the test.
+    ///
+    /// Defaults to `MockOrder::Strict`.
+    fn order(&self) -> MockOrder {
+        MockOrder::Strict
+    }
+
     /// Return a header for use with the formatted error values returned by
     /// `MockResponse::validate()`.
     fn validate_header(&self, error_count: usize) -> String;
 
 }
 
+/// The order in which a `MockResponse` is expected to be fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockOrder {
+    /// The response must be fetched in the order it was provided, relative to
+    /// all other strictly ordered responses.
+    Strict,
+
+    /// The response can be fetched at any point during the test.
+    Unordered,
+
+    /// The response must be fetched in the order it was provided, relative to
+    /// the other responses of the same named group only.
+    Group(String)
+}
+
+/// A fault injected into the stream of a mocked response.
+#[derive(Debug, Clone, Default)]
+pub struct MockFault {
+    /// A delay before the first byte of the response can be read.
+    ///
+    /// If the delay exceeds the read timeout of the stream, reads fail with
+    /// `ErrorKind::WouldBlock` once the timeout has elapsed.
+    pub delay: Option<Duration>,
+
+    /// The number of response bytes after which the stream is interrupted.
+    pub limit: Option<usize>,
+
+    /// The error which is returned once the `limit` is reached, if `None` the
+    /// stream is closed instead.
+    pub error: Option<ErrorKind>
+}
+
+/// A trait for implementation of a protocol which takes over the connection of
+/// a `MockResponse`, e.g. after a `101 Switching Protocols` response.
+pub trait MockUpgrade: Send {
+
+    /// Serve the protocol on the `stream` until the connection is closed.
+    fn serve(&mut self, stream: &mut MockConnection) -> Result<(), Error>;
+
+}
+
+/// A bidirectional stream of a connection which is served by a `MockUpgrade`.
+pub trait MockConnection: Read + Write {}
+impl<T: Read + Write> MockConnection for T {}
+
 /// A trait for implementation of a request matched against concrete types of
 /// `MockResponse`.
 pub trait MockRequest: Send + Any {
@@ -138,6 +245,30 @@ pub trait MockProvider: Send {
     /// Called after each individual `HttpRequest` in a test has completed.
     fn teardown(&mut self);
 
+    /// Called after `MockProvider::teardown()`, return a vector of error
+    /// messages listing any expectations of the mock which were not met.
+    ///
+    /// ### Test Failure
+    ///
+    /// If the return vector contains any error messages.
+    ///
+    /// Defaults to an empty vector.
+    fn validate(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Return a header for use with the formatted error values returned by
+    /// `MockProvider::validate()`.
+    fn validate_header(&self, error_count: usize) -> String {
+        format!(
+            "{} {} {} {}",
+            "Mock".cyan(),
+            "returned".yellow(),
+            format!("{}", error_count).red().bold(),
+            "error(s)".yellow()
+        )
+    }
+
 }
 
 /// A response to a request made against a mocked endpoint.
@@ -167,7 +298,7 @@ impl MockResponseProvider {
         request: Box<MockRequest>
 
     ) -> Result<MockRequestResponse, Error> where Self: Sized {
-        ResponseProvider::request(request)
+        ResponseProvider::request(request).map(|(response, _, _)| response)
     }
 
 }
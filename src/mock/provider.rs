@@ -8,123 +8,182 @@
 
 // STD Dependencies -----------------------------------------------------------
 use std::cell::RefCell;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::io::{Error, ErrorKind};
 
 
 // Internal Dependencies ------------------------------------------------------
-use mock::{MockRequest, MockResponse};
+use mock::{MockRequest, MockResponse, MockOrder, MockFault, MockUpgrade};
 
 
 // Global Mocked Response Provider --------------------------------------------
+pub type MockCandidate = (String, Vec<String>);
+
+/// A provided response which is shared with the requests it responds to, so
+/// that user handlers run without holding the lock of the provider.
+pub type SharedResponse = Arc<Mutex<Box<MockResponse + 'static>>>;
+
 pub struct ResponseProvider {
     response_index: usize,
-    provided_responses: Vec<(Box<MockResponse + 'static>, usize, usize)>,
+    provided_responses: Vec<(SharedResponse, usize, Option<usize>)>,
     request_index: usize,
-    additional_requests: Vec<Box<MockRequest>>
+    additional_requests: Vec<(Box<MockRequest>, Vec<MockCandidate>)>
 }
 
 impl ResponseProvider {
 
+    /// Discards all responses and requests which were not yet validated.
     pub fn reset() {
-        let handler = PROVIDER_INSTANCE.clone();
-        match handler.lock() {
-            Ok(handler) => {
-                let mut provider = handler.borrow_mut();
-                provider.response_index = 0;
-                provider.request_index = 0;
-            },
-            _ => unreachable!()
-        };
+        let handler = lock_provider();
+        let mut provider = handler.borrow_mut();
+        provider.response_index = 0;
+        provider.provided_responses.clear();
+        provider.request_index = 0;
+        provider.additional_requests.clear();
     }
 
     pub fn provide(resources: Vec<Box<MockResponse + 'static>>) {
-        let handler = PROVIDER_INSTANCE.clone();
-        match handler.lock() {
-            Ok(handler) => {
-                let mut provider = handler.borrow_mut();
-                for resource in resources {
-                    let index = provider.response_index;
-                    provider.provided_responses.push((resource, index, 0));
-                    provider.response_index += 1;
-                }
-            },
-            _ => unreachable!()
-        };
+        let handler = lock_provider();
+        let mut provider = handler.borrow_mut();
+        for resource in resources {
+            let index = provider.response_index;
+            provider.provided_responses.push((Arc::new(Mutex::new(resource)), index, None));
+            provider.response_index += 1;
+        }
     }
 
-    #[cfg_attr(feature = "clippy", allow(needless_return))]
-    pub fn provided_responses() -> Vec<(Box<MockResponse + 'static>, usize, usize)> {
-        let handler = PROVIDER_INSTANCE.clone();
-        return match handler.lock() {
-            Ok(handler) => {
-                let mut provider = handler.borrow_mut();
-                return provider.provided_responses.drain(0..).collect();
-            },
-            _ => Vec::new()
-        };
+    /// Returns all provided responses together with the index in which they
+    /// were provided and their ranks for the validation of their order.
+    pub fn provided_responses() -> Vec<(SharedResponse, usize, usize, usize)> {
+
+        let handler = lock_provider();
+        let mut provider = handler.borrow_mut();
+        let responses = provider.provided_responses.drain(0..).collect::<Vec<_>>();
+        let orders = responses.iter().map(|&(ref response, _, request_index)| {
+            (lock_response(response).order(), request_index)
+
+        }).collect::<Vec<(MockOrder, Option<usize>)>>();
+
+        responses.into_iter().enumerate().map(|(i, (response, response_index, request_index))| {
+            match (&orders[i].0, request_index) {
+                // Unordered and unfetched responses are never validated for
+                // their order
+                (&MockOrder::Unordered, _) | (_, None) => {
+                    (response, response_index, response_index, response_index)
+                },
+                // Other responses are ranked against the fetched responses
+                // which share the same order
+                (order, Some(request_index)) => {
+                    let response_rank = orders[..i].iter().filter(|&&(ref o, r)| {
+                        o == order && r.is_some()
+
+                    }).count();
+                    let request_rank = orders.iter().filter(|&&(ref o, r)| {
+                        o == order && r.map_or(false, |r| r < request_index)
+
+                    }).count();
+                    (response, response_index, response_rank, request_rank)
+                }
+            }
+
+        }).collect()
+
     }
 
-    #[cfg_attr(feature = "clippy", allow(needless_return))]
-    pub fn additional_requests() -> Vec<Box<MockRequest + 'static>> {
-        let handler = PROVIDER_INSTANCE.clone();
-        return match handler.lock() {
-            Ok(handler) => {
-                let mut provider = handler.borrow_mut();
-                return provider.additional_requests.drain(0..).collect();
-            },
-            _ => Vec::new()
-        };
+    pub fn additional_requests() -> Vec<(Box<MockRequest + 'static>, Vec<MockCandidate>)> {
+        let handler = lock_provider();
+        let mut provider = handler.borrow_mut();
+        provider.additional_requests.drain(0..).collect()
     }
 
-    #[cfg_attr(feature = "clippy", allow(needless_return))]
     pub fn request(
         request: Box<MockRequest>
 
-    ) -> Result<Result<Vec<u8>, Error>, Error> {
+    ) -> Result<(Result<Vec<u8>, Error>, Option<MockFault>, Option<Box<MockUpgrade>>), Error> {
+
+        let shared: SharedResponse;
+        let mut response = {
 
-        let handler = PROVIDER_INSTANCE.clone();
-        return match handler.lock() {
-            Ok(handler) => {
+            let handler = lock_provider();
+            let mut provider = handler.borrow_mut();
 
-                let mut provider = handler.borrow_mut();
+            // Increase internal request counter for order validation
+            let index = provider.request_index;
+            provider.request_index += 1;
 
-                // Increase internal request counter for order validation
-                let index = provider.request_index;
-                provider.request_index += 1;
+            // Check all responses for a potential match
+            let position = provider.provided_responses.iter().position(|&(ref response, _, _)| {
+                lock_response(response).matches(&request)
+            });
 
-                // Check all responses for a potential match
-                for &mut(
-                    ref mut response,
-                    _,
-                    ref mut request_index
+            match position {
+                Some(position) => {
 
-                ) in &mut provider.provided_responses {
-                    if response.matches(&request) {
-                        *request_index = index;
-                        return Ok(response.respond(request));
+                    let entry = &mut provider.provided_responses[position];
+
+                    // Keep the index of the first request for responses which
+                    // are fetched repeatedly
+                    if entry.2.is_none() {
+                        entry.2 = Some(index);
                     }
-                }
 
-                // Track requests which are missing their response
-                provider.additional_requests.push(request);
+                    // Lock the response before the provider is released, so
+                    // no other request can match it in the meantime
+                    shared = entry.0.clone();
+                    lock_response(&shared)
+
+                },
+                None => {
 
-                Err(Error::new(
-                    ErrorKind::ConnectionRefused,
-                    "noir: No response provided in test."
-                ))
+                    // Collect the closest candidates for the unmatched request
+                    let mut candidates = provider.provided_responses.iter().filter_map(|&(ref response, _, _)| {
+                        lock_response(response).mismatches(&request)
+
+                    }).collect::<Vec<MockCandidate>>();
+
+                    candidates.sort_by_key(|candidate| candidate.1.len());
+                    candidates.truncate(3);
+
+                    // Track requests which are missing their response
+                    provider.additional_requests.push((request, candidates));
+
+                    return Err(Error::new(
+                        ErrorKind::ConnectionRefused,
+                        "noir: No response provided in test."
+                    ));
+
+                }
+            }
 
-            },
-            _ => Err(Error::new(
-                ErrorKind::ConnectionReset,
-                "noir: Handler lock failed."
-            ))
         };
 
+        // User handlers are called without holding the lock of the provider,
+        // so a panicking handler only affects its own response
+        let result = response.respond(request);
+        Ok((result, response.fault(), response.upgrade()))
+
     }
 
 }
 
+pub fn lock_response(response: &SharedResponse) -> MutexGuard<Box<MockResponse + 'static>> {
+    match response.lock() {
+        Ok(response) => response,
+        Err(err) => err.into_inner()
+    }
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn lock_provider() -> MutexGuard<'static, RefCell<ResponseProvider>> {
+    // The provider is reset by every request, so its state remains usable
+    // after a panic
+    match PROVIDER_INSTANCE.lock() {
+        Ok(handler) => handler,
+        Err(err) => err.into_inner()
+    }
+}
+
 
 // Statics --------------------------------------------------------------------
 lazy_static! {
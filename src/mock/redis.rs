@@ -0,0 +1,765 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::str;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+
+
+// Internal Dependencies ------------------------------------------------------
+use util::{duration_millis, format_mismatch};
+use mock::MockProvider;
+use mock::clock::mocked_system_time;
+
+
+/// A local server speaking the Redis protocol which serves the data of the
+/// currently active `MockRedis`.
+///
+/// The server is started on first use and kept running for all tests.
+/// Commands received while no `MockRedis` is active are answered with an
+/// error.
+///
+/// APIs which are spawned via `HttpApiProcess` can be configured to use the
+/// server with `HttpApiProcess::with_redis()`.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # extern crate noir;
+/// use noir::RedisServer;
+///
+/// # fn main() {
+/// std::env::set_var("REDIS_URL", RedisServer::url());
+/// # }
+/// ```
+pub struct RedisServer;
+
+impl RedisServer {
+
+    /// Returns the hostname of the server on the local loopback interface.
+    pub fn hostname() -> &'static str {
+        "127.0.0.1"
+    }
+
+    /// Returns the port of the server on the local loopback interface.
+    pub fn port() -> u16 {
+        *REDIS_PORT
+    }
+
+    /// Returns the url of the server, suitable for use with most Redis
+    /// clients.
+    pub fn url() -> String {
+        format!("redis://{}:{}", RedisServer::hostname(), RedisServer::port())
+    }
+
+}
+
+
+/// A mock provider which serves a seeded key space via the `RedisServer` for
+/// the time of a request and verifies the commands issued against it.
+///
+/// Keys expire once their time to live has passed, based on the time of the
+/// currently active `MockClock`, falling back to the system clock if no clock
+/// is active. The time to live which was last set for a key is kept for
+/// verification once the request has completed.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # #[macro_use] extern crate noir;
+/// use std::time::Duration;
+/// use noir::MockRedis;
+///
+/// # fn main() {
+/// let mocks = mocks![
+///     MockRedis::new()
+///         .with_key("user:1", "Alice")
+///         .expected_command(vec!["GET", "user:2"])
+///         .expected_ttl("user:2", Duration::from_secs(60))
+/// ];
+/// # }
+/// ```
+pub struct MockRedis {
+    state: Arc<Mutex<RedisState>>,
+    keys: Vec<(String, Vec<u8>, Option<u64>)>,
+    expected_commands: Vec<Vec<String>>,
+    expected_keys: Vec<(String, Vec<u8>)>,
+    expected_ttls: Vec<(String, Duration)>
+}
+
+impl MockRedis {
+
+    /// Creates a new mock with an empty key space.
+    pub fn new() -> MockRedis {
+        MockRedis {
+            state: Arc::new(Mutex::new(RedisState::default())),
+            keys: Vec::new(),
+            expected_commands: Vec::new(),
+            expected_keys: Vec::new(),
+            expected_ttls: Vec::new()
+        }
+    }
+
+    /// Seeds the key space with a `key` set to `value`.
+    pub fn with_key<K: Into<String>, V: Into<Vec<u8>>>(mut self, key: K, value: V) -> Self {
+        self.keys.push((key.into(), value.into(), None));
+        self
+    }
+
+    /// Seeds the key space with a `key` set to `value` that has the specified
+    /// time to live.
+    pub fn with_expiring_key<K: Into<String>, V: Into<Vec<u8>>>(
+        mut self,
+        key: K,
+        value: V,
+        ttl: Duration
+
+    ) -> Self {
+        self.keys.push((key.into(), value.into(), Some(duration_millis(ttl))));
+        self
+    }
+
+    /// Sets up an expectation for a command which must be issued by the API
+    /// during the request.
+    ///
+    /// The command name is compared case-insensitively, all of its arguments
+    /// must match exactly.
+    ///
+    /// # Test Failure
+    ///
+    /// If no matching command was issued.
+    pub fn expected_command(mut self, command: Vec<&str>) -> Self {
+        self.expected_commands.push(command.into_iter().enumerate().map(|(i, arg)| {
+            if i == 0 {
+                arg.to_uppercase()
+
+            } else {
+                arg.to_string()
+            }
+
+        }).collect());
+        self
+    }
+
+    /// Sets up an expectation for the value of a `key` once the request has
+    /// completed.
+    ///
+    /// # Test Failure
+    ///
+    /// If the key is missing or has a different value.
+    pub fn expected_key<K: Into<String>, V: Into<Vec<u8>>>(mut self, key: K, value: V) -> Self {
+        self.expected_keys.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets up an expectation for the time to live of a `key` once the
+    /// request has completed.
+    ///
+    /// The expectation is compared against the time to live which was last
+    /// set for the key, not against the time it has left.
+    ///
+    /// # Test Failure
+    ///
+    /// If the key is missing or has a different time to live.
+    pub fn expected_ttl<K: Into<String>>(mut self, key: K, ttl: Duration) -> Self {
+        self.expected_ttls.push((key.into(), ttl));
+        self
+    }
+
+}
+
+impl Default for MockRedis {
+    fn default() -> MockRedis {
+        MockRedis::new()
+    }
+}
+
+impl MockProvider for MockRedis {
+
+    fn setup(&mut self) {
+
+        // Start the server before the API attempts to connect to it
+        RedisServer::port();
+
+        {
+            let mut state = lock(&self.state);
+            state.store.clear();
+            state.commands.clear();
+            for &(ref key, ref value, ttl) in &self.keys {
+                let expiry = ttl.map(|ttl| expiry(ttl).expect(
+                    "noir: Invalid time to live for Redis key."
+                ));
+                state.store.insert(key.to_string(), (value.clone(), expiry));
+            }
+        }
+
+        *lock(&ACTIVE_REDIS) = Some(self.state.clone());
+
+    }
+
+    fn teardown(&mut self) {
+        *lock(&ACTIVE_REDIS) = None;
+    }
+
+    fn validate(&mut self) -> Vec<String> {
+
+        let mut state = lock(&self.state);
+        evict_expired(&mut state.store);
+
+        let mut errors = Vec::new();
+
+        for command in &self.expected_commands {
+            if !state.commands.contains(command) {
+                errors.push(format!(
+                    "{} \"{}\" {} {}{}",
+                    "Command".yellow(),
+                    command.join(" ").blue().bold(),
+                    "was expected to be issued, but".yellow(),
+                    "was not".red().bold(),
+                    ".".yellow()
+                ));
+            }
+        }
+
+        for &(ref key, ref value) in &self.expected_keys {
+            match state.store.get(key) {
+                Some(&(ref actual, _)) => if actual != value {
+                    errors.push(format_key_mismatch(
+                        key.as_str(),
+                        "value",
+                        String::from_utf8_lossy(value).as_ref(),
+                        String::from_utf8_lossy(actual).as_ref()
+                    ));
+                },
+                None => errors.push(format_missing(key.as_str()))
+            }
+        }
+
+        for &(ref key, ttl) in &self.expected_ttls {
+            match state.store.get(key) {
+                Some(&(_, actual)) => {
+                    let actual = actual.map(|expiry| expiry.ttl);
+                    if actual != Some(duration_millis(ttl)) {
+                        errors.push(format_key_mismatch(
+                            key.as_str(),
+                            "TTL",
+                            format_ttl(Some(duration_millis(ttl))).as_str(),
+                            format_ttl(actual).as_str()
+                        ));
+                    }
+                },
+                None => errors.push(format_missing(key.as_str()))
+            }
+        }
+
+        errors
+
+    }
+
+    fn validate_header(&self, error_count: usize) -> String {
+        format!(
+            "{} {} {} {} {}",
+            "Redis".cyan(),
+            "mock".yellow(),
+            "returned".yellow(),
+            format!("{}", error_count).red().bold(),
+            "error(s)".yellow()
+        )
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+type RedisStore = HashMap<String, (Vec<u8>, Option<RedisExpiry>)>;
+
+#[derive(Default)]
+struct RedisState {
+    store: RedisStore,
+    commands: Vec<Vec<String>>
+}
+
+#[derive(Copy, Clone)]
+struct RedisExpiry {
+    ttl: u64,
+    deadline: u64
+}
+
+fn expiry(ttl: u64) -> Option<RedisExpiry> {
+    // Deadlines are limited to the range of Redis' signed millisecond
+    // timestamps
+    now_millis().checked_add(ttl).and_then(|deadline| {
+        if deadline > i64::max_value() as u64 {
+            None
+
+        } else {
+            Some(RedisExpiry {
+                ttl: ttl,
+                deadline: deadline
+            })
+        }
+    })
+}
+
+fn now_millis() -> u64 {
+    match mocked_system_time().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration_millis(duration),
+        Err(_) => 0
+    }
+}
+
+fn evict_expired(store: &mut RedisStore) {
+
+    let now = now_millis();
+    let expired = store.iter().filter(|&(_, &(_, expiry))| {
+        expiry.map_or(false, |expiry| expiry.deadline <= now)
+
+    }).map(|(key, _)| key.to_string()).collect::<Vec<String>>();
+
+    for key in expired {
+        store.remove(&key);
+    }
+
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+    // The state is replaced as a whole, so it remains usable if a thread
+    // panicked while holding the lock
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(err) => err.into_inner()
+    }
+}
+
+fn start_server() -> u16 {
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect(
+        "noir: Failed to bind Redis server."
+    );
+
+    let local_port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                thread::spawn(move || {
+                    serve_redis_connection(stream).ok();
+                });
+            }
+        }
+    });
+
+    local_port
+
+}
+
+fn serve_redis_connection(mut stream: TcpStream) -> Result<(), Error> {
+
+    let mut reader = BufReader::new(try!(stream.try_clone()));
+    loop {
+
+        let args = match try!(read_command(&mut reader)) {
+            Some(args) => args,
+            None => return Ok(())
+        };
+
+        if args.is_empty() {
+            continue;
+        }
+
+        let name = String::from_utf8_lossy(&args[0][..]).to_uppercase();
+        let state = lock(&ACTIVE_REDIS).clone();
+        let reply = match state {
+            Some(state) => {
+                let mut state = lock(&state);
+                state.commands.push(args.iter().enumerate().map(|(i, arg)| {
+                    if i == 0 {
+                        name.clone()
+
+                    } else {
+                        String::from_utf8_lossy(&arg[..]).to_string()
+                    }
+
+                }).collect());
+                execute(&mut state.store, name.as_str(), &args[1..])
+            },
+            None => b"-ERR noir: No Redis mock active in test\r\n".to_vec()
+        };
+
+        try!(stream.write_all(&reply[..]));
+
+        if name == "QUIT" {
+            return Ok(());
+        }
+
+    }
+
+}
+
+fn read_command(reader: &mut BufReader<TcpStream>) -> Result<Option<Vec<Vec<u8>>>, Error> {
+
+    let line = match try!(read_line(reader)) {
+        Some(line) => line,
+        None => return Ok(None)
+    };
+
+    // Inline commands are sent as a single line of space separated arguments
+    if !line.starts_with('*') {
+        return Ok(Some(line.split_whitespace().map(|arg| {
+            arg.as_bytes().to_vec()
+
+        }).collect()));
+    }
+
+    let count = try!(parse_length(&line[1..]));
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+
+        let line = match try!(read_line(reader)) {
+            Some(ref line) if line.starts_with('$') => line.to_string(),
+            _ => return Err(protocol_error())
+        };
+
+        let length = try!(parse_length(&line[1..]));
+        let mut data = vec![0; length + 2];
+        try!(reader.read_exact(&mut data[..]));
+        data.truncate(length);
+        args.push(data);
+
+    }
+
+    Ok(Some(args))
+
+}
+
+fn read_line(reader: &mut BufReader<TcpStream>) -> Result<Option<String>, Error> {
+    let mut line = String::new();
+    if try!(reader.read_line(&mut line)) == 0 {
+        Ok(None)
+
+    } else {
+        Ok(Some(line.trim_right().to_string()))
+    }
+}
+
+fn parse_length(value: &str) -> Result<usize, Error> {
+    value.parse::<usize>().map_err(|_| protocol_error())
+}
+
+fn protocol_error() -> Error {
+    Error::new(ErrorKind::InvalidData, "noir: Invalid Redis protocol data.")
+}
+
+fn execute(store: &mut RedisStore, name: &str, args: &[Vec<u8>]) -> Vec<u8> {
+
+    // Expired keys are removed before they can be accessed by the command
+    evict_expired(store);
+
+    let arg = |index: usize| -> String {
+        args.get(index).map_or(String::new(), |arg| {
+            String::from_utf8_lossy(&arg[..]).to_string()
+        })
+    };
+
+    let arity = match name {
+        "PING" | "QUIT" | "FLUSHDB" | "FLUSHALL" => 0,
+        "GET" | "TTL" | "PTTL" | "PERSIST" | "INCR" | "DECR" |
+        "AUTH" | "SELECT" | "DEL" | "EXISTS" | "MGET" => 1,
+        "SET" | "EXPIRE" | "PEXPIRE" | "INCRBY" | "DECRBY" => 2,
+        "SETEX" | "PSETEX" => 3,
+        _ => return format!("-ERR unknown command '{}'\r\n", name.to_lowercase()).into_bytes()
+    };
+
+    if args.len() < arity {
+        return format!(
+            "-ERR wrong number of arguments for '{}' command\r\n",
+            name.to_lowercase()
+
+        ).into_bytes();
+    }
+
+    match name {
+        "PING" => simple_reply("PONG"),
+        "QUIT" | "AUTH" | "SELECT" => simple_reply("OK"),
+        "FLUSHDB" | "FLUSHALL" => {
+            store.clear();
+            simple_reply("OK")
+        },
+        "GET" => bulk_reply(store.get(&arg(0)).map(|entry| &entry.0[..])),
+        "MGET" => {
+            let mut reply = format!("*{}\r\n", args.len()).into_bytes();
+            for index in 0..args.len() {
+                reply.append(&mut bulk_reply(store.get(&arg(index)).map(|entry| &entry.0[..])));
+            }
+            reply
+        },
+        "SET" => {
+
+            let mut ttl = None;
+            let mut only_missing = false;
+            let mut only_existing = false;
+
+            let mut index = 2;
+            while index < args.len() {
+                match arg(index).to_uppercase().as_str() {
+                    option @ "EX" | option @ "PX" => {
+                        index += 1;
+                        match parse_expiry(name, arg(index).as_str(), option == "EX") {
+                            Ok(expiry) => ttl = Some(expiry),
+                            Err(reply) => return reply
+                        }
+                    },
+                    "NX" => only_missing = true,
+                    "XX" => only_existing = true,
+                    _ => return b"-ERR syntax error\r\n".to_vec()
+                }
+                index += 1;
+            }
+
+            let exists = store.contains_key(&arg(0));
+            if (only_missing && exists) || (only_existing && !exists) {
+                bulk_reply(None)
+
+            } else {
+                store.insert(arg(0), (args[1].clone(), ttl));
+                simple_reply("OK")
+            }
+
+        },
+        "SETEX" | "PSETEX" => match parse_expiry(name, arg(1).as_str(), name == "SETEX") {
+            Ok(expiry) => {
+                store.insert(arg(0), (args[2].clone(), Some(expiry)));
+                simple_reply("OK")
+            },
+            Err(reply) => reply
+        },
+        "DEL" => {
+            let count = (0..args.len()).filter(|index| {
+                store.remove(&arg(*index)).is_some()
+
+            }).count();
+            integer_reply(count as i64)
+        },
+        "EXISTS" => {
+            let count = (0..args.len()).filter(|index| {
+                store.contains_key(&arg(*index))
+
+            }).count();
+            integer_reply(count as i64)
+        },
+        "EXPIRE" | "PEXPIRE" => match parse_expiry(name, arg(1).as_str(), name == "EXPIRE") {
+            Ok(expiry) => match store.get_mut(&arg(0)) {
+                Some(entry) => {
+                    entry.1 = Some(expiry);
+                    integer_reply(1)
+                },
+                None => integer_reply(0)
+            },
+            Err(reply) => reply
+        },
+        "PERSIST" => match store.get_mut(&arg(0)) {
+            Some(entry) => {
+                let had_ttl = entry.1.take().is_some();
+                integer_reply(if had_ttl { 1 } else { 0 })
+            },
+            None => integer_reply(0)
+        },
+        "TTL" | "PTTL" => match store.get(&arg(0)) {
+            Some(&(_, Some(expiry))) => {
+                let remaining = expiry.deadline.saturating_sub(now_millis());
+
+                // TTL rounds the time left to the nearest second
+                let remaining = if name == "TTL" {
+                    (remaining + 500) / 1000
+
+                } else {
+                    remaining
+                };
+
+                integer_reply(remaining as i64)
+            },
+            Some(&(_, None)) => integer_reply(-1),
+            None => integer_reply(-2)
+        },
+        _ => {
+
+            let delta = match name {
+                "INCR" => Ok(1),
+                "DECR" => Ok(-1),
+                "INCRBY" => arg(1).parse::<i64>().map_err(|_| integer_error()),
+                _ => arg(1).parse::<i64>().map_err(|_| integer_error()).and_then(|delta| {
+                    delta.checked_neg().ok_or_else(overflow_error)
+                })
+            };
+
+            let value = match store.get(&arg(0)) {
+                Some(entry) => str::from_utf8(&entry.0[..]).ok().and_then(|value| {
+                    value.parse::<i64>().ok()
+                }),
+                None => Some(0)
+            };
+
+            match (value, delta) {
+                (Some(value), Ok(delta)) => match value.checked_add(delta) {
+                    Some(value) => {
+                        let ttl = store.get(&arg(0)).and_then(|entry| entry.1);
+                        store.insert(arg(0), (format!("{}", value).into_bytes(), ttl));
+                        integer_reply(value)
+                    },
+                    None => overflow_error()
+                },
+                (Some(_), Err(reply)) => reply,
+                (None, _) => integer_error()
+            }
+
+        }
+    }
+
+}
+
+fn simple_reply(value: &str) -> Vec<u8> {
+    format!("+{}\r\n", value).into_bytes()
+}
+
+fn integer_reply(value: i64) -> Vec<u8> {
+    format!(":{}\r\n", value).into_bytes()
+}
+
+fn integer_error() -> Vec<u8> {
+    b"-ERR value is not an integer or out of range\r\n".to_vec()
+}
+
+fn overflow_error() -> Vec<u8> {
+    b"-ERR increment or decrement would overflow\r\n".to_vec()
+}
+
+fn parse_expiry(name: &str, value: &str, seconds: bool) -> Result<RedisExpiry, Vec<u8>> {
+
+    let ttl = try!(value.parse::<u64>().map_err(|_| integer_error()));
+    let ttl = if seconds {
+        ttl.checked_mul(1000)
+
+    } else {
+        Some(ttl)
+    };
+
+    ttl.and_then(expiry).ok_or_else(|| format!(
+        "-ERR invalid expire time in '{}' command\r\n",
+        name.to_lowercase()
+
+    ).into_bytes())
+
+}
+
+fn bulk_reply(value: Option<&[u8]>) -> Vec<u8> {
+    match value {
+        Some(value) => {
+            let mut reply = format!("${}\r\n", value.len()).into_bytes();
+            reply.extend_from_slice(value);
+            reply.extend_from_slice(b"\r\n");
+            reply
+        },
+        None => b"$-1\r\n".to_vec()
+    }
+}
+
+fn format_ttl(ttl: Option<u64>) -> String {
+    match ttl {
+        Some(ttl) => format!("{}ms", ttl),
+        None => "none".to_string()
+    }
+}
+
+fn format_key_mismatch(key: &str, name: &str, expected: &str, actual: &str) -> String {
+    format!(
+        "{} \"{}\" {}",
+        "Key".yellow(),
+        key.blue().bold(),
+        format_mismatch(name, expected, actual)
+    )
+}
+
+fn format_missing(key: &str) -> String {
+    format!(
+        "{} \"{}\" {} {}{} {}{}",
+        "Key".yellow(),
+        key.blue().bold(),
+        "was expected".yellow(),
+        "to be present".green().bold(),
+        ", but".yellow(),
+        "is missing".red().bold(),
+        ".".yellow()
+    )
+}
+
+
+// Statics --------------------------------------------------------------------
+lazy_static! {
+    static ref REDIS_PORT: u16 = start_server();
+    static ref ACTIVE_REDIS: Arc<Mutex<Option<Arc<Mutex<RedisState>>>>> = {
+        Arc::new(Mutex::new(None))
+    };
+}
+
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+
+    use super::{RedisStore, execute};
+
+    fn command(store: &mut RedisStore, name: &str, args: Vec<&str>) -> String {
+        let args: Vec<Vec<u8>> = args.into_iter().map(|arg| arg.as_bytes().to_vec()).collect();
+        String::from_utf8(execute(store, name, &args[..])).unwrap()
+    }
+
+    #[test]
+    fn test_increment_overflow() {
+
+        let mut store = RedisStore::new();
+        assert_eq!(command(&mut store, "SET", vec!["count", "9223372036854775807"]), "+OK\r\n");
+        assert_eq!(
+            command(&mut store, "INCR", vec!["count"]),
+            "-ERR increment or decrement would overflow\r\n"
+        );
+        assert_eq!(
+            command(&mut store, "DECRBY", vec!["count", "-9223372036854775808"]),
+            "-ERR increment or decrement would overflow\r\n"
+        );
+        assert_eq!(command(&mut store, "DECRBY", vec!["count", "7"]), ":9223372036854775800\r\n");
+
+    }
+
+    #[test]
+    fn test_expire_overflow() {
+
+        let mut store = RedisStore::new();
+        assert_eq!(command(&mut store, "SET", vec!["key", "value"]), "+OK\r\n");
+        assert_eq!(
+            command(&mut store, "PEXPIRE", vec!["key", "18446744073709551615"]),
+            "-ERR invalid expire time in 'pexpire' command\r\n"
+        );
+        assert_eq!(
+            command(&mut store, "EXPIRE", vec!["key", "9223372036854775807"]),
+            "-ERR invalid expire time in 'expire' command\r\n"
+        );
+        assert_eq!(command(&mut store, "PEXPIRE", vec!["key", "60000"]), ":1\r\n");
+        assert_eq!(command(&mut store, "TTL", vec!["key"]), ":60\r\n");
+
+    }
+
+}
@@ -0,0 +1,206 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::thread;
+use std::io::{BufRead, BufReader, Error, Write};
+use std::net::{TcpListener, TcpStream};
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::ResponseProvider;
+use resource::smtp::SmtpMessage;
+
+
+/// A local SMTP server which accepts all mails sent to it and matches them
+/// against the `SmtpMail` expectations of the current test.
+///
+/// Mails are only observed while a request is running, APIs which send their
+/// mails in the background after responding will have them reported as
+/// unexpected by the following test.
+///
+/// The server is started on first use and kept running for all tests.
+///
+/// APIs which are spawned via `HttpApiProcess` can be configured to use the
+/// server with `HttpApiProcess::with_smtp()`.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # extern crate noir;
+/// use noir::SmtpServer;
+///
+/// # fn main() {
+/// std::env::set_var("SMTP_PORT", SmtpServer::port().to_string());
+/// # }
+/// ```
+pub struct SmtpServer;
+
+impl SmtpServer {
+
+    /// Returns the hostname of the server on the local loopback interface.
+    pub fn hostname() -> &'static str {
+        "127.0.0.1"
+    }
+
+    /// Returns the port of the server on the local loopback interface.
+    pub fn port() -> u16 {
+        *SMTP_PORT
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn start_server() -> u16 {
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect(
+        "noir: Failed to bind SMTP server."
+    );
+
+    let local_port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                thread::spawn(move || {
+                    serve_smtp_connection(stream).ok();
+                });
+            }
+        }
+    });
+
+    local_port
+
+}
+
+fn serve_smtp_connection(mut stream: TcpStream) -> Result<(), Error> {
+
+    let mut reader = BufReader::new(try!(stream.try_clone()));
+    let mut from: Option<String> = None;
+    let mut recipients = Vec::new();
+
+    try!(stream.write_all(b"220 noir SMTP ready\r\n"));
+
+    let mut line = String::new();
+    loop {
+
+        line.clear();
+        if try!(reader.read_line(&mut line)) == 0 {
+            return Ok(());
+        }
+
+        let command = line.trim_right().to_string();
+        let verb = command.split_whitespace().next().unwrap_or("").to_uppercase();
+        let reply = match verb.as_str() {
+            "HELO" => b"250 noir\r\n".to_vec(),
+            "EHLO" => b"250-noir\r\n250 8BITMIME\r\n".to_vec(),
+            "MAIL" => {
+                from = Some(smtp_address(command.as_str()));
+                b"250 OK\r\n".to_vec()
+            },
+            "RCPT" => {
+                recipients.push(smtp_address(command.as_str()));
+                b"250 OK\r\n".to_vec()
+            },
+            "DATA" => if from.is_none() || recipients.is_empty() {
+                b"503 Bad sequence of commands\r\n".to_vec()
+
+            } else {
+
+                try!(stream.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n"));
+
+                let data = try!(read_data(&mut reader));
+                let from = from.take().unwrap();
+
+                // Mails are delivered separately to each of their recipients,
+                // any recipient without a matching expectation rejects the
+                // whole mail
+                let mut accepted = true;
+                for recipient in recipients.drain(0..) {
+                    let message = SmtpMessage::new(from.clone(), vec![recipient], &data[..]);
+                    match ResponseProvider::request(Box::new(message)) {
+                        Ok((Ok(_), _, _)) => {},
+                        _ => accepted = false
+                    }
+                }
+
+                if accepted {
+                    b"250 OK: Message accepted\r\n".to_vec()
+
+                } else {
+                    b"554 No mail expected in test\r\n".to_vec()
+                }
+
+            },
+            "RSET" => {
+                from = None;
+                recipients.clear();
+                b"250 OK\r\n".to_vec()
+            },
+            "NOOP" => b"250 OK\r\n".to_vec(),
+            "QUIT" => {
+                return stream.write_all(b"221 Bye\r\n");
+            },
+            _ => b"502 Command not implemented\r\n".to_vec()
+        };
+
+        try!(stream.write_all(&reply[..]));
+
+    }
+
+}
+
+fn read_data(reader: &mut BufReader<TcpStream>) -> Result<Vec<u8>, Error> {
+
+    let mut data = Vec::new();
+    loop {
+
+        let mut line = Vec::new();
+        if try!(reader.read_until(b'\n', &mut line)) == 0 {
+            return Ok(data);
+        }
+
+        if line == b".\r\n" || line == b".\n" {
+            return Ok(data);
+        }
+
+        // Remove dot stuffing from lines starting with a period
+        if line.starts_with(b"..") {
+            line.remove(0);
+        }
+
+        data.extend_from_slice(&line[..]);
+
+    }
+
+}
+
+fn smtp_address(command: &str) -> String {
+
+    let argument = match command.find(':') {
+        Some(index) => command[index + 1..].trim(),
+        None => ""
+    };
+
+    // Addresses are usually enclosed in angle brackets and might be followed
+    // by additional parameters
+    if argument.starts_with('<') {
+        argument[1..].split('>').next().unwrap_or("").to_string()
+
+    } else {
+        argument.split_whitespace().next().unwrap_or("").to_string()
+    }
+
+}
+
+
+// Statics --------------------------------------------------------------------
+lazy_static! {
+    static ref SMTP_PORT: u16 = start_server();
+}
+
@@ -0,0 +1,140 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+
+// External Dependencies ------------------------------------------------------
+use rand::{self, Rng};
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::MockProvider;
+
+
+/// A mock provider which creates a temporary directory with seeded files for
+/// the time of a request.
+///
+/// The directory and all of its contents are removed once the request has
+/// completed.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # #[macro_use] extern crate noir;
+/// use noir::MockTempDir;
+///
+/// # fn main() {
+/// let dir = MockTempDir::new()
+///     .with_file("config/app.json", "{}")
+///     .with_env("APP_DATA_DIR");
+///
+/// let path = dir.path();
+/// let mocks = mocks![dir];
+/// # }
+/// ```
+pub struct MockTempDir {
+    path: PathBuf,
+    files: Vec<(String, Vec<u8>)>,
+    env_var: Option<String>,
+    previous_env_var: Option<Option<String>>
+}
+
+impl MockTempDir {
+
+    /// Creates a new temporary directory mock with a unique path inside of
+    /// the system's temporary directory.
+    pub fn new() -> MockTempDir {
+        let mut rng = rand::thread_rng();
+        MockTempDir {
+            path: env::temp_dir().join(format!("noir-{}", rng.next_u64())),
+            files: Vec::new(),
+            env_var: None,
+            previous_env_var: None
+        }
+    }
+
+    /// Adds a file with the specified `contents` to the directory.
+    ///
+    /// The `path` is relative to the directory, any missing parent
+    /// directories are created as well.
+    pub fn with_file<P: Into<String>, D: Into<Vec<u8>>>(mut self, path: P, contents: D) -> Self {
+        self.files.push((path.into(), contents.into()));
+        self
+    }
+
+    /// Sets the environment variable `key` to the path of the directory
+    /// during the request.
+    pub fn with_env<K: Into<String>>(mut self, key: K) -> Self {
+        self.env_var = Some(key.into());
+        self
+    }
+
+    /// Returns the path of the directory.
+    pub fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+}
+
+impl Default for MockTempDir {
+    fn default() -> MockTempDir {
+        MockTempDir::new()
+    }
+}
+
+impl MockProvider for MockTempDir {
+
+    fn setup(&mut self) {
+
+        fs::create_dir_all(&self.path).expect(
+            "noir: Failed to create temporary directory."
+        );
+
+        for &(ref path, ref contents) in &self.files {
+
+            let path = self.path.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect(
+                    "noir: Failed to create temporary directory."
+                );
+            }
+
+            fs::File::create(&path).and_then(|mut file| {
+                file.write_all(&contents[..])
+
+            }).expect("noir: Failed to write temporary file.");
+
+        }
+
+        if let Some(key) = self.env_var.as_ref() {
+            self.previous_env_var = Some(env::var(key).ok());
+            env::set_var(key, &self.path);
+        }
+
+    }
+
+    fn teardown(&mut self) {
+
+        if let (Some(key), Some(previous)) = (self.env_var.as_ref(), self.previous_env_var.take()) {
+            match previous {
+                Some(value) => env::set_var(key, value),
+                None => env::remove_var(key)
+            }
+        }
+
+        fs::remove_dir_all(&self.path).ok();
+
+    }
+
+}
+
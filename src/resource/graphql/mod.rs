@@ -0,0 +1,304 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// External Dependencies ------------------------------------------------------
+use json;
+use json::JsonValue;
+use colored::*;
+
+
+// Internal Dependencies ------------------------------------------------------
+use util;
+use resource::http::HttpBody;
+use resource::http::util::http_body_data;
+
+
+// Noir Internal --------------------------------------------------------------
+#[derive(Clone)]
+pub enum GraphQlExpectation {
+    Data(String, JsonValue),
+    NoErrors,
+    ErrorMessage(String),
+    ErrorCode(String)
+}
+
+pub fn graphql_body(query: &str, variables: JsonValue) -> JsonValue {
+    object! {
+        "query" => query,
+        "variables" => variables
+    }
+}
+
+/// Returns the name of the operation requested by a GraphQL request body.
+///
+/// An explicit `operationName` takes precedence over the name of the first
+/// operation defined in the query document.
+pub fn graphql_operation_name(body: &HttpBody) -> Option<String> {
+
+    let request = match util::json::parse(http_body_data(body), "GraphQL") {
+        Ok(request) => request,
+        Err(_) => return None
+    };
+
+    if let Some(name) = request["operationName"].as_str() {
+        return Some(name.to_string());
+    }
+
+    request["query"].as_str().and_then(parse_operation_name)
+
+}
+
+pub fn graphql_validate(
+    expectations: &[GraphQlExpectation],
+    body: &HttpBody,
+    compare_depth: usize
+
+) -> Vec<String> {
+
+    let response = match util::json::parse(http_body_data(body), "GraphQL") {
+        Ok(response) => response,
+        Err(err) => return vec![format!(
+            "{} {}",
+            "Response".yellow(),
+            err
+        )]
+    };
+
+    let errors = response["errors"].members().collect::<Vec<&JsonValue>>();
+
+    expectations.iter().filter_map(|expectation| {
+        match *expectation {
+            GraphQlExpectation::Data(ref path, ref expected) => {
+                validate_data(&response["data"], path.as_str(), expected, compare_depth)
+            },
+            GraphQlExpectation::NoErrors => if errors.is_empty() {
+                None
+
+            } else {
+                Some(format!(
+                    "{} {} {} {}\n\n        {}",
+                    "Response".yellow(),
+                    "GraphQL errors were expected to be absent, but got".yellow(),
+                    format!("{}", errors.len()).red().bold(),
+                    "error(s):".yellow(),
+                    format_values(&errors[..], |error| &error["message"])
+                ))
+            },
+            GraphQlExpectation::ErrorMessage(ref message) => {
+                validate_error(&errors[..], "message", message.as_str(), |error| {
+                    &error["message"]
+                })
+            },
+            GraphQlExpectation::ErrorCode(ref code) => {
+                validate_error(&errors[..], "code", code.as_str(), |error| {
+                    &error["extensions"]["code"]
+                })
+            }
+        }
+
+    }).collect()
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn parse_operation_name(query: &str) -> Option<String> {
+
+    let chars = query.chars().collect::<Vec<char>>();
+    let mut index = 0;
+    let mut depth = 0;
+
+    // Whether the next top level token starts a new definition
+    let mut definition = true;
+
+    // Whether the previous token was the type of an operation definition
+    let mut operation = false;
+
+    while index < chars.len() {
+
+        let c = chars[index];
+        index += 1;
+
+        // Comments extend until the end of the line
+        if c == '#' {
+            while index < chars.len() && chars[index] != '\n' && chars[index] != '\r' {
+                index += 1;
+            }
+
+        } else if c == '"' {
+            index = skip_string(&chars[..], index);
+
+        } else if c.is_alphabetic() || c == '_' {
+
+            let start = index - 1;
+            while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                index += 1;
+            }
+
+            let name = chars[start..index].iter().cloned().collect::<String>();
+            if operation {
+                return Some(name);
+
+            // Fragments and other definitions are skipped until their
+            // selection set has been closed
+            } else if depth == 0 && definition {
+                definition = false;
+                operation = name == "query" || name == "mutation" || name == "subscription";
+            }
+
+        } else if !c.is_whitespace() && c != ',' {
+
+            // Anonymous operations and the query shorthand have no name
+            if operation || (depth == 0 && definition && c == '{') {
+                return None;
+            }
+
+            match c {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => if depth > 0 {
+                    depth -= 1;
+                    if c == '}' && depth == 0 {
+                        definition = true;
+                    }
+                },
+                _ => {}
+            }
+
+        }
+
+    }
+
+    None
+
+}
+
+fn skip_string(chars: &[char], mut index: usize) -> usize {
+
+    // Block strings are delimited by triple quotes
+    if chars[index..].starts_with(&['"', '"']) {
+        index += 2;
+        while index < chars.len() {
+            if chars[index..].starts_with(&['\\', '"', '"', '"']) {
+                index += 4;
+
+            } else if chars[index..].starts_with(&['"', '"', '"']) {
+                return index + 3;
+
+            } else {
+                index += 1;
+            }
+        }
+
+    } else {
+        while index < chars.len() {
+            match chars[index] {
+                '\\' => index += 2,
+                '"' => return index + 1,
+                _ => index += 1
+            }
+        }
+    }
+
+    chars.len()
+
+}
+
+fn lookup<'a>(mut value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    for key in path.split('.').filter(|key| !key.is_empty()) {
+        value = if value.is_array() {
+            match key.parse::<usize>() {
+                Ok(index) if index < value.len() => &value[index],
+                _ => return None
+            }
+
+        } else if value.entries().any(|(k, _)| k == key) {
+            &value[key]
+
+        } else {
+            return None;
+        };
+    }
+    Some(value)
+}
+
+fn validate_data(
+    data: &JsonValue,
+    path: &str,
+    expected: &JsonValue,
+    compare_depth: usize
+
+) -> Option<String> {
+    match lookup(data, path) {
+        Some(actual) => match util::json::compare(expected, actual, compare_depth, false) {
+            Ok(_) => None,
+            Err(errors) => Some(format!(
+                "{} {} \"{}\" {}\n\n        {}",
+                "Response".yellow(),
+                "GraphQL data at".yellow(),
+                path.blue().bold(),
+                "does not match:".yellow(),
+                util::json::format(errors)
+            ))
+        },
+        None => Some(format!(
+            "{} {} \"{}\" {} {}{}",
+            "Response".yellow(),
+            "GraphQL data at".yellow(),
+            path.blue().bold(),
+            "is".yellow(),
+            "missing".red().bold(),
+            ".".yellow()
+        ))
+    }
+}
+
+fn validate_error<F>(
+    errors: &[&JsonValue],
+    name: &str,
+    expected: &str,
+    field: F
+
+) -> Option<String> where F: Fn(&JsonValue) -> &JsonValue {
+
+    if errors.iter().any(|error| field(*error).as_str() == Some(expected)) {
+        None
+
+    } else if errors.is_empty() {
+        Some(format!(
+            "{} {} \"{}\" {} {}{}",
+            "Response".yellow(),
+            format!("GraphQL error with {}", name).yellow(),
+            expected.green().bold(),
+            "was expected, but".yellow(),
+            "none was returned".red().bold(),
+            ".".yellow()
+        ))
+
+    } else {
+        Some(format!(
+            "{} {} \"{}\" {}\n\n        {}",
+            "Response".yellow(),
+            format!("GraphQL error with {}", name).yellow(),
+            expected.green().bold(),
+            "was expected, but got:".yellow(),
+            format_values(errors, field)
+        ))
+    }
+
+}
+
+fn format_values<F>(errors: &[&JsonValue], field: F) -> String where F: Fn(&JsonValue) -> &JsonValue {
+    errors.iter().map(|error| {
+        let value = field(*error);
+        format!("- \"{}\"", value.as_str().map_or_else(|| {
+            json::stringify(value.clone())
+
+        }, |value| value.to_string()).red().bold())
+
+    }).collect::<Vec<String>>().join("\n        ")
+}
@@ -32,6 +32,7 @@ use super::form::{
 
 
 /// An abstraction over different data types used for HTTP request bodies.
+#[derive(Clone)]
 pub struct HttpBody {
     data: Vec<u8>,
     mime: Option<Mime>
@@ -145,6 +146,7 @@ impl From<HttpFormData> for HttpBody {
 enum ParsedHttpBody<'a> {
     Text(&'a str),
     Json(json::JsonValue),
+    Xml(util::xml::XmlElement),
     Form(HttpFormData),
     Raw(&'a [u8])
 }
@@ -153,6 +155,13 @@ fn parse_http_body(body: &HttpBody) -> Result<ParsedHttpBody, String> {
 
     if let Some(mime) = body.mime.as_ref() {
         match mime.clone() {
+            Mime(TopLevel::Application, SubLevel::Xml, _) |
+            Mime(TopLevel::Text, SubLevel::Xml, _) => {
+                match util::xml::parse(body.data.as_slice(), "body XML") {
+                    Ok(xml) => Ok(ParsedHttpBody::Xml(xml)),
+                    Err(err) => Err(err)
+                }
+            },
             Mime(TopLevel::Text, _, _) => {
                 match str::from_utf8(body.data.as_slice()) {
                     Ok(text) => Ok(ParsedHttpBody::Text(text)),
@@ -198,18 +207,82 @@ fn parse_http_body(body: &HttpBody) -> Result<ParsedHttpBody, String> {
 }
 
 
+// Matching -------------------------------------------------------------------
+pub fn http_body_matches(
+    expected: &HttpBody,
+    actual: &HttpBody,
+    options: &Options
+
+) -> bool {
+
+    if actual.data == expected.data {
+        return true;
+    }
+
+    // JSON, XML and form bodies are matched as subsets of the actual body
+    match (parse_http_body(expected), parse_http_body(actual)) {
+        (Ok(ParsedHttpBody::Json(expected)), Ok(ParsedHttpBody::Json(actual))) => {
+            util::json::compare(
+                &expected,
+                &actual,
+                options.json_compare_depth,
+                false
+
+            ).is_ok()
+        },
+        (_, Ok(ParsedHttpBody::Xml(actual))) => {
+            // Expected XML is usually provided as plain text
+            util::xml::parse(expected.data.as_slice(), "body XML").map(|expected| {
+                util::xml::compare(&expected, &actual, false).is_ok()
+
+            }).unwrap_or(false)
+        },
+        (Ok(ParsedHttpBody::Form(expected)), Ok(ParsedHttpBody::Form(actual))) => {
+            util::form::compare(
+                &http_form_into_fields(expected),
+                &http_form_into_fields(actual),
+                false,
+                options
+
+            ).is_ok()
+        },
+        _ => false
+    }
+
+}
+
+pub fn http_body_text(body: &HttpBody) -> Option<&str> {
+    str::from_utf8(body.data.as_slice()).ok()
+}
+
+pub fn http_body_data(body: &HttpBody) -> &[u8] {
+    &body.data[..]
+}
+
+pub fn http_body_json(body: &HttpBody) -> Option<json::JsonValue> {
+    match parse_http_body(body) {
+        Ok(ParsedHttpBody::Json(json)) => Some(json),
+        _ => None
+    }
+}
+
+pub fn http_body_form(body: &HttpBody) -> Option<HttpFormData> {
+    match parse_http_body(body) {
+        Ok(ParsedHttpBody::Form(form)) => Some(form),
+        _ => None
+    }
+}
+
+
 // Formatting -----------------------------------------------------------------
 pub fn format_http_body(body: &HttpBody) -> String {
     match parse_http_body(body) {
         Ok(actual) => match actual {
-            ParsedHttpBody::Text(text) => {
-                let text = format!("{:?}", text);
-                format!(
-                    "{}\n\n        \"{}\"",
-                    "body dump:".yellow(),
-                    &text[1..text.len() - 1].purple().bold()
-                )
-            },
+            ParsedHttpBody::Text(text) => format_text_body(text),
+
+            ParsedHttpBody::Xml(_) => format_text_body(
+                http_body_text(body).unwrap_or("")
+            ),
 
             ParsedHttpBody::Json(json) => {
                 format!(
@@ -316,6 +389,15 @@ pub fn format_http_body(body: &HttpBody) -> String {
     }
 }
 
+fn format_text_body(text: &str) -> String {
+    let text = format!("{:?}", text);
+    format!(
+        "{}\n\n        \"{}\"",
+        "body dump:".yellow(),
+        &text[1..text.len() - 1].purple().bold()
+    )
+}
+
 
 // Validation -----------------------------------------------------------------
 pub fn validate_http_body(
@@ -345,6 +427,10 @@ pub fn validate_http_body(
                 compare_exact,
                 options.json_compare_depth
             ),
+            ParsedHttpBody::Xml(actual) => compare_xml_body(
+                context, expected_body, actual,
+                compare_exact
+            ),
             ParsedHttpBody::Form(actual) => compare_form_body(
                 context, expected_body, actual,
                 compare_exact,
@@ -446,6 +532,39 @@ fn compare_json_body(
 
 }
 
+fn compare_xml_body(
+    context: &str,
+    expected: &HttpBody,
+    actual: util::xml::XmlElement,
+    compare_exact: bool
+
+) -> Option<String> {
+
+    let expected_xml = util::xml::parse(
+        expected.data.as_slice(),
+        "body XML provided by test"
+    );
+
+    match expected_xml {
+        Ok(expected) => match util::xml::compare(&expected, &actual, compare_exact) {
+            Ok(()) => None,
+            Err(errors) => Some(format!(
+                "{} {}\n\n        {}",
+                context.yellow(),
+                "body XML does not match:".yellow(),
+                util::xml::format(errors)
+            ))
+        },
+        Err(err) => Some(format!(
+            "{} {} {}",
+            context.yellow(),
+            "body, expected".yellow(),
+            err
+        ))
+    }
+
+}
+
 fn compare_form_body(
     context: &str,
     expected: &HttpBody,
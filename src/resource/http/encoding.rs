@@ -0,0 +1,59 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::cmp;
+use std::io::Write;
+
+
+// External Dependencies ------------------------------------------------------
+use flate2::Compression;
+use flate2::write::{GzEncoder, ZlibEncoder};
+use hyper::header::{Encoding, Headers};
+
+
+// Internal -------------------------------------------------------------------
+pub fn encode_body(encoding: &Encoding, data: Vec<u8>) -> Vec<u8> {
+    match *encoding {
+        Encoding::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::Default);
+            encoder.write_all(&data[..]).unwrap();
+            encoder.finish().unwrap()
+        },
+        // HTTP's "deflate" refers to the zlib format
+        Encoding::Deflate => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::Default);
+            encoder.write_all(&data[..]).unwrap();
+            encoder.finish().unwrap()
+        },
+        // Other encodings are rejected by HttpResponse::with_content_encoding()
+        _ => data
+    }
+}
+
+pub fn chunk_body(data: &[u8], sizes: &[usize], trailers: &Headers) -> Vec<u8> {
+
+    let mut body = Vec::new();
+    let mut offset = 0;
+
+    // Any data left after the configured chunks is send as a final chunk
+    for size in sizes.iter().cloned().chain(Some(data.len())) {
+        let size = cmp::min(size, data.len() - offset);
+        if size > 0 {
+            body.extend_from_slice(format!("{:x}\r\n", size).as_bytes());
+            body.extend_from_slice(&data[offset..offset + size]);
+            body.extend_from_slice(b"\r\n");
+            offset += size;
+        }
+    }
+
+    body.extend_from_slice(format!("0\r\n{}\r\n", trailers).as_bytes());
+    body
+
+}
+
@@ -11,6 +11,7 @@ use hyper::method::Method;
 
 
 // Internal Dependencies ------------------------------------------------------
+use mock::http::loopback_port;
 use super::response::{HttpResponse, http_response};
 
 
@@ -75,6 +76,43 @@ pub trait HttpEndpoint: Send + Copy {
         format!("{}{}", self.url(), path)
     }
 
+    /// Returns the path of the endpoint's GraphQL API.
+    ///
+    /// Defaults to `/graphql`.
+    fn graphql_path(&self) -> &'static str {
+        "/graphql"
+    }
+
+    /// Returns the path of the endpoint's JSON-RPC API.
+    ///
+    /// Defaults to `/rpc`.
+    fn jsonrpc_path(&self) -> &'static str {
+        "/rpc"
+    }
+
+    /// Returns the base url of a local loopback server for the endpoint.
+    ///
+    /// The server is started on first use and dispatches all incoming
+    /// requests to the responses provided for the endpoint, allowing HTTP
+    /// clients which are not created via `hyper_client!()` to be mocked by
+    /// configuring them with the returned url.
+    ///
+    /// The returned url always uses plain `http`, see
+    /// `HttpEndpoint::loopback_tls_url()` for clients which require `https`.
+    fn loopback_url(&self) -> String {
+        format!("http://127.0.0.1:{}", loopback_port(self.hostname(), self.port()))
+    }
+
+    /// Returns the `https` base url of the local loopback server for the
+    /// endpoint.
+    ///
+    /// TLS connections to the server are intercepted with a certificate for
+    /// the endpoint's hostname and `127.0.0.1`, which is signed by the
+    /// authority at `HttpProxy::ca_certificate_path()`.
+    fn loopback_tls_url(&self) -> String {
+        format!("https://127.0.0.1:{}", loopback_port(self.hostname(), self.port()))
+    }
+
     /// Return a response to the next `OPTIONS` request made against the endpoint
     /// which matches the specified path.
     fn options(&self, path: &'static str) -> HttpResponse<Self> {
@@ -135,5 +173,21 @@ pub trait HttpEndpoint: Send + Copy {
         http_response(*self, Method::Extension(http_verb.to_string()), path)
     }
 
+    /// Return a response to the next GraphQL request made against the
+    /// endpoint's `HttpEndpoint::graphql_path()` which requests the specified
+    /// `operation`.
+    fn graphql(&self, operation: &str) -> HttpResponse<Self> {
+        http_response(*self, Method::Post, self.graphql_path()).matching_graphql_operation(operation)
+    }
+
+    /// Return a response to the next JSON-RPC request made against the
+    /// endpoint's `HttpEndpoint::jsonrpc_path()` which calls the specified
+    /// `method`.
+    ///
+    /// Batch requests must call the `method` with every one of their calls.
+    fn jsonrpc(&self, method: &str) -> HttpResponse<Self> {
+        http_response(*self, Method::Post, self.jsonrpc_path()).matching_jsonrpc_method(method)
+    }
+
 }
 
@@ -18,6 +18,8 @@ mod query;
 mod form;
 
 mod endpoint;
+mod encoding;
+mod reply;
 mod request;
 mod response;
 pub mod util;
@@ -29,6 +31,7 @@ pub use self::header::HttpHeader;
 pub use self::query::HttpQueryString;
 pub use self::form::{HttpFormData, HttpFormDataField};
 
+pub use self::reply::HttpReply;
 pub use self::request::HttpRequest;
 pub use self::endpoint::HttpEndpoint;
 pub use self::response::HttpResponse;
@@ -40,6 +43,41 @@ pub trait HttpResource {
     fn into_http_body(&mut self) -> HttpBody where Self: Sized;
 }
 
+/// A resource whose body has already been read, allowing it to be inspected
+/// multiple times.
+pub struct HttpBufferedResource {
+    headers: Headers,
+    body: HttpBody
+}
+
+impl HttpBufferedResource {
+
+    pub fn new<T: HttpResource>(resource: &mut T) -> HttpBufferedResource {
+        let body = resource.into_http_body();
+        HttpBufferedResource {
+            headers: resource.headers().clone(),
+            body: body
+        }
+    }
+
+    pub fn body(&self) -> &HttpBody {
+        &self.body
+    }
+
+}
+
+impl HttpResource for HttpBufferedResource {
+
+    fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    fn into_http_body(&mut self) -> HttpBody where Self: Sized {
+        self.body.clone()
+    }
+
+}
+
 impl HttpResource for Response {
 
     fn headers(&self) -> &Headers {
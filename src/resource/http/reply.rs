@@ -0,0 +1,96 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::io::Error;
+
+
+// External Dependencies ------------------------------------------------------
+use hyper::status::StatusCode;
+use hyper::header::{Header, Headers, HeaderFormat};
+
+
+// Internal Dependencies ------------------------------------------------------
+use resource::http::util;
+use resource::http::{HttpHeader, HttpBody};
+
+
+/// A response which is computed from an intercepted request by the handler of
+/// a provided `HttpResponse`.
+///
+/// Any values set on the reply take precedence over the ones configured on
+/// the `HttpResponse` itself.
+pub struct HttpReply {
+    status: Option<StatusCode>,
+    headers: Headers,
+    body: Option<HttpBody>,
+    error: Option<Error>
+}
+
+impl HttpReply {
+
+    /// Creates a new, empty reply.
+    pub fn new() -> HttpReply {
+        HttpReply {
+            status: None,
+            headers: Headers::new(),
+            body: None,
+            error: None
+        }
+    }
+
+    /// Sets the reply status.
+    pub fn with_status(mut self, status_code: StatusCode) -> Self {
+        self.status = Some(status_code);
+        self
+    }
+
+    /// Sets additional headers to be send with the reply.
+    pub fn with_headers(mut self, headers: Vec<HttpHeader>) -> Self {
+        for header in headers {
+            let (name, value) = util::http_header_into_tuple(header);
+            self.headers.set_raw(name, vec![value]);
+        }
+        self
+    }
+
+    /// Sets one additional header to be send with the reply.
+    pub fn with_header<H: Header + HeaderFormat>(mut self, header: H) -> Self {
+        self.headers.set(header);
+        self
+    }
+
+    /// Sets the reply body.
+    pub fn with_body<S: Into<HttpBody>>(mut self, body: S) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Sets a low level IO error to be returned once the reply is read.
+    pub fn with_error(mut self, error: Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+}
+
+impl Default for HttpReply {
+    fn default() -> HttpReply {
+        HttpReply::new()
+    }
+}
+
+
+// Internal -------------------------------------------------------------------
+pub fn http_reply_into_parts(
+    reply: HttpReply
+
+) -> (Option<StatusCode>, Headers, Option<HttpBody>, Option<Error>) {
+    (reply.status, reply.headers, reply.body, reply.error)
+}
+
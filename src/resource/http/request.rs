@@ -14,20 +14,35 @@ use std::any::Any;
 use colored::*;
 use httparse::Request;
 use hyper::method::Method;
-use hyper::header::Headers;
+use hyper::header::{Header, HeaderFormat, Headers};
+use json::JsonValue;
 
 
 // Internal Dependencies ------------------------------------------------------
 use mock::MockRequest;
 use super::{HttpResource, HttpBody};
-use super::body::http_body_from_parts;
-
-
-// Noir Internal --------------------------------------------------------------
+use super::form::{HttpFormDataField, http_form_into_fields};
+use super::body::{
+    http_body_from_parts,
+    http_body_data,
+    http_body_text,
+    http_body_json,
+    http_body_form
+};
+
+
+/// A HTTP request made by a tested API which was intercepted by noir.
+///
+/// Handed to the handlers of provided responses, see
+/// `HttpResponse::with_handler()`.
 pub struct HttpRequest {
+    /// The hostname the request was made to.
     pub hostname: String,
+    /// The port the request was made to.
     pub port: u16,
+    /// The method of the request.
     pub method: Method,
+    /// The path of the request, including its query string.
     pub path: String,
     headers: Headers,
     body: Option<HttpBody>
@@ -35,26 +50,80 @@ pub struct HttpRequest {
 
 impl HttpRequest  {
 
+    #[doc(hidden)]
     pub fn new(
         hostname: String,
         port: u16,
         req: Request,
         data: Vec<u8>
 
-    ) -> HttpRequest {
+    ) -> Result<HttpRequest, String> {
+
+        let headers = try!(Headers::from_raw(req.headers).map_err(|err| {
+            format!("Invalid headers ({})", err)
+        }));
+
+        let method = req.method.unwrap_or("");
+        let method = try!(method.parse::<Method>().map_err(|_| {
+            format!("Invalid method \"{}\"", method)
+        }));
 
-        let headers = Headers::from_raw(req.headers).unwrap();
         let body = http_body_from_parts(data, &headers);
 
-        HttpRequest {
+        Ok(HttpRequest {
             hostname: hostname,
             port: port,
-            method: req.method.unwrap().parse().unwrap(),
-            path: req.path.unwrap().to_string(),
+            method: method,
+            path: req.path.unwrap_or("").to_string(),
             headers: headers,
             body: Some(body)
-        }
+        })
+
+    }
+
+    /// Returns the body of the request.
+    pub fn body(&self) -> Option<&HttpBody> {
+        self.body.as_ref()
+    }
+
+    /// Returns the header of type `H` from the request, if present.
+    pub fn header<H: Header + HeaderFormat>(&self) -> Option<&H> {
+        self.headers.get::<H>()
+    }
+
+    /// Returns the raw body data of the request.
+    pub fn data(&self) -> &[u8] {
+        self.body.as_ref().map_or(&[][..], http_body_data)
+    }
+
+    /// Returns the body of the request as a string slice, if it is valid
+    /// UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        self.body.as_ref().and_then(http_body_text)
+    }
+
+    /// Returns the parsed JSON body of the request, if the request has a
+    /// `application/json` content type and a valid body.
+    pub fn json(&self) -> Option<JsonValue> {
+        self.body.as_ref().and_then(http_body_json)
+    }
 
+    /// Returns the value of the form field `name`, if the request has a form
+    /// body which contains the field.
+    pub fn form_field(&self, name: &str) -> Option<String> {
+        let form = match self.body.as_ref().and_then(http_body_form) {
+            Some(form) => form,
+            None => return None
+        };
+        http_form_into_fields(form).into_iter().filter_map(|field| {
+            match field {
+                HttpFormDataField::Field(ref n, ref value) if n == name => {
+                    Some(value.to_string())
+                },
+                _ => None
+            }
+
+        }).next()
     }
 
     fn host(&self) -> String {
@@ -68,7 +137,8 @@ impl HttpRequest  {
         }
     }
 
-    fn url(&self) -> String {
+    /// Returns the full URL of the request, excluding its path.
+    pub fn url(&self) -> String {
         match self.port {
             443 | 80 => format!("{}://{}", self.protocol(), self.hostname),
             _ => format!("{}://{}", self.protocol(), self.host())
@@ -7,24 +7,40 @@
 // except according to those terms.
 
 // STD Dependencies -----------------------------------------------------------
-use std::io::Error;
+use std::time::Duration;
+use std::io::{Error, ErrorKind};
 
 
 // External Dependencies ------------------------------------------------------
 use colored::*;
+use regex::Regex;
+use json::JsonValue;
 use hyper::method::Method;
 use hyper::server::Response as ServerResponse;
 use hyper::status::StatusCode;
-use hyper::header::{Header, Headers, HeaderFormat, ContentType, ContentLength};
+use hyper::header::{
+    Header, Headers, HeaderFormat,
+    ContentType, ContentLength, ContentEncoding, Encoding, TransferEncoding
+};
 
 
 // Internal Dependencies ------------------------------------------------------
 use Options;
+use util::format_mismatch;
 use super::request::HttpRequest;
 use super::endpoint::HttpEndpoint;
-use mock::{MockRequest, MockResponse};
+use mock::{MockRequest, MockResponse, MockOrder, MockFault, MockUpgrade};
 use resource::http::util;
-use resource::http::{HttpHeader, HttpBody, HttpQueryString};
+use resource::http::{HttpResource, HttpHeader, HttpBody, HttpQueryString, HttpReply};
+use super::reply::http_reply_into_parts;
+use super::encoding::{encode_body, chunk_body};
+use super::body::{http_body_matches, http_body_text};
+use resource::graphql::graphql_operation_name;
+use resource::jsonrpc::{jsonrpc_methods, jsonrpc_response};
+use resource::websocket::{
+    WebSocket, WebSocketSession,
+    websocket_session, websocket_validate, websocket_accept
+};
 
 
 /// A mocked HTTP response that is being provided to a testable API.
@@ -41,13 +57,35 @@ pub struct HttpResponse<E: HttpEndpoint> {
     response_headers: Headers,
     response_body: Option<HttpBody>,
     response_error: Option<Error>,
+    response_handler: Option<Box<Fn(&HttpRequest) -> HttpReply + Send>>,
+    response_encoding: Option<Encoding>,
+    response_chunks: Option<Vec<usize>>,
+    response_trailers: Headers,
+    response_websocket: Option<WebSocket>,
+    upgrade: Option<WebSocketSession>,
+
+    fault_delay: Option<Duration>,
+    fault_interrupt: Option<(usize, Option<ErrorKind>)>,
+    fault_malformed: bool,
+    fault: Option<MockFault>,
+
+    matching_headers: Headers,
+    matching_body: Option<HttpBody>,
+    matching_body_regex: Option<Regex>,
+    matching_operation: Option<String>,
+    matching_jsonrpc_method: Option<String>,
 
     expected_headers: Headers,
     unexpected_headers: Vec<String>,
     expected_body: Option<HttpBody>,
     compare_exact: bool,
 
-    request: Option<Box<MockRequest>>
+    min_requests: usize,
+    max_requests: Option<usize>,
+    requests: Vec<Box<MockRequest>>,
+
+    optional: bool,
+    order: MockOrder
 }
 
 impl<E: HttpEndpoint> HttpResponse<E> {
@@ -102,6 +140,131 @@ impl<E: HttpEndpoint> HttpResponse<E> {
         self
     }
 
+    /// Sets a handler which computes the response from the intercepted
+    /// request.
+    ///
+    /// The handler is called for every request the response is fetched by.
+    /// The status, headers, body and error of the returned `HttpReply` take
+    /// precedence over the ones set on the response itself.
+    ///
+    /// Any expected headers and bodies are still validated against the
+    /// request.
+    pub fn with_handler<F>(mut self, handler: F) -> Self
+        where F: Fn(&HttpRequest) -> HttpReply + Send + 'static {
+        self.response_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets the `result` which is returned for every call of a JSON-RPC
+    /// request.
+    ///
+    /// The `id` of each call is copied into its response, notifications are
+    /// not answered.
+    pub fn with_jsonrpc_result(self, result: JsonValue) -> Self {
+        self.with_handler(move |request| jsonrpc_reply(request, "result", &result))
+    }
+
+    /// Sets the `error` object with the specified `code` and `message` which
+    /// is returned for every call of a JSON-RPC request.
+    ///
+    /// The `id` of each call is copied into its response, notifications are
+    /// not answered.
+    pub fn with_jsonrpc_error(self, code: i64, message: &str) -> Self {
+        let error = object! {
+            "code" => code,
+            "message" => message
+        };
+        self.with_handler(move |request| jsonrpc_reply(request, "error", &error))
+    }
+
+    /// Compresses the response body with the given content encoding and sets
+    /// the `Content-Encoding` header of the response.
+    ///
+    /// The body itself is still specified in its plain, uncompressed form.
+    ///
+    /// # Panics
+    ///
+    /// If the encoding is neither `Encoding::Gzip`, `Encoding::Deflate` nor
+    /// `Encoding::Identity`.
+    pub fn with_content_encoding(mut self, encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip | Encoding::Deflate | Encoding::Identity => {
+                self.response_encoding = Some(encoding);
+            },
+            _ => panic!("noir: Unsupported content encoding \"{}\".", encoding)
+        }
+        self
+    }
+
+    /// Sends the response body with `Transfer-Encoding: chunked`, split into
+    /// chunks of the given sizes.
+    ///
+    /// Any body data left after the last chunk is send as one final chunk.
+    pub fn with_chunks(mut self, sizes: Vec<usize>) -> Self {
+        self.response_chunks = Some(sizes);
+        self
+    }
+
+    /// Sets one trailer header to be send after the body of the response.
+    ///
+    /// Trailers require the body to be chunked, if no chunks were set via
+    /// `HttpResponse::with_chunks()` the body is send as a single chunk.
+    pub fn with_trailer<H: Header + HeaderFormat>(mut self, header: H) -> Self {
+        self.response_trailers.set(header);
+        if self.response_chunks.is_none() {
+            self.response_chunks = Some(Vec::new());
+        }
+        self
+    }
+
+    /// Delays the response by the given duration before its first byte can be
+    /// read.
+    ///
+    /// If the delay exceeds the read timeout set by the client, reads will fail
+    /// with an `ErrorKind::WouldBlock` error once the timeout has elapsed.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.fault_delay = Some(delay);
+        self
+    }
+
+    /// Closes the connection after the given number of body bytes have been
+    /// read.
+    pub fn with_close_after(mut self, bytes: usize) -> Self {
+        self.fault_interrupt = Some((bytes, None));
+        self
+    }
+
+    /// Resets the connection after the given number of body bytes have been
+    /// read.
+    ///
+    /// Any further reads will fail with an `ErrorKind::ConnectionReset` error.
+    pub fn with_reset_after(mut self, bytes: usize) -> Self {
+        self.fault_interrupt = Some((bytes, Some(ErrorKind::ConnectionReset)));
+        self
+    }
+
+    /// Replaces the response with malformed HTTP data which cannot be parsed
+    /// by the client.
+    pub fn with_malformed_response(mut self) -> Self {
+        self.fault_malformed = true;
+        self
+    }
+
+    /// Accepts the request as a WebSocket handshake and holds the specified
+    /// `websocket` conversation with the API once the connection has been
+    /// upgraded.
+    ///
+    /// The frames received from the API are validated together with the
+    /// request, expected frames which have not been received by then are
+    /// reported as missing.
+    ///
+    /// Upgrades are only supported for requests made to the endpoint's
+    /// `HttpEndpoint::loopback_url()` or through the `HttpProxy`.
+    pub fn with_websocket(mut self, websocket: WebSocket) -> Self {
+        self.response_websocket = Some(websocket);
+        self
+    }
+
     /// Sets the response's configuration options.
     ///
     /// This allows to change or override the default response behaviour.
@@ -110,6 +273,62 @@ impl<E: HttpEndpoint> HttpResponse<E> {
         self
     }
 
+    /// Sets one additional header that a request must contain in order to be
+    /// matched with the response.
+    ///
+    /// Requests without the header will not receive the response, which allows
+    /// to distinguish multiple requests to the same path.
+    pub fn matching_header<H: Header + HeaderFormat>(mut self, header: H) -> Self {
+        self.matching_headers.set(header);
+        self
+    }
+
+    /// Sets a body that a request must contain in order to be matched with
+    /// the response.
+    ///
+    /// JSON and form bodies match when the request body contains all of the
+    /// specified values, additional keys and fields on the request are
+    /// ignored. All other bodies need to be identical.
+    pub fn matching_body<S: Into<HttpBody>>(mut self, body: S) -> Self {
+        self.matching_body = Some(body.into());
+        self
+    }
+
+    /// Sets a regular expression that the body of a request must match in
+    /// order to be matched with the response.
+    ///
+    /// # Panics
+    ///
+    /// If the regular expression is invalid.
+    pub fn matching_body_regex(mut self, pattern: &str) -> Self {
+        self.matching_body_regex = Some(
+            Regex::new(pattern).expect("noir: Invalid body regular expression.")
+        );
+        self
+    }
+
+    /// Sets the name of the GraphQL operation that a request must perform in
+    /// order to be matched with the response.
+    ///
+    /// The operation is taken from the `operationName` of the request body or
+    /// from the first operation defined in its `query`.
+    pub fn matching_graphql_operation(mut self, operation: &str) -> Self {
+        self.matching_operation = Some(operation.to_string());
+        self
+    }
+
+    /// Sets the name of the remote procedure that a JSON-RPC request must call
+    /// in order to be matched with the response.
+    ///
+    /// Batch requests are only matched in case all of their calls invoke the
+    /// procedure, since a single response answers the whole batch. Batches
+    /// which mix different procedures can be answered by a response without
+    /// a method, e.g. `HttpEndpoint::post()` with `with_jsonrpc_result()`.
+    pub fn matching_jsonrpc_method(mut self, method: &str) -> Self {
+        self.matching_jsonrpc_method = Some(method.to_string());
+        self
+    }
+
     /// Sets one additional header that should be present on the request to the
     /// response.
     ///
@@ -169,6 +388,14 @@ impl<E: HttpEndpoint> HttpResponse<E> {
     /// This allows for simpler and more fine grained assertions against JSON
     /// requests.
     ///
+    /// ##### application/xml and text/xml
+    ///
+    /// XML documents are compared structurally, ignoring namespace prefixes
+    /// and the order of attributes. Child elements with the same name are
+    /// compared in order, but __additional attributes and child elements on
+    /// request elements are ignored__, as is their text when the expected
+    /// element has none.
+    ///
     /// ##### All other mime types
     ///
     /// These are compared on a byte by byte basis.
@@ -199,6 +426,12 @@ impl<E: HttpEndpoint> HttpResponse<E> {
     /// In contrast to `HttpResponse::expected_body()` __additional keys on
     /// request objects are compared and will fail the test__.
     ///
+    /// ##### application/xml and text/xml
+    ///
+    /// In contrast to `HttpResponse::expected_body()` __additional attributes,
+    /// child elements and text on request elements are compared and will fail
+    /// the test__.
+    ///
     /// ##### All other mime types
     ///
     /// See `HttpResponse::expected_body()`.
@@ -212,6 +445,92 @@ impl<E: HttpEndpoint> HttpResponse<E> {
         self
     }
 
+    /// Sets the exact number of requests the response should be fetched by.
+    ///
+    /// By default, a response is fetched by exactly one request.
+    ///
+    /// ### Test Failure
+    ///
+    /// If the response is fetched by fewer requests. Any further requests will
+    /// not be matched with the response.
+    pub fn times(mut self, count: usize) -> Self {
+        self.min_requests = count;
+        self.max_requests = Some(count);
+        self
+    }
+
+    /// Sets the minimum number of requests the response should be fetched by,
+    /// without any upper limit.
+    ///
+    /// ### Test Failure
+    ///
+    /// If the response is fetched by fewer requests.
+    pub fn at_least(mut self, count: usize) -> Self {
+        self.min_requests = count;
+        self.max_requests = None;
+        self
+    }
+
+    /// Sets the maximum number of requests the response can be fetched by,
+    /// including none at all.
+    ///
+    /// Any further requests will not be matched with the response.
+    pub fn at_most(mut self, count: usize) -> Self {
+        self.min_requests = 0;
+        self.max_requests = Some(count);
+        self
+    }
+
+    /// Allows the response to be fetched by any number of requests, including
+    /// none at all.
+    pub fn any_number_of_times(mut self) -> Self {
+        self.min_requests = 0;
+        self.max_requests = None;
+        self
+    }
+
+    /// Asserts that the endpoint is never requested.
+    ///
+    /// ### Test Failure
+    ///
+    /// If a matching request is made, it will be reported as an unexpected
+    /// request.
+    pub fn never(self) -> Self {
+        self.times(0)
+    }
+
+    /// Marks the response as optional, allowing it to not be fetched at all.
+    ///
+    /// If the response is fetched, the number of requests set via
+    /// `HttpResponse::times()` or `HttpResponse::at_least()` still applies.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// Allows the response to be fetched at any point during the test,
+    /// regardless of the order in which it was provided.
+    ///
+    /// Useful for APIs which request multiple upstreams concurrently.
+    pub fn unordered(mut self) -> Self {
+        self.order = MockOrder::Unordered;
+        self
+    }
+
+    /// Adds the response to a named ordering group.
+    ///
+    /// Responses of the same group must be fetched in the order they were
+    /// provided, but can be fetched at any point relative to responses of
+    /// other groups.
+    ///
+    /// ### Test Failure
+    ///
+    /// If the response is fetched out of order within its group.
+    pub fn in_group(mut self, name: &str) -> Self {
+        self.order = MockOrder::Group(name.to_string());
+        self
+    }
+
     /// Dumps the request headers and body for this response.
     ///
     /// ### Test Failure
@@ -229,9 +548,15 @@ impl<E: HttpEndpoint> HttpResponse<E> {
     }
 
     // Internal ---------------------------------------------------------------
-    fn http_response(&mut self) -> Vec<u8> {
+    fn http_response(
+        &self,
+        status: Option<StatusCode>,
+        mut headers: Headers,
+        body: Option<HttpBody>
 
-        let (content_mime, mut body) = if let Some(body) = self.response_body.take() {
+    ) -> Vec<u8> {
+
+        let (content_mime, mut body) = if let Some(body) = body {
             util::http_body_into_parts(body)
 
         } else {
@@ -242,25 +567,64 @@ impl<E: HttpEndpoint> HttpResponse<E> {
             // Set Content-Type based on body data if:
             // A. The body has a Mime
             // B. No other Content-Type has been set on the request
-            if !self.response_headers.has::<ContentType>() {
-                self.response_headers.set(ContentType(content_mime));
+            if !headers.has::<ContentType>() {
+                headers.set(ContentType(content_mime));
+            }
+        }
+
+        // Compress the plain body data
+        if let Some(encoding) = self.response_encoding.as_ref() {
+            body = body.map(|data| encode_body(encoding, data));
+            headers.set(ContentEncoding(vec![encoding.clone()]));
+        }
+
+        // Chunked bodies are serialized manually in order to support custom
+        // chunk boundaries and trailers
+        if let Some(chunks) = self.response_chunks.as_ref() {
+
+            headers.remove::<ContentLength>();
+            headers.set(TransferEncoding(vec![Encoding::Chunked]));
+
+            let trailers = self.response_trailers.iter().map(|header| {
+                header.name().to_string()
+
+            }).collect::<Vec<String>>();
+
+            if !trailers.is_empty() {
+                headers.set_raw("Trailer", vec![trailers.join(", ").into_bytes()]);
             }
+
+            let mut data = format!(
+                "HTTP/1.1 {}\r\n{}\r\n",
+                status.unwrap_or(StatusCode::Ok),
+                headers
+
+            ).into_bytes();
+
+            data.append(&mut chunk_body(
+                &body.unwrap_or_else(Vec::new)[..],
+                &chunks[..],
+                &self.response_trailers
+            ));
+
+            return data;
+
         }
 
         // Set Content-Length based on body (unless already specified)
-        if !self.response_headers.has::<ContentLength>() && body.is_none() {
+        if !headers.has::<ContentLength>() && body.is_none() {
             // Explicitly set 0 to avoid having to wait for read timeouts
-            self.response_headers.set(ContentLength(0));
+            headers.set(ContentLength(0));
         }
 
         let mut data = Vec::new();
         {
             let mut res = ServerResponse::new(
                 &mut data,
-                &mut self.response_headers
+                &mut headers
             );
 
-            if let Some(status) = self.response_status {
+            if let Some(status) = status {
                 *res.status_mut() = status;
             }
 
@@ -276,40 +640,258 @@ impl<E: HttpEndpoint> HttpResponse<E> {
 
     }
 
+    fn http_fault(&self, data: &[u8]) -> Option<MockFault> {
+
+        if self.fault_delay.is_none() && self.fault_interrupt.is_none() {
+            return None;
+        }
+
+        // Interrupts are relative to the start of the response body
+        let header_len = data.windows(4).position(|w| w == b"\r\n\r\n").map_or(
+            data.len(),
+            |index| index + 4
+        );
+
+        Some(MockFault {
+            delay: self.fault_delay,
+            limit: self.fault_interrupt.map(|(bytes, _)| header_len + bytes),
+            error: self.fault_interrupt.and_then(|(_, error)| error)
+        })
+
+    }
+
+    fn mismatch_reasons(&self, request: &HttpRequest) -> Option<Vec<String>> {
+
+        // Responses for other endpoints are never considered
+        if self.endpoint.hostname() != request.hostname
+            || self.endpoint.port() != request.port {
+            return None;
+        }
+
+        let mut reasons = Vec::new();
+        if let Some(max) = self.max_requests {
+            if max == 0 {
+                reasons.push(format!(
+                    "{}",
+                    "Response was expected to never be fetched.".yellow()
+                ));
+
+            } else if self.requests.len() >= max {
+                reasons.push(format!(
+                    "{} {} {}",
+                    "Response was already fetched by".yellow(),
+                    format!("{}", self.requests.len()).red().bold(),
+                    "previous request(s).".yellow()
+                ));
+            }
+        }
+
+        if self.method != request.method {
+            reasons.push(format_mismatch(
+                "Method",
+                format!("{}", self.method).as_str(),
+                format!("{}", request.method).as_str()
+            ));
+        }
+
+        let (path, query) = util::split_path_query(self.path.as_str());
+        let (request_path, request_query) = util::split_path_query(request.path.as_str());
+        if path != request_path {
+            reasons.push(format_mismatch("Path", path, request_path));
+
+        } else if !util::query_matches(query, request_query) {
+            reasons.push(format_mismatch("Query string", query, request_query));
+        }
+
+        self.header_mismatches(request, &mut reasons);
+
+        if let Some(operation) = self.matching_operation.as_ref() {
+            let actual = request.body().and_then(graphql_operation_name);
+            if actual.as_ref() != Some(operation) {
+                reasons.push(format_mismatch(
+                    "GraphQL operation",
+                    operation.as_str(),
+                    actual.as_ref().map_or("none", |name| name.as_str())
+                ));
+            }
+        }
+
+        if let Some(method) = self.matching_jsonrpc_method.as_ref() {
+            let actual = request.body().map_or_else(Vec::new, jsonrpc_methods);
+            if actual.is_empty() || actual.iter().any(|name| name != method) {
+                let names = actual.join(", ");
+                reasons.push(format_mismatch(
+                    "JSON-RPC method",
+                    method.as_str(),
+                    if actual.is_empty() {
+                        "none"
+
+                    } else {
+                        names.as_str()
+                    }
+                ));
+            }
+        }
+
+        if !self.body_matches(request) {
+            reasons.push(format!("{}", "Body does not match.".yellow()));
+        }
+
+        Some(reasons)
+
+    }
+
+    fn header_mismatches(&self, request: &HttpRequest, reasons: &mut Vec<String>) {
+        for header in self.matching_headers.iter() {
+            let expected = header.value_string();
+            match request.headers().get_raw(header.name()) {
+                Some(actual) if actual[0].as_slice() == expected.as_bytes() => {},
+                Some(actual) => reasons.push(format_mismatch(
+                    format!("Header \"{}\"", header.name()).as_str(),
+                    expected.as_str(),
+                    String::from_utf8_lossy(&actual[0][..]).as_ref()
+                )),
+                None => reasons.push(format!(
+                    "{} \"{}\" {}",
+                    "Header".yellow(),
+                    header.name().blue().bold(),
+                    "is missing.".yellow()
+                ))
+            }
+        }
+    }
+
+    fn body_matches(&self, request: &HttpRequest) -> bool {
+
+        let body = match request.body() {
+            Some(body) => body,
+            None => return self.matching_body.is_none() && self.matching_body_regex.is_none()
+        };
+
+        if let Some(expected) = self.matching_body.as_ref() {
+            if !http_body_matches(expected, body, &self.options) {
+                return false;
+            }
+        }
+
+        if let Some(regex) = self.matching_body_regex.as_ref() {
+            if !http_body_text(body).map_or(false, |text| regex.is_match(text)) {
+                return false;
+            }
+        }
+
+        true
+
+    }
+
 }
 
 impl<E: HttpEndpoint> MockResponse for HttpResponse<E> {
 
     fn matches(&self, request: &Box<MockRequest>) -> bool {
         if let Some(request) = HttpRequest::downcast_ref(request) {
-            self.request.is_none()
-                && self.method == request.method
-                && self.endpoint.hostname() == request.hostname
-                && self.endpoint.port() == request.port
-                && self.path == request.path
+            self.mismatch_reasons(request).map_or(false, |r| r.is_empty())
 
         } else {
             false
         }
     }
 
+    fn mismatches(&self, request: &Box<MockRequest>) -> Option<(String, Vec<String>)> {
+        if let Some(request) = HttpRequest::downcast_ref(request) {
+            self.mismatch_reasons(request).map(|reasons| {
+                (format!(
+                    "{} {} \"{}{}\"",
+                    format!("{}", self.method).cyan(),
+                    "response provided for".yellow(),
+                    self.endpoint.url().cyan(),
+                    self.path.cyan()
+
+                ), reasons)
+            })
+
+        } else {
+            None
+        }
+    }
+
     fn respond(
         &mut self,
         request: Box<MockRequest>
 
     ) -> Result<Vec<u8>, Error> {
 
-        self.request = Some(request);
+        // Status, headers, body and errors are cloned to support responses
+        // which are fetched repeatedly
+        let mut status = self.response_status;
+        let mut headers = self.response_headers.clone();
+        let mut body = self.response_body.clone();
+        let mut error = self.response_error.as_ref().map(|err| {
+            Error::new(err.kind(), format!("{}", err))
+        });
+
+        // Replies computed by a handler take precedence
+        if let Some(handler) = self.response_handler.as_ref() {
+            if let Some(request) = HttpRequest::downcast_ref(&request) {
+                let (
+                    reply_status,
+                    reply_headers,
+                    reply_body,
+                    reply_error
+
+                ) = http_reply_into_parts(handler(request));
+
+                status = reply_status.or(status);
+                body = reply_body.or(body);
+                error = reply_error.or(error);
+
+                for header in reply_headers.iter() {
+                    headers.set_raw(
+                        header.name().to_string(),
+                        vec![header.value_string().into_bytes()]
+                    );
+                }
+            }
+        }
 
-        if let Some(err) = self.response_error.take() {
+        let websocket_key = HttpRequest::downcast_ref(&request).and_then(|request| {
+            request.headers.get_raw("Sec-WebSocket-Key").and_then(|values| {
+                values.get(0).map(|value| String::from_utf8_lossy(value).to_string())
+            })
+        });
+
+        self.requests.push(request);
+
+        if let Some(err) = error {
+            self.fault = None;
             Err(err)
 
         } else {
-            Ok(self.http_response())
+            let data = if self.fault_malformed {
+                MALFORMED_RESPONSE.to_vec()
+
+            } else if let Some(websocket) = self.response_websocket.as_ref() {
+                self.upgrade = Some(websocket_session(websocket, &self.options));
+                websocket_response(headers, websocket_key)
+
+            } else {
+                self.http_response(status, headers, body)
+            };
+
+            self.fault = self.http_fault(&data[..]);
+            Ok(data)
         }
 
     }
 
+    fn fault(&self) -> Option<MockFault> {
+        self.fault.clone()
+    }
+
+    fn upgrade(&mut self) -> Option<Box<MockUpgrade>> {
+        self.upgrade.take().map(|session| Box::new(session) as Box<MockUpgrade>)
+    }
+
     fn validate(
         &mut self,
         response_index: usize,
@@ -317,18 +899,19 @@ impl<E: HttpEndpoint> MockResponse for HttpResponse<E> {
 
     ) -> Vec<String> {
 
-        if let Some(request) = self.request.as_mut() {
+        let mut errors = Vec::new();
 
-            let mut errors = Vec::new();
+        for (index, request) in self.requests.iter_mut().enumerate() {
 
             let request = HttpRequest::downcast_mut(request).unwrap();
             if self.dump_request {
                 util::dump_http_resource(
-                    &mut errors, request, "Request"
+                    &mut errors, request, "Request", None
                 );
             }
 
-            if response_index != request_index {
+            // Only the first request is validated for its order
+            if index == 0 && response_index != request_index {
                 errors.push(format!(
                     "{} {} {}{} {} {}{}",
                     "Response fetched out of order,".yellow(),
@@ -353,19 +936,53 @@ impl<E: HttpEndpoint> MockResponse for HttpResponse<E> {
                 &self.options
             ));
 
-            errors
+        }
 
-        } else {
-            vec![format!(
+        // Frames of upgraded connections
+        if let Some(websocket) = self.response_websocket.as_ref() {
+            if !self.requests.is_empty() {
+                errors.append(&mut websocket_validate(websocket));
+            }
+        }
+
+        // Optional responses are not required to be fetched at all
+        let count = self.requests.len();
+        if count == 0 && self.optional {
+            return errors;
+        }
+
+        if count == 0 && self.min_requests == 1 && self.max_requests == Some(1) {
+            errors.push(format!(
                 "{} {} {} {}{}",
                 "Expected".yellow(),
                 "a request".green().bold(),
                 "for the response, but got".yellow(),
                 "none".red().bold(),
                 ".".yellow()
-            )]
+            ));
+
+        } else if count < self.min_requests {
+            errors.push(format!(
+                "{} {} {} {}{}",
+                "Expected".yellow(),
+                format!(
+                    "{} {} request(s)",
+                    if self.max_requests.is_some() { "exactly" } else { "at least" },
+                    self.min_requests
+
+                ).green().bold(),
+                "for the response, but got".yellow(),
+                format!("{}", count).red().bold(),
+                ".".yellow()
+            ));
         }
 
+        errors
+
+    }
+
+    fn order(&self) -> MockOrder {
+        self.order.clone()
     }
 
     fn validate_header(
@@ -387,7 +1004,40 @@ impl<E: HttpEndpoint> MockResponse for HttpResponse<E> {
 
 }
 
+// Helpers --------------------------------------------------------------------
+fn jsonrpc_reply(request: &HttpRequest, member: &str, value: &JsonValue) -> HttpReply {
+    match request.json().and_then(|json| jsonrpc_response(&json, member, value)) {
+        Some(body) => HttpReply::new().with_body(body),
+        None => HttpReply::new()
+    }
+}
+
+
+fn websocket_response(mut headers: Headers, key: Option<String>) -> Vec<u8> {
+
+    headers.set_raw("Upgrade", vec![b"websocket".to_vec()]);
+    headers.set_raw("Connection", vec![b"Upgrade".to_vec()]);
+
+    if let Some(key) = key {
+        headers.set_raw(
+            "Sec-WebSocket-Accept",
+            vec![websocket_accept(key.as_str()).into_bytes()]
+        );
+    }
+
+    format!(
+        "HTTP/1.1 {}\r\n{}\r\n",
+        StatusCode::SwitchingProtocols,
+        headers
+
+    ).into_bytes()
+
+}
+
+
 // Internal -------------------------------------------------------------------
+const MALFORMED_RESPONSE: &'static [u8] = b"HTTP/1.1 Malformed\r\nNoir\r\n\r\n";
+
 pub fn http_response<E: HttpEndpoint>(
     endpoint: E,
     method: Method,
@@ -407,13 +1057,35 @@ pub fn http_response<E: HttpEndpoint>(
         response_headers: Headers::new(),
         response_body: None,
         response_error: None,
+        response_handler: None,
+        response_encoding: None,
+        response_chunks: None,
+        response_trailers: Headers::new(),
+        response_websocket: None,
+        upgrade: None,
+
+        fault_delay: None,
+        fault_interrupt: None,
+        fault_malformed: false,
+        fault: None,
+
+        matching_headers: Headers::new(),
+        matching_body: None,
+        matching_body_regex: None,
+        matching_operation: None,
+        matching_jsonrpc_method: None,
 
         unexpected_headers: Vec::new(),
         expected_headers: Headers::new(),
         expected_body: None,
         compare_exact: false,
 
-        request: None
+        min_requests: 1,
+        max_requests: Some(1),
+        requests: Vec::new(),
+
+        optional: false,
+        order: MockOrder::Strict
     }
 }
 
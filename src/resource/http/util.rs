@@ -8,24 +8,27 @@
 
 // STD Dependencies -----------------------------------------------------------
 use std::str;
+use std::collections::BTreeMap;
 
 
 // External Dependencies ------------------------------------------------------
 use colored::*;
+use url::form_urlencoded;
 use hyper::mime::Mime;
 use hyper::status::StatusCode;
+use hyper::version::HttpVersion;
 use hyper::header::{Headers, ContentType};
 
 
 // Internal Dependencies ------------------------------------------------------
 use Options;
 use super::{HttpBody, HttpResource, HttpQueryString};
-use super::body::{http_body_from_parts, format_http_body, validate_http_body};
+use super::body::{format_http_body, validate_http_body};
 use super::header::{validate_http_headers, format_http_headers};
 
 
 // Re-Exports -----------------------------------------------------------------
-pub use super::body::http_body_into_parts;
+pub use super::body::{http_body_data, http_body_from_parts, http_body_into_parts};
 pub use super::header::http_header_into_tuple;
 
 
@@ -48,6 +51,74 @@ pub fn path_with_query(path: &str, query: HttpQueryString) -> String {
 
 }
 
+pub fn split_path_query(path: &str) -> (&str, &str) {
+
+    // Ignore hash
+    let (path, _) = path.split_at(path.find('#').unwrap_or(path.len()));
+    let (path, query) = path.split_at(path.find('?').unwrap_or(path.len()));
+
+    (path, query.trim_left_matches('?'))
+
+}
+
+pub fn query_matches(expected: &str, actual: &str) -> bool {
+
+    // Compare query string parameters regardless of the order of their names,
+    // but keep the order of repeated parameters like `a[]=1&a[]=2`
+    let mut expected_groups = BTreeMap::new();
+    for (key, value) in form_urlencoded::parse(expected.as_bytes()).into_owned() {
+        expected_groups.entry(key).or_insert_with(Vec::new).push(value);
+    }
+
+    let mut actual_groups = BTreeMap::new();
+    for (key, value) in form_urlencoded::parse(actual.as_bytes()).into_owned() {
+        actual_groups.entry(key).or_insert_with(Vec::new).push(value);
+    }
+
+    expected_groups == actual_groups
+
+}
+
+pub fn path_with_default_query(path: &str, defaults: &[(String, String)]) -> String {
+
+    // Split off hash and existing query string
+    let (path, hash) = path.split_at(path.find('#').unwrap_or(path.len()));
+    let (path, query) = path.split_at(path.find('?').unwrap_or(path.len()));
+    let query = query.trim_left_matches('?');
+
+    // Parameters set on the path take precedence over the defaults
+    let existing = form_urlencoded::parse(query.as_bytes()).map(|(key, _)| {
+        key.into_owned()
+
+    }).collect::<Vec<String>>();
+
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    for &(ref key, ref value) in defaults {
+        if !existing.contains(key) {
+            serializer.append_pair(key.as_str(), value.as_str());
+        }
+    }
+
+    let default_query = serializer.finish();
+    let query = if default_query.is_empty() {
+        query.to_string()
+
+    } else if query.is_empty() {
+        default_query
+
+    } else {
+        format!("{}&{}", query, default_query)
+    };
+
+    if query.is_empty() {
+        format!("{}{}", path, hash)
+
+    } else {
+        format!("{}?{}{}", path, query, hash)
+    }
+
+}
+
 pub fn validate_http_resource<T: HttpResource>(
     context: &str,
     expected_status: Option<StatusCode>,
@@ -105,17 +176,31 @@ pub fn validate_http_resource<T: HttpResource>(
 pub fn dump_http_resource<T: HttpResource>(
     errors: &mut Vec<String>,
     actual: &mut T,
-    context: &str
+    context: &str,
+    version: Option<HttpVersion>
 ) {
 
     let headers = format_http_headers(actual.headers());
     let body = actual.into_http_body();
 
+    // Show the protocol version which was actually used, if known
+    let title = if let Some(version) = version {
+        format!(
+            "{}{}{}",
+            "headers dump (".yellow(),
+            format!("{}", version).cyan(),
+            "):".yellow()
+        )
+
+    } else {
+        "headers dump:".yellow().to_string()
+    };
+
     errors.push(
         format!(
             "{} {}\n\n        {}\n\n    {} {}",
             context.yellow(),
-            "headers dump:".yellow(),
+            title,
             headers,
             context.yellow(),
             format_http_body(&body)
@@ -0,0 +1,148 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::io::{Error, ErrorKind, Read, Write};
+
+
+// Noir Internal --------------------------------------------------------------
+pub const DATA: u8 = 0x0;
+pub const HEADERS: u8 = 0x1;
+pub const RST_STREAM: u8 = 0x3;
+pub const SETTINGS: u8 = 0x4;
+pub const PING: u8 = 0x6;
+pub const GOAWAY: u8 = 0x7;
+pub const WINDOW_UPDATE: u8 = 0x8;
+pub const CONTINUATION: u8 = 0x9;
+
+pub const FLAG_END_STREAM: u8 = 0x1;
+pub const FLAG_ACK: u8 = 0x1;
+pub const FLAG_END_HEADERS: u8 = 0x4;
+pub const FLAG_PADDED: u8 = 0x8;
+pub const FLAG_PRIORITY: u8 = 0x20;
+
+pub const SETTINGS_ENABLE_PUSH: u16 = 0x2;
+pub const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+pub const SETTINGS_MAX_FRAME_SIZE: u16 = 0x5;
+
+pub const NO_ERROR: u32 = 0x0;
+pub const PROTOCOL_ERROR: u32 = 0x1;
+
+#[derive(Debug)]
+pub struct Http2Frame {
+    pub kind: u8,
+    pub flags: u8,
+    pub stream_id: u32,
+    pub payload: Vec<u8>
+}
+
+impl Http2Frame {
+
+    pub fn new(kind: u8, flags: u8, stream_id: u32, payload: Vec<u8>) -> Http2Frame {
+        Http2Frame {
+            kind: kind,
+            flags: flags,
+            stream_id: stream_id,
+            payload: payload
+        }
+    }
+
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag == flag
+    }
+
+    /// Returns the payload of `DATA` and `HEADERS` frames with any padding
+    /// and priority information removed.
+    pub fn data(&self) -> Result<&[u8], Error> {
+
+        let mut start = 0;
+        let mut end = self.payload.len();
+
+        if self.has_flag(FLAG_PADDED) {
+            let padding = *try!(self.payload.get(0).ok_or_else(invalid_padding)) as usize;
+            start += 1;
+            end = try!(end.checked_sub(padding).ok_or_else(invalid_padding));
+        }
+
+        if self.kind == HEADERS && self.has_flag(FLAG_PRIORITY) {
+            start += 5;
+        }
+
+        if start > end {
+            Err(invalid_padding())
+
+        } else {
+            Ok(&self.payload[start..end])
+        }
+
+    }
+
+}
+
+pub fn read_frame<S: Read + ?Sized>(stream: &mut S) -> Result<Http2Frame, Error> {
+
+    let mut head = [0; 9];
+    try!(stream.read_exact(&mut head));
+
+    let length = ((head[0] as usize) << 16) | ((head[1] as usize) << 8) | head[2] as usize;
+    let stream_id = read_u32(&head[5..9]) & 0x7FFF_FFFF;
+
+    let mut payload = vec![0; length];
+    try!(stream.read_exact(&mut payload[..]));
+
+    Ok(Http2Frame::new(head[3], head[4], stream_id, payload))
+
+}
+
+pub fn write_frame<S: Write + ?Sized>(stream: &mut S, frame: &Http2Frame) -> Result<(), Error> {
+
+    let length = frame.payload.len();
+    let mut data = vec![
+        (length >> 16) as u8,
+        (length >> 8) as u8,
+        length as u8,
+        frame.kind,
+        frame.flags
+    ];
+
+    data.extend_from_slice(&u32_bytes(frame.stream_id & 0x7FFF_FFFF)[..]);
+    data.extend_from_slice(&frame.payload[..]);
+    stream.write_all(&data[..])
+
+}
+
+pub fn settings_payload(settings: &[(u16, u32)]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for &(id, value) in settings {
+        payload.push((id >> 8) as u8);
+        payload.push(id as u8);
+        payload.extend_from_slice(&u32_bytes(value)[..]);
+    }
+    payload
+}
+
+pub fn parse_settings(payload: &[u8]) -> Vec<(u16, u32)> {
+    payload.chunks(6).filter(|chunk| chunk.len() == 6).map(|chunk| {
+        (((chunk[0] as u16) << 8) | chunk[1] as u16, read_u32(&chunk[2..6]))
+
+    }).collect()
+}
+
+pub fn read_u32(data: &[u8]) -> u32 {
+    ((data[0] as u32) << 24) | ((data[1] as u32) << 16) | ((data[2] as u32) << 8) | data[3] as u32
+}
+
+pub fn u32_bytes(value: u32) -> [u8; 4] {
+    [(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn invalid_padding() -> Error {
+    Error::new(ErrorKind::InvalidData, "noir: Invalid HTTP/2 frame padding.")
+}
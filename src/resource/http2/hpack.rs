@@ -0,0 +1,529 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::collections::VecDeque;
+use std::io::{Error, ErrorKind};
+
+
+// Internal Dependencies ------------------------------------------------------
+use super::huffman;
+
+
+// Noir Internal --------------------------------------------------------------
+pub type HeaderField = (Vec<u8>, Vec<u8>);
+
+/// Encodes header fields as literals without indexing, so the encoder never
+/// has to keep any dynamic table state in sync with the peer.
+pub fn encode(fields: &[HeaderField]) -> Vec<u8> {
+    let mut block = Vec::new();
+    for &(ref name, ref value) in fields {
+        block.push(0x00);
+        encode_string(&mut block, &name[..]);
+        encode_string(&mut block, &value[..]);
+    }
+    block
+}
+
+pub struct Decoder {
+    table: VecDeque<HeaderField>,
+    table_size: usize,
+    max_table_size: usize
+}
+
+impl Decoder {
+
+    pub fn new() -> Decoder {
+        Decoder {
+            table: VecDeque::new(),
+            table_size: 0,
+            max_table_size: 4096
+        }
+    }
+
+    pub fn decode(&mut self, block: &[u8]) -> Result<Vec<HeaderField>, Error> {
+
+        let mut fields = Vec::new();
+        let mut offset = 0;
+
+        while offset < block.len() {
+
+            let byte = block[offset];
+
+            // Indexed Header Field
+            if byte & 0x80 != 0 {
+                let index = try!(decode_integer(block, &mut offset, 7));
+                let field = try!(self.field(index));
+                fields.push(field);
+
+            // Literal Header Field with Incremental Indexing
+            } else if byte & 0x40 != 0 {
+                let field = try!(self.literal(block, &mut offset, 6));
+                self.insert(field.clone());
+                fields.push(field);
+
+            // Dynamic Table Size Update
+            } else if byte & 0x20 != 0 {
+                let size = try!(decode_integer(block, &mut offset, 5));
+                self.max_table_size = size;
+                self.evict(0);
+
+            // Literal Header Field without Indexing / Never Indexed
+            } else {
+                let field = try!(self.literal(block, &mut offset, 4));
+                fields.push(field);
+            }
+
+        }
+
+        Ok(fields)
+
+    }
+
+    fn literal(&self, block: &[u8], offset: &mut usize, prefix: u8) -> Result<HeaderField, Error> {
+
+        let index = try!(decode_integer(block, offset, prefix));
+        let name = if index == 0 {
+            try!(decode_string(block, offset))
+
+        } else {
+            try!(self.field(index)).0
+        };
+
+        let value = try!(decode_string(block, offset));
+        Ok((name, value))
+
+    }
+
+    fn field(&self, index: usize) -> Result<HeaderField, Error> {
+        if index == 0 {
+            Err(invalid("Invalid header table index."))
+
+        } else if index <= STATIC_TABLE.len() {
+            let (name, value) = STATIC_TABLE[index - 1];
+            Ok((name.as_bytes().to_vec(), value.as_bytes().to_vec()))
+
+        } else {
+            self.table.get(index - STATIC_TABLE.len() - 1).cloned().ok_or_else(|| {
+                invalid("Invalid header table index.")
+            })
+        }
+    }
+
+    fn insert(&mut self, field: HeaderField) {
+        let size = field_size(&field);
+        self.evict(size);
+        if size <= self.max_table_size {
+            self.table_size += size;
+            self.table.push_front(field);
+        }
+    }
+
+    fn evict(&mut self, required: usize) {
+        while self.table_size + required > self.max_table_size {
+            match self.table.pop_back() {
+                Some(field) => self.table_size -= field_size(&field),
+                None => break
+            }
+        }
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn invalid(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("noir: HPACK: {}", message))
+}
+
+fn field_size(field: &HeaderField) -> usize {
+    field.0.len() + field.1.len() + 32
+}
+
+fn encode_integer(block: &mut Vec<u8>, mut value: usize, prefix: u8, flags: u8) {
+    let max = (1usize << prefix) - 1;
+    if value < max {
+        block.push(flags | value as u8);
+
+    } else {
+        block.push(flags | max as u8);
+        value -= max;
+        while value >= 128 {
+            block.push((value % 128 + 128) as u8);
+            value /= 128;
+        }
+        block.push(value as u8);
+    }
+}
+
+fn encode_string(block: &mut Vec<u8>, data: &[u8]) {
+    encode_integer(block, data.len(), 7, 0x00);
+    block.extend_from_slice(data);
+}
+
+fn decode_integer(block: &[u8], offset: &mut usize, prefix: u8) -> Result<usize, Error> {
+
+    let max = (1usize << prefix) - 1;
+    let mut value = (block[*offset] as usize) & max;
+    *offset += 1;
+
+    if value == max {
+        let mut shift = 0;
+        loop {
+
+            let byte = try!(block.get(*offset).cloned().ok_or_else(|| {
+                invalid("Truncated integer.")
+            }));
+
+            *offset += 1;
+            value += ((byte & 0x7F) as usize) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                break;
+
+            } else if shift > 28 {
+                return Err(invalid("Integer overflow."));
+            }
+
+        }
+    }
+
+    Ok(value)
+
+}
+
+fn decode_string(block: &[u8], offset: &mut usize) -> Result<Vec<u8>, Error> {
+
+    if *offset >= block.len() {
+        return Err(invalid("Truncated string."));
+    }
+
+    let encoded = block[*offset] & 0x80 != 0;
+    let length = try!(decode_integer(block, offset, 7));
+    if *offset + length > block.len() {
+        return Err(invalid("Truncated string."));
+    }
+
+    let data = &block[*offset..*offset + length];
+    *offset += length;
+
+    if encoded {
+        huffman::decode(data).ok_or_else(|| invalid("Invalid huffman encoded string."))
+
+    } else {
+        Ok(data.to_vec())
+    }
+
+}
+
+
+// Statics --------------------------------------------------------------------
+const STATIC_TABLE: [(&'static str, &'static str); 61] = [
+    (":authority", ""),
+    (":method", "GET"),
+    (":method", "POST"),
+    (":path", "/"),
+    (":path", "/index.html"),
+    (":scheme", "http"),
+    (":scheme", "https"),
+    (":status", "200"),
+    (":status", "204"),
+    (":status", "206"),
+    (":status", "304"),
+    (":status", "400"),
+    (":status", "404"),
+    (":status", "500"),
+    ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"),
+    ("accept-language", ""),
+    ("accept-ranges", ""),
+    ("accept", ""),
+    ("access-control-allow-origin", ""),
+    ("age", ""),
+    ("allow", ""),
+    ("authorization", ""),
+    ("cache-control", ""),
+    ("content-disposition", ""),
+    ("content-encoding", ""),
+    ("content-language", ""),
+    ("content-length", ""),
+    ("content-location", ""),
+    ("content-range", ""),
+    ("content-type", ""),
+    ("cookie", ""),
+    ("date", ""),
+    ("etag", ""),
+    ("expect", ""),
+    ("expires", ""),
+    ("from", ""),
+    ("host", ""),
+    ("if-match", ""),
+    ("if-modified-since", ""),
+    ("if-none-match", ""),
+    ("if-range", ""),
+    ("if-unmodified-since", ""),
+    ("last-modified", ""),
+    ("link", ""),
+    ("location", ""),
+    ("max-forwards", ""),
+    ("proxy-authenticate", ""),
+    ("proxy-authorization", ""),
+    ("range", ""),
+    ("referer", ""),
+    ("refresh", ""),
+    ("retry-after", ""),
+    ("server", ""),
+    ("set-cookie", ""),
+    ("strict-transport-security", ""),
+    ("transfer-encoding", ""),
+    ("user-agent", ""),
+    ("vary", ""),
+    ("via", ""),
+    ("www-authenticate", "")
+];
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+
+    use super::{Decoder, HeaderField, decode_integer, decode_string, encode, encode_integer};
+
+    fn bytes(hex: &str) -> Vec<u8> {
+        let digits = hex.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>();
+        digits.chunks(2).map(|pair| {
+            (pair[0].to_digit(16).unwrap() * 16 + pair[1].to_digit(16).unwrap()) as u8
+
+        }).collect()
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> Vec<HeaderField> {
+        pairs.iter().map(|&(name, value)| {
+            (name.as_bytes().to_vec(), value.as_bytes().to_vec())
+
+        }).collect()
+    }
+
+    fn assert_table(decoder: &Decoder, size: usize, pairs: &[(&str, &str)]) {
+        assert_eq!(decoder.table_size, size);
+        assert_eq!(decoder.table.iter().cloned().collect::<Vec<HeaderField>>(), fields(pairs));
+    }
+
+    // RFC 7541 C.1
+    #[test]
+    fn test_integer_representation() {
+
+        let mut offset = 0;
+        assert_eq!(decode_integer(&[0x0A], &mut offset, 5).unwrap(), 10);
+        assert_eq!(offset, 1);
+
+        let mut offset = 0;
+        assert_eq!(decode_integer(&[0x1F, 0x9A, 0x0A], &mut offset, 5).unwrap(), 1337);
+        assert_eq!(offset, 3);
+
+        let mut offset = 0;
+        assert_eq!(decode_integer(&[0x2A], &mut offset, 8).unwrap(), 42);
+
+        let mut block = Vec::new();
+        encode_integer(&mut block, 1337, 5, 0x00);
+        assert_eq!(block, vec![0x1F, 0x9A, 0x0A]);
+
+        let mut offset = 0;
+        assert!(decode_integer(&[0x1F, 0x9A], &mut offset, 5).is_err());
+
+        let mut offset = 0;
+        assert!(decode_integer(&[0x1F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01], &mut offset, 5).is_err());
+
+    }
+
+    #[test]
+    fn test_string_representation() {
+
+        let block = bytes("0f 7777 772e 6578 616d 706c 652e 636f 6d");
+        let mut offset = 0;
+        assert_eq!(decode_string(&block[..], &mut offset).unwrap(), b"www.example.com".to_vec());
+        assert_eq!(offset, block.len());
+
+        let block = bytes("8c f1e3 c2e5 f23a 6ba0 ab90 f4ff");
+        let mut offset = 0;
+        assert_eq!(decode_string(&block[..], &mut offset).unwrap(), b"www.example.com".to_vec());
+        assert_eq!(offset, block.len());
+
+        let mut offset = 0;
+        assert!(decode_string(&bytes("0f 7777")[..], &mut offset).is_err());
+
+    }
+
+    #[test]
+    fn test_encode_literals() {
+
+        let encoded = encode(&fields(&[("custom-key", "custom-header")])[..]);
+        assert_eq!(
+            encoded,
+            bytes("00 0a 6375 7374 6f6d 2d6b 6579 0d 6375 7374 6f6d 2d68 6561 6465 72")
+        );
+
+        let mut decoder = Decoder::new();
+        assert_eq!(decoder.decode(&encoded[..]).unwrap(), fields(&[("custom-key", "custom-header")]));
+        assert_table(&decoder, 0, &[]);
+
+    }
+
+    // RFC 7541 C.3
+    #[test]
+    fn test_requests_without_huffman() {
+
+        let mut decoder = Decoder::new();
+
+        assert_eq!(decoder.decode(&bytes("
+            8286 8441 0f77 7777 2e65 7861 6d70 6c65 2e63 6f6d
+        ")[..]).unwrap(), fields(&[
+            (":method", "GET"),
+            (":scheme", "http"),
+            (":path", "/"),
+            (":authority", "www.example.com")
+        ]));
+
+        assert_table(&decoder, 57, &[(":authority", "www.example.com")]);
+
+        assert_eq!(decoder.decode(&bytes("
+            8286 84be 5808 6e6f 2d63 6163 6865
+        ")[..]).unwrap(), fields(&[
+            (":method", "GET"),
+            (":scheme", "http"),
+            (":path", "/"),
+            (":authority", "www.example.com"),
+            ("cache-control", "no-cache")
+        ]));
+
+        assert_table(&decoder, 110, &[
+            ("cache-control", "no-cache"),
+            (":authority", "www.example.com")
+        ]);
+
+        assert_eq!(decoder.decode(&bytes("
+            8287 85bf 400a 6375 7374 6f6d 2d6b 6579 0c63 7573 746f 6d2d 7661 6c75 65
+        ")[..]).unwrap(), fields(&[
+            (":method", "GET"),
+            (":scheme", "https"),
+            (":path", "/index.html"),
+            (":authority", "www.example.com"),
+            ("custom-key", "custom-value")
+        ]));
+
+        assert_table(&decoder, 164, &[
+            ("custom-key", "custom-value"),
+            ("cache-control", "no-cache"),
+            (":authority", "www.example.com")
+        ]);
+
+    }
+
+    // RFC 7541 C.4
+    #[test]
+    fn test_requests_with_huffman() {
+
+        let mut decoder = Decoder::new();
+
+        assert_eq!(decoder.decode(&bytes("
+            8286 8441 8cf1 e3c2 e5f2 3a6b a0ab 90f4 ff
+        ")[..]).unwrap(), fields(&[
+            (":method", "GET"),
+            (":scheme", "http"),
+            (":path", "/"),
+            (":authority", "www.example.com")
+        ]));
+
+        assert_table(&decoder, 57, &[(":authority", "www.example.com")]);
+
+        assert_eq!(decoder.decode(&bytes("
+            8286 84be 5886 a8eb 1064 9cbf
+        ")[..]).unwrap(), fields(&[
+            (":method", "GET"),
+            (":scheme", "http"),
+            (":path", "/"),
+            (":authority", "www.example.com"),
+            ("cache-control", "no-cache")
+        ]));
+
+        assert_table(&decoder, 110, &[
+            ("cache-control", "no-cache"),
+            (":authority", "www.example.com")
+        ]);
+
+        assert_eq!(decoder.decode(&bytes("
+            8287 85bf 4088 25a8 49e9 5ba9 7d7f 8925 a849 e95b b8e8 b4bf
+        ")[..]).unwrap(), fields(&[
+            (":method", "GET"),
+            (":scheme", "https"),
+            (":path", "/index.html"),
+            (":authority", "www.example.com"),
+            ("custom-key", "custom-value")
+        ]));
+
+        assert_table(&decoder, 164, &[
+            ("custom-key", "custom-value"),
+            ("cache-control", "no-cache"),
+            (":authority", "www.example.com")
+        ]);
+
+    }
+
+    // RFC 7541 C.5.1 and C.5.2, with a preceding dynamic table size update
+    #[test]
+    fn test_dynamic_table_eviction() {
+
+        let mut decoder = Decoder::new();
+
+        assert_eq!(decoder.decode(&bytes("
+            3fe1 01
+            4803 3330 3258 0770 7269 7661 7465 611d 4d6f 6e2c 2032 3120 4f63 7420
+            3230 3133 2032 303a 3133 3a32 3120 474d 546e 1768 7474 7073 3a2f 2f77
+            7777 2e65 7861 6d70 6c65 2e63 6f6d
+        ")[..]).unwrap(), fields(&[
+            (":status", "302"),
+            ("cache-control", "private"),
+            ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+            ("location", "https://www.example.com")
+        ]));
+
+        assert_eq!(decoder.max_table_size, 256);
+        assert_table(&decoder, 222, &[
+            ("location", "https://www.example.com"),
+            ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+            ("cache-control", "private"),
+            (":status", "302")
+        ]);
+
+        // The oldest entry is evicted to make room for the new status
+        assert_eq!(decoder.decode(&bytes("4803 3330 37c1 c0bf")[..]).unwrap(), fields(&[
+            (":status", "307"),
+            ("cache-control", "private"),
+            ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+            ("location", "https://www.example.com")
+        ]));
+
+        assert_table(&decoder, 222, &[
+            (":status", "307"),
+            ("location", "https://www.example.com"),
+            ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+            ("cache-control", "private")
+        ]);
+
+        // Shrinking the table evicts all entries which no longer fit
+        decoder.decode(&bytes("3f 21")[..]).unwrap();
+        assert_table(&decoder, 42, &[(":status", "307")]);
+
+        // Indices beyond the dynamic table are rejected
+        assert!(decoder.decode(&bytes("c0")[..]).is_err());
+        assert!(decoder.decode(&bytes("80")[..]).is_err());
+
+    }
+
+}
@@ -0,0 +1,600 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::cmp;
+use std::str;
+use std::net::TcpStream;
+use std::time::Duration;
+use std::io::{Error, ErrorKind, Read, Write};
+
+
+// External Dependencies ------------------------------------------------------
+use hyper::method::Method;
+use hyper::header::Headers;
+use hyper::status::StatusCode;
+
+
+// Internal Dependencies ------------------------------------------------------
+use resource::http::{HttpBody, HttpResource};
+use resource::http::util::http_body_from_parts;
+
+
+// Modules --------------------------------------------------------------------
+mod frame;
+mod hpack;
+mod huffman;
+
+
+// Re-Exports -----------------------------------------------------------------
+pub use self::frame::{Http2Frame, read_frame, write_frame};
+
+
+// Noir Internal --------------------------------------------------------------
+pub struct Http2Response {
+    pub status: StatusCode,
+    pub headers: Headers,
+    pub body: Vec<u8>
+}
+
+impl HttpResource for Http2Response {
+
+    fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    fn into_http_body(&mut self) -> HttpBody where Self: Sized {
+        http_body_from_parts(self.body.clone(), &self.headers)
+    }
+
+}
+
+/// Performs a single request over a new cleartext HTTP/2 connection with
+/// prior knowledge (h2c) and closes the connection afterwards.
+pub fn http2_request(
+    host: &str,
+    method: &Method,
+    path: &str,
+    headers: &Headers,
+    body: Option<&[u8]>,
+    timeout: Duration
+
+) -> Result<Http2Response, Error> {
+
+    let stream = try!(TcpStream::connect(host));
+    try!(stream.set_read_timeout(Some(timeout)));
+    try!(stream.set_write_timeout(Some(timeout)));
+
+    let mut connection = Http2Connection::new(stream);
+    let response = connection.request(host, method, path, headers, body);
+
+    // Always attempt to shut down the connection gracefully
+    connection.close();
+    response
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+const PREFACE: &'static [u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const STREAM_ID: u32 = 1;
+const DEFAULT_WINDOW_SIZE: i64 = 65_535;
+const DEFAULT_MAX_FRAME_SIZE: usize = 16_384;
+const MAX_FRAME_SIZE_LIMIT: usize = 16_777_215;
+
+// Connection specific headers which must not be send via HTTP/2
+const CONNECTION_HEADERS: [&'static str; 6] = [
+    "connection",
+    "host",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade"
+];
+
+struct Http2Connection<S: Read + Write> {
+    stream: S,
+    decoder: hpack::Decoder,
+    connection_window: i64,
+    stream_window: i64,
+    initial_window: i64,
+    max_frame_size: usize,
+    error_code: u32
+}
+
+impl<S: Read + Write> Http2Connection<S> {
+
+    fn new(stream: S) -> Http2Connection<S> {
+        Http2Connection {
+            stream: stream,
+            decoder: hpack::Decoder::new(),
+            connection_window: DEFAULT_WINDOW_SIZE,
+            stream_window: DEFAULT_WINDOW_SIZE,
+            initial_window: DEFAULT_WINDOW_SIZE,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            error_code: frame::NO_ERROR
+        }
+    }
+
+    fn request(
+        &mut self,
+        authority: &str,
+        method: &Method,
+        path: &str,
+        headers: &Headers,
+        body: Option<&[u8]>
+
+    ) -> Result<Http2Response, Error> {
+
+        try!(self.stream.write_all(PREFACE));
+        try!(write_frame(&mut self.stream, &Http2Frame::new(
+            frame::SETTINGS,
+            0,
+            0,
+            frame::settings_payload(&[(frame::SETTINGS_ENABLE_PUSH, 0)])
+        )));
+
+        let mut fields = vec![
+            (b":method".to_vec(), format!("{}", method).into_bytes()),
+            (b":scheme".to_vec(), b"http".to_vec()),
+            (b":authority".to_vec(), authority.as_bytes().to_vec()),
+            (b":path".to_vec(), path.as_bytes().to_vec())
+        ];
+
+        for header in headers.iter() {
+            let name = header.name().to_lowercase();
+            if !CONNECTION_HEADERS.contains(&name.as_str()) {
+                fields.push((name.into_bytes(), header.value_string().into_bytes()));
+            }
+        }
+
+        if let Some(body) = body {
+            if headers.get_raw("Content-Length").is_none() {
+                fields.push((
+                    b"content-length".to_vec(),
+                    format!("{}", body.len()).into_bytes()
+                ));
+            }
+        }
+
+        try!(self.write_headers(hpack::encode(&fields[..]), body.is_none()));
+
+        if let Some(body) = body {
+            try!(self.write_data(body));
+        }
+
+        self.read_response()
+
+    }
+
+    fn write_headers(&mut self, block: Vec<u8>, end_stream: bool) -> Result<(), Error> {
+
+        let chunks = block.chunks(self.max_frame_size).collect::<Vec<&[u8]>>();
+        let count = cmp::max(chunks.len(), 1);
+
+        for index in 0..count {
+
+            let payload = chunks.get(index).map_or(Vec::new(), |chunk| chunk.to_vec());
+            let mut flags = 0;
+            if index == count - 1 {
+                flags |= frame::FLAG_END_HEADERS;
+            }
+
+            let kind = if index == 0 {
+                if end_stream {
+                    flags |= frame::FLAG_END_STREAM;
+                }
+                frame::HEADERS
+
+            } else {
+                frame::CONTINUATION
+            };
+
+            try!(write_frame(&mut self.stream, &Http2Frame::new(
+                kind,
+                flags,
+                STREAM_ID,
+                payload
+            )));
+
+        }
+
+        Ok(())
+
+    }
+
+    fn write_data(&mut self, body: &[u8]) -> Result<(), Error> {
+
+        let mut offset = 0;
+        loop {
+
+            let window = cmp::min(self.connection_window, self.stream_window);
+            if window <= 0 && offset < body.len() {
+
+                // Wait for the server to open up the flow control windows
+                let received = try!(read_frame(&mut self.stream));
+                if received.stream_id == STREAM_ID && received.kind != frame::WINDOW_UPDATE {
+                    return Err(protocol_error("Response received before the request body was sent."));
+                }
+
+                try!(self.control_frame(&received));
+                continue;
+
+            }
+
+            let size = cmp::min(cmp::max(window, 0) as usize, self.max_frame_size);
+            let end = cmp::min(offset + size, body.len());
+            let flags = if end == body.len() {
+                frame::FLAG_END_STREAM
+
+            } else {
+                0
+            };
+
+            try!(write_frame(&mut self.stream, &Http2Frame::new(
+                frame::DATA,
+                flags,
+                STREAM_ID,
+                body[offset..end].to_vec()
+            )));
+
+            self.connection_window -= (end - offset) as i64;
+            self.stream_window -= (end - offset) as i64;
+            offset = end;
+
+            if offset == body.len() {
+                return Ok(());
+            }
+
+        }
+
+    }
+
+    fn read_response(&mut self) -> Result<Http2Response, Error> {
+
+        let mut status = None;
+        let mut headers = Headers::new();
+        let mut body = Vec::new();
+        let mut block = Vec::new();
+        let mut end_stream = false;
+
+        loop {
+
+            let received = try!(read_frame(&mut self.stream));
+            if received.stream_id == STREAM_ID && (
+                received.kind == frame::HEADERS || received.kind == frame::CONTINUATION
+            ) {
+
+                if received.kind == frame::HEADERS {
+                    end_stream = received.has_flag(frame::FLAG_END_STREAM);
+                    block.clear();
+                    block.extend_from_slice(try!(received.data()));
+
+                } else {
+                    block.extend_from_slice(&received.payload[..]);
+                }
+
+                if received.has_flag(frame::FLAG_END_HEADERS) {
+
+                    let fields = try!(self.decoder.decode(&block[..]));
+
+                    // Trailers are merged into the response headers
+                    if status.is_some() {
+                        append_headers(&mut headers, fields);
+
+                    } else {
+                        let code = try!(response_status(&fields[..]));
+
+                        // Skip informational responses
+                        if code >= 200 {
+                            status = Some(StatusCode::from_u16(code));
+                            append_headers(&mut headers, fields);
+                        }
+                    }
+
+                    if end_stream {
+                        break;
+                    }
+
+                }
+
+            } else if received.stream_id == STREAM_ID && received.kind == frame::DATA {
+
+                body.extend_from_slice(try!(received.data()));
+
+                if received.has_flag(frame::FLAG_END_STREAM) {
+                    break;
+
+                // Keep the flow control windows open for larger bodies
+                } else if !received.payload.is_empty() {
+                    let increment = frame::u32_bytes(received.payload.len() as u32).to_vec();
+                    try!(write_frame(&mut self.stream, &Http2Frame::new(
+                        frame::WINDOW_UPDATE, 0, 0, increment.clone()
+                    )));
+                    try!(write_frame(&mut self.stream, &Http2Frame::new(
+                        frame::WINDOW_UPDATE, 0, STREAM_ID, increment
+                    )));
+                }
+
+            } else {
+                try!(self.control_frame(&received));
+            }
+
+        }
+
+        match status {
+            Some(status) => Ok(Http2Response {
+                status: status,
+                headers: headers,
+                body: body
+            }),
+            None => Err(protocol_error("Response ended without a status."))
+        }
+
+    }
+
+    fn control_frame(&mut self, received: &Http2Frame) -> Result<(), Error> {
+        match received.kind {
+            frame::SETTINGS if !received.has_flag(frame::FLAG_ACK) => {
+
+                for (id, value) in frame::parse_settings(&received.payload[..]) {
+                    if id == frame::SETTINGS_INITIAL_WINDOW_SIZE {
+                        self.stream_window += value as i64 - self.initial_window;
+                        self.initial_window = value as i64;
+
+                    } else if id == frame::SETTINGS_MAX_FRAME_SIZE {
+
+                        // Sizes outside of the allowed range are a connection
+                        // error which is reported once the connection is closed
+                        let size = value as usize;
+                        if size < DEFAULT_MAX_FRAME_SIZE || size > MAX_FRAME_SIZE_LIMIT {
+                            self.error_code = frame::PROTOCOL_ERROR;
+                            return Err(protocol_error(
+                                "Server sent an invalid SETTINGS_MAX_FRAME_SIZE."
+                            ));
+                        }
+
+                        self.max_frame_size = size;
+
+                    }
+                }
+
+                write_frame(&mut self.stream, &Http2Frame::new(
+                    frame::SETTINGS,
+                    frame::FLAG_ACK,
+                    0,
+                    Vec::new()
+                ))
+
+            },
+            frame::PING if !received.has_flag(frame::FLAG_ACK) => {
+                write_frame(&mut self.stream, &Http2Frame::new(
+                    frame::PING,
+                    frame::FLAG_ACK,
+                    0,
+                    received.payload.clone()
+                ))
+            },
+            frame::WINDOW_UPDATE if received.payload.len() == 4 => {
+                let increment = (frame::read_u32(&received.payload[..]) & 0x7FFF_FFFF) as i64;
+                if received.stream_id == 0 {
+                    self.connection_window += increment;
+
+                } else if received.stream_id == STREAM_ID {
+                    self.stream_window += increment;
+                }
+                Ok(())
+            },
+            frame::RST_STREAM if received.stream_id == STREAM_ID => {
+                Err(protocol_error("Stream was reset by the server."))
+            },
+            frame::GOAWAY => {
+                Err(protocol_error("Connection was closed by the server."))
+            },
+            _ => Ok(())
+        }
+    }
+
+    fn close(&mut self) {
+        let mut payload = frame::u32_bytes(0).to_vec();
+        payload.extend_from_slice(&frame::u32_bytes(self.error_code)[..]);
+        write_frame(&mut self.stream, &Http2Frame::new(
+            frame::GOAWAY,
+            0,
+            0,
+            payload
+
+        )).ok();
+    }
+
+}
+
+fn protocol_error(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("noir: HTTP/2: {}", message))
+}
+
+fn response_status(fields: &[hpack::HeaderField]) -> Result<u16, Error> {
+    fields.iter().find(|&&(ref name, _)| &name[..] == b":status").and_then(|&(_, ref value)| {
+        str::from_utf8(&value[..]).ok().and_then(|value| value.parse::<u16>().ok())
+
+    }).ok_or_else(|| protocol_error("Response is missing a valid :status."))
+}
+
+fn append_headers(headers: &mut Headers, fields: Vec<hpack::HeaderField>) {
+    for (name, value) in fields {
+
+        // Pseudo headers are not part of the HTTP/1.1 header model
+        if name.starts_with(b":") {
+            continue;
+        }
+
+        let name = String::from_utf8_lossy(&name[..]).into_owned();
+        let mut values = headers.get_raw(name.as_str()).map_or(Vec::new(), |values| {
+            values.to_vec()
+        });
+
+        values.push(value);
+        headers.set_raw(name, values);
+
+    }
+}
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+
+    use std::str;
+    use std::io::{Cursor, Read, Result, Write};
+    use hyper::method::Method;
+    use hyper::header::Headers;
+    use hyper::status::StatusCode;
+    use super::{
+        Http2Connection, Http2Frame, PREFACE, STREAM_ID,
+        frame, read_frame, write_frame
+    };
+
+    struct Stream {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>
+    }
+
+    impl Stream {
+        fn new(frames: Vec<Http2Frame>) -> Stream {
+            let mut input = Vec::new();
+            for frame in frames {
+                write_frame(&mut input, &frame).unwrap();
+            }
+            Stream {
+                input: Cursor::new(input),
+                output: Vec::new()
+            }
+        }
+    }
+
+    impl Read for Stream {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Stream {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_response_continuation() {
+
+        let mut connection = Http2Connection::new(Stream::new(vec![
+            Http2Frame::new(frame::SETTINGS, 0, 0, Vec::new()),
+            // :status 200 followed by the first part of a literal field
+            Http2Frame::new(frame::HEADERS, 0, STREAM_ID, vec![0x88, 0x00]),
+            Http2Frame::new(frame::CONTINUATION, 0, STREAM_ID, vec![
+                0x06, b'x', b'-', b't', b'e'
+            ]),
+            Http2Frame::new(frame::CONTINUATION, frame::FLAG_END_HEADERS, STREAM_ID, vec![
+                b's', b't', 0x01, b'a'
+            ]),
+            Http2Frame::new(frame::DATA, frame::FLAG_END_STREAM, STREAM_ID, b"Hello".to_vec())
+        ]));
+
+        let response = connection.request(
+            "localhost",
+            &Method::Get,
+            "/",
+            &Headers::new(),
+            None
+
+        ).unwrap();
+
+        assert_eq!(response.status, StatusCode::Ok);
+        assert_eq!(response.headers.get_raw("x-test").unwrap(), &[b"a".to_vec()][..]);
+        assert_eq!(str::from_utf8(&response.body[..]).unwrap(), "Hello");
+
+    }
+
+    #[test]
+    fn test_request_flow_control() {
+
+        let mut connection = Http2Connection::new(Stream::new(vec![
+            Http2Frame::new(frame::WINDOW_UPDATE, 0, STREAM_ID, frame::u32_bytes(4).to_vec()),
+            Http2Frame::new(frame::WINDOW_UPDATE, 0, STREAM_ID, frame::u32_bytes(100).to_vec())
+        ]));
+
+        // The body is only sent as far as the stream window allows
+        connection.stream_window = 4;
+        connection.write_data(b"Hello World").unwrap();
+
+        assert_eq!(connection.stream_window, 97);
+        assert_eq!(connection.connection_window, 65_535 - 11);
+
+        let mut output = Cursor::new(connection.stream.output.clone());
+        let frames = (0..3).map(|_| read_frame(&mut output).unwrap()).collect::<Vec<Http2Frame>>();
+
+        assert_eq!(frames.iter().map(|frame| {
+            (frame.kind, frame.flags, frame.payload.clone())
+
+        }).collect::<Vec<(u8, u8, Vec<u8>)>>(), vec![
+            (frame::DATA, 0, b"Hell".to_vec()),
+            (frame::DATA, 0, b"o Wo".to_vec()),
+            (frame::DATA, frame::FLAG_END_STREAM, b"rld".to_vec())
+        ]);
+
+        assert!(read_frame(&mut output).is_err());
+
+    }
+
+    #[test]
+    fn test_settings_invalid_max_frame_size() {
+
+        let mut connection = Http2Connection::new(Stream::new(vec![
+            Http2Frame::new(frame::SETTINGS, 0, 0, frame::settings_payload(&[
+                (frame::SETTINGS_MAX_FRAME_SIZE, 0)
+            ]))
+        ]));
+
+        let error = match connection.request(
+            "localhost",
+            &Method::Get,
+            "/",
+            &Headers::new(),
+            None
+
+        ) {
+            Ok(_) => panic!("Invalid frame size was accepted."),
+            Err(err) => err
+        };
+
+        assert_eq!(
+            format!("{}", error),
+            "noir: HTTP/2: Server sent an invalid SETTINGS_MAX_FRAME_SIZE."
+        );
+        assert_eq!(connection.max_frame_size, 16_384);
+
+        // The connection is closed with a PROTOCOL_ERROR
+        connection.close();
+
+        let mut output = Cursor::new(connection.stream.output[PREFACE.len()..].to_vec());
+        let mut frames = Vec::new();
+        while let Ok(frame) = read_frame(&mut output) {
+            frames.push(frame);
+        }
+
+        let goaway = frames.last().unwrap();
+        assert_eq!(goaway.kind, frame::GOAWAY);
+        assert_eq!(goaway.payload, vec![0, 0, 0, 0, 0, 0, 0, 1]);
+
+    }
+
+}
@@ -0,0 +1,247 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// External Dependencies ------------------------------------------------------
+use json;
+use json::JsonValue;
+use colored::*;
+
+
+// Internal Dependencies ------------------------------------------------------
+use util;
+use resource::http::HttpBody;
+use resource::http::util::http_body_data;
+
+
+/// A JSON-RPC 2.0 call which is send as part of a batch request.
+///
+/// Calls without an `id` are notifications and do not receive a response.
+#[derive(Debug, Clone)]
+pub struct JsonRpcCall {
+    id: Option<JsonValue>,
+    method: String,
+    params: JsonValue
+}
+
+impl JsonRpcCall {
+
+    /// Creates a new call of the remote procedure `method` with the specified
+    /// `id` and `params`.
+    ///
+    /// `params` which are `null` are omitted from the call.
+    pub fn new<I: Into<JsonValue>>(id: I, method: &str, params: JsonValue) -> JsonRpcCall {
+        JsonRpcCall {
+            id: Some(id.into()),
+            method: method.to_string(),
+            params: params
+        }
+    }
+
+    /// Creates a new notification of the remote procedure `method` with the
+    /// specified `params`.
+    pub fn notification(method: &str, params: JsonValue) -> JsonRpcCall {
+        JsonRpcCall {
+            id: None,
+            method: method.to_string(),
+            params: params
+        }
+    }
+
+}
+
+
+// Noir Internal --------------------------------------------------------------
+#[derive(Clone)]
+pub enum JsonRpcExpectation {
+    Result(JsonValue, JsonValue),
+    Error(JsonValue, JsonValue)
+}
+
+pub fn jsonrpc_call_body(call: &JsonRpcCall) -> JsonValue {
+
+    let mut body = object! {
+        "jsonrpc" => "2.0",
+        "method" => call.method.as_str()
+    };
+
+    if !call.params.is_null() {
+        body["params"] = call.params.clone();
+    }
+
+    if let Some(id) = call.id.as_ref() {
+        body["id"] = id.clone();
+    }
+
+    body
+
+}
+
+pub fn jsonrpc_batch_body(calls: &[JsonRpcCall]) -> JsonValue {
+    JsonValue::Array(calls.iter().map(jsonrpc_call_body).collect())
+}
+
+/// Returns the names of all methods invoked by a JSON-RPC request body,
+/// which can either be a single call or a batch of calls.
+pub fn jsonrpc_methods(body: &HttpBody) -> Vec<String> {
+    match util::json::parse(http_body_data(body), "JSON-RPC") {
+        Ok(request) => calls(&request).into_iter().filter_map(|call| {
+            call["method"].as_str().map(|method| method.to_string())
+
+        }).collect(),
+        Err(_) => Vec::new()
+    }
+}
+
+/// Builds the response to a JSON-RPC request, answering every call that has
+/// an `id` with the specified `result` or `error` member.
+///
+/// Returns `None` in case the request only consists of notifications.
+pub fn jsonrpc_response(request: &JsonValue, member: &str, value: &JsonValue) -> Option<JsonValue> {
+
+    let responses = calls(request).into_iter().filter(|call| {
+        has_key(call, "id")
+
+    }).map(|call| {
+        let mut response = object! {
+            "jsonrpc" => "2.0"
+        };
+        response[member] = value.clone();
+        response["id"] = call["id"].clone();
+        response
+
+    }).collect::<Vec<JsonValue>>();
+
+    if responses.is_empty() {
+        None
+
+    } else if request.is_array() {
+        Some(JsonValue::Array(responses))
+
+    } else {
+        responses.into_iter().next()
+    }
+
+}
+
+pub fn jsonrpc_validate(
+    expectations: &[JsonRpcExpectation],
+    body: &HttpBody,
+    compare_depth: usize
+
+) -> Vec<String> {
+
+    let response = match util::json::parse(http_body_data(body), "JSON-RPC") {
+        Ok(response) => response,
+        Err(err) => return vec![format!(
+            "{} {}",
+            "Response".yellow(),
+            err
+        )]
+    };
+
+    // Responses to batch requests can be returned in any order
+    let responses = calls(&response);
+
+    expectations.iter().filter_map(|expectation| {
+        match *expectation {
+            JsonRpcExpectation::Result(ref id, ref expected) => {
+                validate_member(&responses[..], id, "result", expected, compare_depth)
+            },
+            JsonRpcExpectation::Error(ref id, ref expected) => {
+                validate_member(&responses[..], id, "error", expected, compare_depth)
+            }
+        }
+
+    }).collect()
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn calls(value: &JsonValue) -> Vec<&JsonValue> {
+    if value.is_array() {
+        value.members().collect()
+
+    } else {
+        vec![value]
+    }
+}
+
+fn has_key(value: &JsonValue, key: &str) -> bool {
+    value.entries().any(|(k, _)| k == key)
+}
+
+fn same_id(a: &JsonValue, b: &JsonValue) -> bool {
+    json::stringify(a.clone()) == json::stringify(b.clone())
+}
+
+fn validate_member(
+    responses: &[&JsonValue],
+    id: &JsonValue,
+    member: &str,
+    expected: &JsonValue,
+    compare_depth: usize
+
+) -> Option<String> {
+
+    let response = match responses.iter().find(|response| same_id(&response["id"], id)) {
+        Some(response) => *response,
+        None => return Some(format!(
+            "{} {} {} {} {}{}",
+            "Response".yellow(),
+            "JSON-RPC response with id".yellow(),
+            json::stringify(id.clone()).blue().bold(),
+            "is".yellow(),
+            "missing".red().bold(),
+            ".".yellow()
+        ))
+    };
+
+    let (other, article) = if member == "result" {
+        ("error", "an")
+
+    } else {
+        ("result", "a")
+    };
+
+    if has_key(response, member) {
+        match util::json::compare(expected, &response[member], compare_depth, false) {
+            Ok(_) => None,
+            Err(errors) => Some(format!(
+                "{} {} {} {}\n\n        {}",
+                "Response".yellow(),
+                format!("JSON-RPC {} for id", member).yellow(),
+                json::stringify(id.clone()).blue().bold(),
+                "does not match:".yellow(),
+                util::json::format(errors)
+            ))
+        }
+
+    } else if has_key(response, other) {
+        Some(format!(
+            "{} {} {} {}\n\n        {}",
+            "Response".yellow(),
+            format!("JSON-RPC {} for id", member).yellow(),
+            json::stringify(id.clone()).blue().bold(),
+            format!("was expected, but got {} {}:", article, other).yellow(),
+            json::stringify(response[other].clone()).red().bold()
+        ))
+
+    } else {
+        Some(format!(
+            "{} {} {} {} {}{}",
+            "Response".yellow(),
+            format!("JSON-RPC {} for id", member).yellow(),
+            json::stringify(id.clone()).blue().bold(),
+            "is".yellow(),
+            "missing".red().bold(),
+            ".".yellow()
+        ))
+    }
+
+}
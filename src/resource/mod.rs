@@ -7,5 +7,11 @@
 // except according to those terms.
 
 // Modules --------------------------------------------------------------------
+pub mod graphql;
 pub mod http;
+pub mod http2;
+pub mod jsonrpc;
+pub mod smtp;
+pub mod sse;
+pub mod websocket;
 
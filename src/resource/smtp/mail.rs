@@ -0,0 +1,269 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::io::Error;
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+use regex::Regex;
+
+
+// Internal Dependencies ------------------------------------------------------
+use util::format_mismatch;
+use mock::{MockRequest, MockResponse, MockOrder};
+use super::SmtpMessage;
+
+
+/// A mail which is expected to be sent to the noir `SmtpServer` during a test.
+///
+/// Each expected mail is matched against exactly one received message for its
+/// recipient. Additional messages are reported as unexpected mails. A message
+/// sent to multiple recipients is matched against the expected mails of each
+/// of them.
+///
+/// Mails are not validated for the order in which they were received.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # extern crate noir;
+/// use noir::SmtpMail;
+///
+/// # fn main() {
+/// let mail = SmtpMail::new("user@example.com")
+///     .expected_subject_regex("^Reset your password")
+///     .expected_body_containing("https://example.com/reset/");
+/// # }
+/// ```
+pub struct SmtpMail {
+    recipient: String,
+    expected_from: Option<String>,
+    expected_subject: Option<String>,
+    expected_subject_regex: Option<Regex>,
+    expected_body: Vec<String>,
+    expected_body_regex: Option<Regex>,
+    messages: Vec<SmtpMessage>
+}
+
+impl SmtpMail {
+
+    /// Creates a new mail which is expected to be sent to the specified
+    /// `recipient` address.
+    pub fn new<S: Into<String>>(recipient: S) -> SmtpMail {
+        SmtpMail {
+            recipient: recipient.into(),
+            expected_from: None,
+            expected_subject: None,
+            expected_subject_regex: None,
+            expected_body: Vec::new(),
+            expected_body_regex: None,
+            messages: Vec::new()
+        }
+    }
+
+    /// Sets the expected sender address of the mail.
+    pub fn expected_from<S: Into<String>>(mut self, address: S) -> Self {
+        self.expected_from = Some(address.into());
+        self
+    }
+
+    /// Sets the expected subject of the mail.
+    pub fn expected_subject<S: Into<String>>(mut self, subject: S) -> Self {
+        self.expected_subject = Some(subject.into());
+        self
+    }
+
+    /// Sets a regular expression which the subject of the mail is expected
+    /// to match.
+    ///
+    /// # Panics
+    ///
+    /// If the `pattern` is not a valid regular expression.
+    pub fn expected_subject_regex(mut self, pattern: &str) -> Self {
+        self.expected_subject_regex = Some(
+            Regex::new(pattern).expect("noir: Invalid subject regular expression.")
+        );
+        self
+    }
+
+    /// Adds a text which the body of the mail is expected to contain.
+    pub fn expected_body_containing<S: Into<String>>(mut self, text: S) -> Self {
+        self.expected_body.push(text.into());
+        self
+    }
+
+    /// Sets a regular expression which the body of the mail is expected to
+    /// match.
+    ///
+    /// # Panics
+    ///
+    /// If the `pattern` is not a valid regular expression.
+    pub fn expected_body_regex(mut self, pattern: &str) -> Self {
+        self.expected_body_regex = Some(
+            Regex::new(pattern).expect("noir: Invalid body regular expression.")
+        );
+        self
+    }
+
+}
+
+impl MockResponse for SmtpMail {
+
+    fn matches(&self, request: &Box<MockRequest>) -> bool {
+        if let Some(message) = SmtpMessage::downcast_ref(request) {
+            self.messages.is_empty() && message.has_recipient(self.recipient.as_str())
+
+        } else {
+            false
+        }
+    }
+
+    fn mismatches(&self, request: &Box<MockRequest>) -> Option<(String, Vec<String>)> {
+        if let Some(message) = SmtpMessage::downcast_ref(request) {
+
+            let mut reasons = Vec::new();
+            if !message.has_recipient(self.recipient.as_str()) {
+                reasons.push(format_mismatch(
+                    "Recipient",
+                    self.recipient.as_str(),
+                    message.recipients.join(", ").as_str()
+                ));
+            }
+
+            if !self.messages.is_empty() {
+                reasons.push(format!(
+                    "{} {}{}",
+                    "Mail was".yellow(),
+                    "already received".red().bold(),
+                    ".".yellow()
+                ));
+            }
+
+            Some((format!(
+                "{} {} \"{}\"",
+                "SMTP".cyan(),
+                "mail expected for".yellow(),
+                self.recipient.cyan()
+
+            ), reasons))
+
+        } else {
+            None
+        }
+    }
+
+    fn respond(&mut self, mut request: Box<MockRequest>) -> Result<Vec<u8>, Error> {
+        if let Some(message) = SmtpMessage::downcast_mut(&mut request) {
+            self.messages.push(message.clone());
+        }
+        Ok(b"250 OK: Message accepted\r\n".to_vec())
+    }
+
+    fn validate(&mut self, _: usize, _: usize) -> Vec<String> {
+
+        let mut errors = Vec::new();
+
+        let message = match self.messages.first() {
+            Some(message) => message,
+            None => {
+                errors.push(format!(
+                    "{} {} {} {}{}",
+                    "Expected".yellow(),
+                    "a mail".green().bold(),
+                    "for the recipient, but got".yellow(),
+                    "none".red().bold(),
+                    ".".yellow()
+                ));
+                return errors;
+            }
+        };
+
+        if let Some(from) = self.expected_from.as_ref() {
+            if message.from.to_lowercase() != from.to_lowercase() {
+                errors.push(format_mismatch(
+                    "Mail sender",
+                    from.as_str(),
+                    message.from.as_str()
+                ));
+            }
+        }
+
+        if let Some(subject) = self.expected_subject.as_ref() {
+            if message.subject() != subject.as_str() {
+                errors.push(format_mismatch(
+                    "Mail subject",
+                    subject.as_str(),
+                    message.subject()
+                ));
+            }
+        }
+
+        if let Some(regex) = self.expected_subject_regex.as_ref() {
+            if !regex.is_match(message.subject()) {
+                errors.push(format!(
+                    "{} {} \"{}\"{} \"{}\"{}",
+                    "Mail subject".yellow(),
+                    "does not match the pattern".yellow(),
+                    regex.as_str().green().bold(),
+                    ", got".yellow(),
+                    message.subject().red().bold(),
+                    ".".yellow()
+                ));
+            }
+        }
+
+        for text in &self.expected_body {
+            if !message.body.contains(text.as_str()) {
+                errors.push(format!(
+                    "{} {} \"{}\"{} {}{}",
+                    "Mail body".yellow(),
+                    "was expected to contain".yellow(),
+                    text.green().bold(),
+                    ", but".yellow(),
+                    "does not".red().bold(),
+                    ".".yellow()
+                ));
+            }
+        }
+
+        if let Some(regex) = self.expected_body_regex.as_ref() {
+            if !regex.is_match(message.body.as_str()) {
+                errors.push(format!(
+                    "{} {} \"{}\"{}",
+                    "Mail body".yellow(),
+                    "does not match the pattern".yellow(),
+                    regex.as_str().green().bold(),
+                    ".".yellow()
+                ));
+            }
+        }
+
+        errors
+
+    }
+
+    fn order(&self) -> MockOrder {
+        MockOrder::Unordered
+    }
+
+    fn validate_header(&self, error_count: usize) -> String {
+        format!(
+            "{} {} \"{}\" {} {} {}",
+            "SMTP".cyan(),
+            "mail expected for".yellow(),
+            self.recipient.cyan(),
+            "returned".yellow(),
+            format!("{}", error_count).red().bold(),
+            "error(s)".yellow()
+        )
+    }
+
+}
+
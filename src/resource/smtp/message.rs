@@ -0,0 +1,329 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::any::Any;
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+
+
+// Internal Dependencies ------------------------------------------------------
+use mock::MockRequest;
+
+
+/// A mail which was received by the noir `SmtpServer`.
+#[derive(Debug, Clone)]
+pub struct SmtpMessage {
+    /// The sender address from the `MAIL FROM` command.
+    pub from: String,
+
+    /// The recipient addresses from the `RCPT TO` commands.
+    ///
+    /// Mails with multiple recipients are received as separate messages for
+    /// each of their recipients.
+    pub recipients: Vec<String>,
+
+    /// The unfolded headers of the message with any RFC 2047 encoded words
+    /// decoded.
+    pub headers: Vec<(String, String)>,
+
+    /// The body of the message with normalized line endings, decoded
+    /// according to its `Content-Transfer-Encoding`.
+    pub body: String
+}
+
+impl SmtpMessage {
+
+    #[doc(hidden)]
+    pub fn new(from: String, recipients: Vec<String>, data: &[u8]) -> SmtpMessage {
+
+        let data = String::from_utf8_lossy(data).replace("\r\n", "\n");
+        let (head, body) = match data.find("\n\n") {
+            Some(index) => (&data[..index], &data[index + 2..]),
+            None => (&data[..], "")
+        };
+
+        // Unfold headers which continue on the following lines
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in head.lines() {
+            if line.starts_with(' ') || line.starts_with('\t') {
+                if let Some(header) = headers.last_mut() {
+                    header.1.push(' ');
+                    header.1.push_str(line.trim());
+                }
+
+            } else if let Some(index) = line.find(':') {
+                headers.push((
+                    line[..index].trim().to_string(),
+                    line[index + 1..].trim().to_string()
+                ));
+            }
+        }
+
+        for header in &mut headers {
+            header.1 = decode_encoded_words(header.1.as_str());
+        }
+
+        let mut message = SmtpMessage {
+            from: from,
+            recipients: recipients,
+            headers: headers,
+            body: String::new()
+        };
+
+        let charset = message.header("Content-Type").map_or_else(String::new, content_charset);
+        let encoding = message.header("Content-Transfer-Encoding").map_or_else(String::new, |encoding| {
+            encoding.trim().to_lowercase()
+        });
+
+        message.body = match encoding.as_str() {
+            "quoted-printable" => decode_charset(
+                charset.as_str(),
+                &decode_quoted_printable(body)[..]
+            ),
+            "base64" => decode_charset(charset.as_str(), &decode_base64(body)[..]),
+            _ => body.to_string()
+        };
+
+        message
+
+    }
+
+    /// Returns the value of the first header with the specified `name`.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|header| {
+            header.0.to_lowercase() == name.to_lowercase()
+
+        }).map(|header| header.1.as_str())
+    }
+
+    /// Returns the subject of the message.
+    pub fn subject(&self) -> &str {
+        self.header("Subject").unwrap_or("")
+    }
+
+    /// Returns whether the message was sent to the specified `address`.
+    pub fn has_recipient(&self, address: &str) -> bool {
+        self.recipients.iter().any(|recipient| {
+            recipient.to_lowercase() == address.to_lowercase()
+        })
+    }
+
+}
+
+impl MockRequest for SmtpMessage {
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+
+    fn validate(&mut self) -> Option<String> {
+        Some(format!(
+            "{} {} {} {} \"{}\" {} \"{}\"{}",
+            "Request Failure:".red().bold(),
+            "Unexpected".yellow(),
+            "SMTP".cyan(),
+            "mail to".yellow(),
+            self.recipients.join(", ").cyan(),
+            "with subject".yellow(),
+            self.subject().cyan(),
+            ", no mail was expected.".yellow()
+        ))
+    }
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn content_charset(content_type: &str) -> String {
+    content_type.split(';').skip(1).filter_map(|param| {
+        let mut pair = param.splitn(2, '=');
+        match (pair.next(), pair.next()) {
+            (Some(key), Some(value)) if key.trim().to_lowercase() == "charset" => {
+                Some(value.trim().trim_matches('"').to_lowercase())
+            },
+            _ => None
+        }
+
+    }).next().unwrap_or_else(String::new)
+}
+
+fn decode_charset(charset: &str, data: &[u8]) -> String {
+    match charset.to_lowercase().as_str() {
+        "iso-8859-1" | "latin1" => data.iter().map(|b| *b as char).collect(),
+        _ => String::from_utf8_lossy(data).into_owned()
+    }
+}
+
+// Decodes all `=?charset?encoding?text?=` words within a header value
+fn decode_encoded_words(value: &str) -> String {
+
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+
+    while let Some(start) = rest.find("=?") {
+        match decode_encoded_word(&rest[start..]) {
+            Some((text, length)) => {
+                // Whitespace in between adjacent encoded words is ignored
+                let between = &rest[..start];
+                if !after_word || !between.trim().is_empty() {
+                    decoded.push_str(between);
+                }
+                decoded.push_str(text.as_str());
+                rest = &rest[start + length..];
+                after_word = true;
+            },
+            None => {
+                decoded.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                after_word = false;
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+
+}
+
+fn decode_encoded_word(word: &str) -> Option<(String, usize)> {
+
+    let inner = &word[2..];
+    let charset_end = match inner.find('?') {
+        Some(index) => index,
+        None => return None
+    };
+
+    let rest = inner[charset_end + 1..].as_bytes();
+    if rest.len() < 2 || rest[1] != b'?' {
+        return None;
+    }
+
+    let text = &inner[charset_end + 3..];
+    let text_end = match text.find("?=") {
+        Some(index) => index,
+        None => return None
+    };
+
+    let data = match rest[0] {
+        b'B' | b'b' => decode_base64(&text[..text_end]),
+        b'Q' | b'q' => decode_hex_escapes(&text[..text_end].replace('_', " ")),
+        _ => return None
+    };
+
+    Some((
+        decode_charset(&inner[..charset_end], &data[..]),
+        2 + charset_end + 3 + text_end + 2
+    ))
+
+}
+
+fn decode_quoted_printable(text: &str) -> Vec<u8> {
+
+    let mut data = Vec::new();
+    let mut soft_break = true;
+
+    for line in text.split('\n') {
+
+        if !soft_break {
+            data.push(b'\n');
+        }
+
+        // Trailing whitespace was added in transport and is not part of the
+        // encoded text
+        let line = line.trim_right_matches(|c| c == ' ' || c == '\t');
+        soft_break = line.ends_with('=');
+
+        let line = if soft_break {
+            &line[..line.len() - 1]
+
+        } else {
+            line
+        };
+
+        data.extend(decode_hex_escapes(line));
+
+    }
+
+    data
+
+}
+
+fn decode_hex_escapes(text: &str) -> Vec<u8> {
+
+    let bytes = text.as_bytes();
+    let mut data = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+
+        if bytes[index] == b'=' && index + 2 < bytes.len() {
+            if let (Some(high), Some(low)) = (hex_value(bytes[index + 1]), hex_value(bytes[index + 2])) {
+                data.push(high << 4 | low);
+                index += 3;
+                continue;
+            }
+        }
+
+        data.push(bytes[index]);
+        index += 1;
+
+    }
+
+    data
+
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'...b'9' => Some(byte - b'0'),
+        b'A'...b'F' => Some(byte - b'A' + 10),
+        b'a'...b'f' => Some(byte - b'a' + 10),
+        _ => None
+    }
+}
+
+fn decode_base64(text: &str) -> Vec<u8> {
+
+    let mut data = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+
+    for c in text.bytes() {
+
+        let value = match c {
+            b'A'...b'Z' => c - b'A',
+            b'a'...b'z' => c - b'a' + 26,
+            b'0'...b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            // Padding and line breaks
+            _ => continue
+        };
+
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            data.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+
+    }
+
+    data
+
+}
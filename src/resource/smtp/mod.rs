@@ -0,0 +1,17 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Modules --------------------------------------------------------------------
+mod mail;
+mod message;
+
+
+// Re-Exports -----------------------------------------------------------------
+pub use self::mail::SmtpMail;
+pub use self::message::SmtpMessage;
+
@@ -0,0 +1,326 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::time::{Duration, Instant};
+use std::io::{ErrorKind, Read};
+
+
+// External Dependencies ------------------------------------------------------
+use json::JsonValue;
+use colored::*;
+
+
+// Internal Dependencies ------------------------------------------------------
+use util;
+use util::format_mismatch;
+use Options;
+
+
+// Modules --------------------------------------------------------------------
+mod parser;
+
+
+// Re-Exports -----------------------------------------------------------------
+pub use self::parser::{EventParser, ReceivedEvent};
+
+
+/// A sequence of Server-Sent Events which are expected to be received from a
+/// `text/event-stream` response.
+///
+/// Events are read incrementally from the response and compared against the
+/// expected events in the order in which they were added. Once all expected
+/// events have been received the connection is closed, any further events
+/// sent by the API are ignored.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # #[macro_use] extern crate json;
+/// # extern crate noir;
+/// use std::time::Duration;
+/// use noir::{EventStream, ServerEvent};
+///
+/// # fn main() {
+/// let stream = EventStream::new()
+///     .with_event_timeout(Duration::from_millis(500))
+///     .expected_event(ServerEvent::new().with_name("update").with_json(object! {
+///         "count" => 1
+///     }))
+///     .expected_event(ServerEvent::new().with_id("2"));
+/// # }
+/// ```
+pub struct EventStream {
+    events: Vec<ServerEvent>,
+    event_timeout: Option<Duration>,
+    options: Option<Options>
+}
+
+impl EventStream {
+
+    /// Creates a new event stream without any expected events.
+    pub fn new() -> EventStream {
+        EventStream {
+            events: Vec::new(),
+            event_timeout: None,
+            options: None
+        }
+    }
+
+    /// Sets additional options for the event stream.
+    ///
+    /// Defaults to the options of the request which receives the stream.
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Sets the maximum duration to wait for each individual event.
+    ///
+    /// The response headers are still awaited for the `api_request_timeout`
+    /// of the request's options, which is also the default for this timeout.
+    pub fn with_event_timeout(mut self, timeout: Duration) -> Self {
+        self.event_timeout = Some(timeout);
+        self
+    }
+
+    /// Expects the next received event to match `event`.
+    pub fn expected_event(mut self, event: ServerEvent) -> Self {
+        self.events.push(event);
+        self
+    }
+
+}
+
+impl Default for EventStream {
+    fn default() -> EventStream {
+        EventStream::new()
+    }
+}
+
+/// An expected Server-Sent Event.
+///
+/// Only the properties which have been set are compared against the received
+/// event.
+#[derive(Clone, Default)]
+pub struct ServerEvent {
+    name: Option<String>,
+    id: Option<String>,
+    data: Option<ServerEventData>
+}
+
+impl ServerEvent {
+
+    /// Creates a new expected event which matches any received event.
+    pub fn new() -> ServerEvent {
+        ServerEvent::default()
+    }
+
+    /// Expects the event to have the specified `name`.
+    ///
+    /// Events without an explicit `event` field are named `message`.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Expects the event to have the specified `id`.
+    pub fn with_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Expects the data of the event to be equal to `data`.
+    ///
+    /// Multiple `data` fields of a single event are joined by newlines.
+    pub fn with_data<S: Into<String>>(mut self, data: S) -> Self {
+        self.data = Some(ServerEventData::Text(data.into()));
+        self
+    }
+
+    /// Expects the data of the event to contain JSON which deep compares
+    /// equal to `value`.
+    pub fn with_json(mut self, value: JsonValue) -> Self {
+        self.data = Some(ServerEventData::Json(value));
+        self
+    }
+
+}
+
+
+// Noir Internal --------------------------------------------------------------
+pub fn event_stream_timeout(stream: &EventStream, default: Duration) -> Duration {
+    stream.event_timeout.unwrap_or(default)
+}
+
+pub fn event_stream_validate<R: Read>(
+    stream: &EventStream,
+    reader: &mut R,
+    options: &Options
+
+) -> Vec<String> {
+
+    let timeout = event_stream_timeout(stream, options.api_request_timeout);
+    let compare_depth = stream.options.as_ref().unwrap_or(options).json_compare_depth;
+    let mut parser = EventParser::new();
+    let mut errors = Vec::new();
+
+    for (index, expected) in stream.events.iter().enumerate() {
+        match read_event(reader, &mut parser, timeout) {
+            Ok(actual) => {
+                errors.append(&mut validate_event(
+                    index + 1,
+                    expected,
+                    &actual,
+                    compare_depth
+                ));
+            },
+            Err(timed_out) => {
+                let reason = if timed_out {
+                    format!(
+                        "{} {}",
+                        "within".yellow(),
+                        util::format_duration(timeout).green().bold()
+                    )
+
+                } else {
+                    "before the stream ended".yellow().to_string()
+                };
+                errors.push(format!(
+                    "{} {} {} {}{} {}{}",
+                    "Event".yellow(),
+                    format!("{}", index + 1).blue().bold(),
+                    "was expected".yellow(),
+                    reason,
+                    ", but".yellow(),
+                    "none was received".red().bold(),
+                    ".".yellow()
+                ));
+
+                // Subsequent events cannot be received either
+                break;
+            }
+        }
+    }
+
+    errors
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+#[derive(Clone)]
+enum ServerEventData {
+    Text(String),
+    Json(JsonValue)
+}
+
+fn read_event<R: Read>(
+    reader: &mut R,
+    parser: &mut EventParser,
+    timeout: Duration
+
+) -> Result<ReceivedEvent, bool> {
+
+    let start = Instant::now();
+    let mut buffer = [0; 1024];
+    loop {
+
+        if let Some(event) = parser.next_event() {
+            return Ok(event);
+
+        } else if start.elapsed() >= timeout {
+            return Err(true);
+        }
+
+        match reader.read(&mut buffer) {
+            Ok(0) => return Err(false),
+            Ok(size) => parser.push(&buffer[..size]),
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => {},
+            Err(ref err) if err.kind() == ErrorKind::WouldBlock ||
+                            err.kind() == ErrorKind::TimedOut => return Err(true),
+            Err(_) => return Err(false)
+        }
+
+    }
+
+}
+
+fn validate_event(
+    index: usize,
+    expected: &ServerEvent,
+    actual: &ReceivedEvent,
+    compare_depth: usize
+
+) -> Vec<String> {
+
+    let context = format!(
+        "{} {}",
+        "Event".yellow(),
+        format!("{}", index).blue().bold()
+    );
+
+    let mut errors = Vec::new();
+    if let Some(ref name) = expected.name {
+        if *name != actual.name {
+            errors.push(format!(
+                "{} {}",
+                context,
+                format_mismatch("name", name.as_str(), actual.name.as_str())
+            ));
+        }
+    }
+
+    if let Some(ref id) = expected.id {
+        if Some(id) != actual.id.as_ref() {
+            errors.push(format!(
+                "{} {}",
+                context,
+                format_mismatch(
+                    "id",
+                    id.as_str(),
+                    actual.id.as_ref().map_or("none", |id| id.as_str())
+                )
+            ));
+        }
+    }
+
+    match expected.data {
+        Some(ServerEventData::Text(ref data)) => {
+            if *data != actual.data {
+                errors.push(format!(
+                    "{} {}",
+                    context,
+                    format_mismatch("data", data.as_str(), actual.data.as_str())
+                ));
+            }
+        },
+        Some(ServerEventData::Json(ref value)) => {
+            match util::json::parse(actual.data.as_bytes(), "JSON") {
+                Ok(data) => if let Err(json_errors) = util::json::compare(
+                    value,
+                    &data,
+                    compare_depth,
+                    true
+                ) {
+                    errors.push(format!(
+                        "{} {}\n\n        {}",
+                        context,
+                        "JSON data does not match:".yellow(),
+                        util::json::format(json_errors)
+                    ));
+                },
+                Err(err) => errors.push(format!("{} {}", context, err))
+            }
+        },
+        None => {}
+    }
+
+    errors
+
+}
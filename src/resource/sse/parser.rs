@@ -0,0 +1,106 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::collections::VecDeque;
+
+
+// Noir Internal --------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedEvent {
+    pub name: String,
+    pub id: Option<String>,
+    pub data: String
+}
+
+/// Incremental parser for a `text/event-stream` body.
+///
+/// Data can be pushed in arbitrary slices, complete events are queued once
+/// their terminating blank line has been received.
+#[derive(Default)]
+pub struct EventParser {
+    buffer: Vec<u8>,
+    name: Option<String>,
+    id: Option<String>,
+    data: Vec<String>,
+    events: VecDeque<ReceivedEvent>
+}
+
+impl EventParser {
+
+    pub fn new() -> EventParser {
+        EventParser::default()
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+
+        self.buffer.extend_from_slice(data);
+
+        while let Some(index) = self.buffer.iter().position(|b| *b == b'\n') {
+
+            let mut line = self.buffer.drain(0..index + 1).collect::<Vec<u8>>();
+            line.pop();
+
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+
+            let line = String::from_utf8_lossy(&line[..]).into_owned();
+            self.parse_line(line.as_str());
+
+        }
+
+    }
+
+    pub fn next_event(&mut self) -> Option<ReceivedEvent> {
+        self.events.pop_front()
+    }
+
+    fn parse_line(&mut self, line: &str) {
+
+        // A blank line dispatches the current event
+        if line.is_empty() {
+
+            let name = self.name.take();
+            if !self.data.is_empty() {
+                self.events.push_back(ReceivedEvent {
+                    name: name.unwrap_or_else(|| "message".to_string()),
+                    id: self.id.clone(),
+                    data: self.data.join("\n")
+                });
+                self.data.clear();
+            }
+
+        // Comments
+        } else if !line.starts_with(':') {
+
+            let (field, value) = match line.find(':') {
+                Some(index) => {
+                    let value = &line[index + 1..];
+                    if value.starts_with(' ') {
+                        (&line[..index], &value[1..])
+
+                    } else {
+                        (&line[..index], value)
+                    }
+                },
+                None => (line, "")
+            };
+
+            match field {
+                "event" => self.name = Some(value.to_string()),
+                "id" => self.id = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+
+        }
+
+    }
+
+}
@@ -0,0 +1,299 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::io::{Error, ErrorKind, Read, Write};
+
+
+// External Dependencies ------------------------------------------------------
+use rand;
+use openssl::crypto::hash::{hash, Type};
+
+
+// Noir Internal --------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebSocketFrame {
+    Text(String),
+    Binary(Vec<u8>),
+    Close(Option<u16>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>)
+}
+
+impl WebSocketFrame {
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            WebSocketFrame::Text(_) => "text frame",
+            WebSocketFrame::Binary(_) => "binary frame",
+            WebSocketFrame::Close(_) => "close frame",
+            WebSocketFrame::Ping(_) => "ping frame",
+            WebSocketFrame::Pong(_) => "pong frame"
+        }
+    }
+}
+
+/// Reads the next frame from the `stream`, collecting fragmented messages
+/// until their final frame.
+///
+/// Pings and pongs which are interleaved with the fragments of a message are
+/// handled here, so the partial message is not lost; pings are answered with
+/// a pong that is `masked` as required for the local peer.
+pub fn read_frame<S: Read + Write + ?Sized>(
+    stream: &mut S,
+    masked: bool
+
+) -> Result<WebSocketFrame, Error> {
+
+    // Fragmented messages are collected until their final frame
+    let mut message: Option<(u8, Vec<u8>)> = None;
+
+    loop {
+
+        let mut head = [0; 2];
+        try!(stream.read_exact(&mut head));
+
+        let fin = head[0] & 0x80 != 0;
+        let opcode = head[0] & 0x0F;
+        let is_masked = head[1] & 0x80 != 0;
+
+        let length = match head[1] & 0x7F {
+            126 => {
+                let mut bytes = [0; 2];
+                try!(stream.read_exact(&mut bytes));
+                bytes.iter().fold(0, |l, b| (l << 8) | *b as usize)
+            },
+            127 => {
+                let mut bytes = [0; 8];
+                try!(stream.read_exact(&mut bytes));
+                bytes.iter().fold(0, |l, b| (l << 8) | *b as usize)
+            },
+            length => length as usize
+        };
+
+        let mut mask = [0; 4];
+        if is_masked {
+            try!(stream.read_exact(&mut mask));
+        }
+
+        let mut payload = vec![0; length];
+        try!(stream.read_exact(&mut payload[..]));
+
+        if is_masked {
+            for (index, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[index % 4];
+            }
+        }
+
+        match opcode {
+            0x0 => match message.as_mut() {
+                Some(message) => message.1.append(&mut payload),
+                None => return Err(protocol_error())
+            },
+            0x1 | 0x2 => if message.is_some() {
+                return Err(protocol_error());
+
+            } else {
+                message = Some((opcode, payload));
+            },
+            0x8 => return Ok(WebSocketFrame::Close(if payload.len() >= 2 {
+                Some(((payload[0] as u16) << 8) | payload[1] as u16)
+
+            } else {
+                None
+            })),
+            0x9 => if message.is_some() {
+                try!(write_frame(stream, &WebSocketFrame::Pong(payload), masked));
+                continue;
+
+            } else {
+                return Ok(WebSocketFrame::Ping(payload));
+            },
+            0xA => if message.is_some() {
+                continue;
+
+            } else {
+                return Ok(WebSocketFrame::Pong(payload));
+            },
+            _ => return Err(protocol_error())
+        }
+
+        if fin {
+            if let Some((opcode, data)) = message.take() {
+                return if opcode == 0x1 {
+                    String::from_utf8(data).map(WebSocketFrame::Text).map_err(|_| {
+                        Error::new(ErrorKind::InvalidData, "noir: Invalid UTF-8 in WebSocket text frame.")
+                    })
+
+                } else {
+                    Ok(WebSocketFrame::Binary(data))
+                };
+            }
+        }
+
+    }
+
+}
+
+pub fn write_frame<S: Write + ?Sized>(
+    stream: &mut S,
+    frame: &WebSocketFrame,
+    masked: bool
+
+) -> Result<(), Error> {
+
+    let (opcode, mut payload) = match *frame {
+        WebSocketFrame::Text(ref text) => (0x1, text.as_bytes().to_vec()),
+        WebSocketFrame::Binary(ref data) => (0x2, data.clone()),
+        WebSocketFrame::Close(Some(code)) => (0x8, vec![(code >> 8) as u8, code as u8]),
+        WebSocketFrame::Close(None) => (0x8, Vec::new()),
+        WebSocketFrame::Ping(ref data) => (0x9, data.clone()),
+        WebSocketFrame::Pong(ref data) => (0xA, data.clone())
+    };
+
+    let mask_bit = if masked { 0x80 } else { 0x00 };
+    let mut data = vec![0x80 | opcode];
+    if payload.len() < 126 {
+        data.push(mask_bit | payload.len() as u8);
+
+    } else if payload.len() <= 0xFFFF {
+        data.push(mask_bit | 126);
+        data.push((payload.len() >> 8) as u8);
+        data.push(payload.len() as u8);
+
+    } else {
+        data.push(mask_bit | 127);
+        for shift in (0..8).rev() {
+            data.push((payload.len() as u64 >> (shift * 8)) as u8);
+        }
+    }
+
+    // Frames sent by clients must always be masked
+    if masked {
+        let mask: [u8; 4] = [rand::random(), rand::random(), rand::random(), rand::random()];
+        data.extend_from_slice(&mask);
+        for (index, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[index % 4];
+        }
+    }
+
+    data.append(&mut payload);
+    try!(stream.write_all(&data[..]));
+    stream.flush()
+
+}
+
+pub fn websocket_key() -> String {
+    let key = (0..16).map(|_| rand::random::<u8>()).collect::<Vec<u8>>();
+    base64(&key[..])
+}
+
+pub fn websocket_accept(key: &str) -> String {
+    let data = format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key.trim());
+    base64(&hash(Type::SHA1, data.as_bytes())[..])
+}
+
+
+// Helpers --------------------------------------------------------------------
+fn protocol_error() -> Error {
+    Error::new(ErrorKind::InvalidData, "noir: Invalid WebSocket frame.")
+}
+
+fn base64(data: &[u8]) -> String {
+
+    const CHARS: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0)
+        ];
+
+        let n = ((bytes[0] as usize) << 16) | ((bytes[1] as usize) << 8) | bytes[2] as usize;
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(CHARS[(n >> (18 - index * 6)) & 0x3F] as char);
+
+            } else {
+                encoded.push('=');
+            }
+        }
+
+    }
+
+    encoded
+
+}
+
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+
+    use std::io::{Cursor, Read, Result, Write};
+    use super::{WebSocketFrame, read_frame};
+
+    struct Stream {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>
+    }
+
+    impl Read for Stream {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Stream {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_read_frame_control_between_fragments() {
+
+        let mut stream = Stream {
+            input: Cursor::new(vec![
+                // Text fragment "Hel"
+                0x01, 0x03, b'H', b'e', b'l',
+                // Ping "p"
+                0x89, 0x01, b'p',
+                // Pong
+                0x8A, 0x00,
+                // Final continuation "lo"
+                0x80, 0x02, b'l', b'o',
+                // Ping "q"
+                0x89, 0x01, b'q'
+            ]),
+            output: Vec::new()
+        };
+
+        assert_eq!(
+            read_frame(&mut stream, false).unwrap(),
+            WebSocketFrame::Text("Hello".to_string())
+        );
+
+        // The interleaved ping is answered with an unmasked pong
+        assert_eq!(stream.output, vec![0x8A, 0x01, b'p']);
+
+        assert_eq!(
+            read_frame(&mut stream, false).unwrap(),
+            WebSocketFrame::Ping(b"q".to_vec())
+        );
+
+    }
+
+}
@@ -0,0 +1,493 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// STD Dependencies -----------------------------------------------------------
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::str;
+use std::io::{Error, ErrorKind, Read, Write};
+
+
+// External Dependencies ------------------------------------------------------
+use json;
+use json::JsonValue;
+use colored::*;
+use httparse;
+use hyper::header::Headers;
+use hyper::status::StatusCode;
+
+
+// Internal Dependencies ------------------------------------------------------
+use util;
+use util::format_mismatch;
+use Options;
+use mock::{MockConnection, MockUpgrade};
+use resource::http::{HttpBody, HttpResource};
+
+
+// Modules --------------------------------------------------------------------
+mod frame;
+
+
+// Re-Exports -----------------------------------------------------------------
+pub use self::frame::{
+    WebSocketFrame,
+    read_frame, write_frame,
+    websocket_key, websocket_accept
+};
+
+
+/// A scripted WebSocket conversation of frames that are sent and frames that
+/// are expected to be received.
+///
+/// The steps of the conversation are performed in the order in which they
+/// were added. Expected frames are compared against the received frames in
+/// the same order, pings are answered automatically.
+///
+/// A conversation can either be held with the API under test via
+/// `HttpRequest::with_websocket()`, or be provided to the API from a mocked
+/// upstream server via `HttpResponse::with_websocket()`.
+///
+/// # Example Usage
+///
+/// ```rust
+/// # #[macro_use] extern crate json;
+/// # extern crate noir;
+/// use noir::WebSocket;
+///
+/// # fn main() {
+/// let websocket = WebSocket::new()
+///     .send_text("subscribe")
+///     .expected_json(object! {
+///         "subscribed" => true
+///     })
+///     .send_close(1000)
+///     .expected_close(1000);
+/// # }
+/// ```
+pub struct WebSocket {
+    steps: Vec<WebSocketStep>,
+    options: Option<Options>,
+    state: Arc<Mutex<WebSocketState>>
+}
+
+impl WebSocket {
+
+    /// Creates a new, empty WebSocket conversation.
+    pub fn new() -> WebSocket {
+        WebSocket {
+            steps: Vec::new(),
+            options: None,
+            state: Arc::new(Mutex::new(WebSocketState::default()))
+        }
+    }
+
+    /// Sets additional options for the conversation.
+    ///
+    /// Defaults to the options of the request or response which holds the
+    /// conversation.
+    pub fn with_options(mut self, options: Options) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Sends a text frame.
+    pub fn send_text<S: Into<String>>(mut self, text: S) -> Self {
+        self.steps.push(WebSocketStep::Send(WebSocketFrame::Text(text.into())));
+        self
+    }
+
+    /// Sends a text frame containing the serialized JSON `value`.
+    pub fn send_json(mut self, value: JsonValue) -> Self {
+        self.steps.push(WebSocketStep::Send(WebSocketFrame::Text(json::stringify(value))));
+        self
+    }
+
+    /// Sends a binary frame.
+    pub fn send_binary<D: Into<Vec<u8>>>(mut self, data: D) -> Self {
+        self.steps.push(WebSocketStep::Send(WebSocketFrame::Binary(data.into())));
+        self
+    }
+
+    /// Sends a close frame with the specified status `code`.
+    pub fn send_close(mut self, code: u16) -> Self {
+        self.steps.push(WebSocketStep::Send(WebSocketFrame::Close(Some(code))));
+        self
+    }
+
+    /// Expects the next received frame to be a text frame containing `text`.
+    pub fn expected_text<S: Into<String>>(mut self, text: S) -> Self {
+        self.steps.push(WebSocketStep::Expect(WebSocketExpectation::Text(text.into())));
+        self
+    }
+
+    /// Expects the next received frame to be a text frame containing JSON
+    /// which deep compares equal to `value`.
+    pub fn expected_json(mut self, value: JsonValue) -> Self {
+        self.steps.push(WebSocketStep::Expect(WebSocketExpectation::Json(value)));
+        self
+    }
+
+    /// Expects the next received frame to be a binary frame containing
+    /// `data`.
+    pub fn expected_binary<D: Into<Vec<u8>>>(mut self, data: D) -> Self {
+        self.steps.push(WebSocketStep::Expect(WebSocketExpectation::Binary(data.into())));
+        self
+    }
+
+    /// Expects the next received frame to be a close frame with the
+    /// specified status `code`.
+    pub fn expected_close(mut self, code: u16) -> Self {
+        self.steps.push(WebSocketStep::Expect(WebSocketExpectation::Close(code)));
+        self
+    }
+
+}
+
+impl Default for WebSocket {
+    fn default() -> WebSocket {
+        WebSocket::new()
+    }
+}
+
+
+// Noir Internal --------------------------------------------------------------
+pub struct WebSocketHandshake {
+    pub status: StatusCode,
+    pub headers: Headers
+}
+
+impl WebSocketHandshake {
+
+    pub fn parse(data: &[u8]) -> Result<WebSocketHandshake, Error> {
+
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut res = httparse::Response::new(&mut headers);
+
+        match res.parse(data) {
+            Ok(httparse::Status::Complete(_)) => {
+                let headers = try!(Headers::from_raw(res.headers).map_err(|err| {
+                    Error::new(ErrorKind::InvalidData, format!("{}", err))
+                }));
+                Ok(WebSocketHandshake {
+                    status: StatusCode::from_u16(res.code.unwrap_or(0)),
+                    headers: headers
+                })
+            },
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                "noir: Invalid WebSocket handshake response."
+            ))
+        }
+
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get_raw(name).and_then(|values| {
+            values.get(0).and_then(|value| str::from_utf8(value).ok())
+        })
+    }
+
+}
+
+impl HttpResource for WebSocketHandshake {
+
+    fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    fn into_http_body(&mut self) -> HttpBody where Self: Sized {
+        HttpBody::from(Vec::<u8>::new())
+    }
+
+}
+
+pub struct WebSocketSession {
+    steps: Vec<WebSocketStep>,
+    compare_depth: usize,
+    state: Arc<Mutex<WebSocketState>>
+}
+
+impl WebSocketSession {
+
+    pub fn run<S: Read + Write + ?Sized>(&self, stream: &mut S, client: bool) {
+
+        let mut closed = false;
+        for step in &self.steps {
+            match *step {
+                WebSocketStep::Send(ref frame) => {
+                    if let WebSocketFrame::Close(_) = *frame {
+                        closed = true;
+                    }
+                    if write_frame(stream, frame, client).is_err() {
+                        return;
+                    }
+                },
+                WebSocketStep::Expect(ref expected) => {
+
+                    let frame = match next_frame(stream, client) {
+                        Some(frame) => frame,
+                        None => return
+                    };
+
+                    {
+                        let mut state = lock_state(&self.state);
+                        let index = state.results.len() + 1;
+                        state.results.push(validate_frame(
+                            index,
+                            expected,
+                            &frame,
+                            self.compare_depth
+                        ));
+                    }
+
+                    // Close frames received from the peer are echoed
+                    if let WebSocketFrame::Close(code) = frame {
+                        if !closed {
+                            write_frame(stream, &WebSocketFrame::Close(code), client).ok();
+                        }
+                        return;
+                    }
+
+                }
+            }
+        }
+
+        // Clients close the connection once all steps were performed, servers
+        // wait for their peer to close it instead
+        if client && !closed {
+            write_frame(stream, &WebSocketFrame::Close(Some(1000)), client).ok();
+            closed = true;
+        }
+
+        // Any further frames until the connection is closed are unexpected
+        while let Some(frame) = next_frame(stream, client) {
+            if let WebSocketFrame::Close(code) = frame {
+                if !closed {
+                    write_frame(stream, &WebSocketFrame::Close(code), client).ok();
+                }
+                return;
+
+            } else {
+                lock_state(&self.state).unexpected.push(format!(
+                    "{} {} {}{}",
+                    "Received unexpected".yellow(),
+                    frame.kind().red().bold(),
+                    "after all expected frames".yellow(),
+                    ".".yellow()
+                ));
+            }
+        }
+
+    }
+
+}
+
+impl MockUpgrade for WebSocketSession {
+    fn serve(&mut self, stream: &mut MockConnection) -> Result<(), Error> {
+        self.run(stream, false);
+        Ok(())
+    }
+}
+
+pub fn websocket_session(websocket: &WebSocket, options: &Options) -> WebSocketSession {
+
+    // Results are reset for every new conversation
+    *lock_state(&websocket.state) = WebSocketState::default();
+
+    WebSocketSession {
+        steps: websocket.steps.clone(),
+        compare_depth: websocket.options.as_ref().unwrap_or(options).json_compare_depth,
+        state: websocket.state.clone()
+    }
+
+}
+
+pub fn websocket_validate(websocket: &WebSocket) -> Vec<String> {
+
+    let state = lock_state(&websocket.state);
+    let mut errors = state.results.iter().filter_map(|error| {
+        error.clone()
+
+    }).collect::<Vec<String>>();
+
+    // Expected frames which were never received
+    let expectations = websocket.steps.iter().filter_map(|step| {
+        match *step {
+            WebSocketStep::Expect(ref expected) => Some(expected),
+            WebSocketStep::Send(_) => None
+        }
+
+    }).collect::<Vec<&WebSocketExpectation>>();
+
+    for (index, expected) in expectations.iter().enumerate().skip(state.results.len()) {
+        errors.push(format!(
+            "{} {} {} {}{} {}{}",
+            "Frame".yellow(),
+            format!("{}", index + 1).blue().bold(),
+            "was expected to be a".yellow(),
+            expected.kind().green().bold(),
+            ", but".yellow(),
+            "none was received".red().bold(),
+            ".".yellow()
+        ));
+    }
+
+    errors.extend(state.unexpected.iter().cloned());
+    errors
+
+}
+
+
+// Helpers --------------------------------------------------------------------
+#[derive(Clone)]
+enum WebSocketStep {
+    Send(WebSocketFrame),
+    Expect(WebSocketExpectation)
+}
+
+#[derive(Clone)]
+enum WebSocketExpectation {
+    Text(String),
+    Json(JsonValue),
+    Binary(Vec<u8>),
+    Close(u16)
+}
+
+impl WebSocketExpectation {
+    fn kind(&self) -> &'static str {
+        match *self {
+            WebSocketExpectation::Text(_) | WebSocketExpectation::Json(_) => "text frame",
+            WebSocketExpectation::Binary(_) => "binary frame",
+            WebSocketExpectation::Close(_) => "close frame"
+        }
+    }
+}
+
+#[derive(Default)]
+struct WebSocketState {
+    results: Vec<Option<String>>,
+    unexpected: Vec<String>
+}
+
+fn lock_state(state: &Mutex<WebSocketState>) -> MutexGuard<WebSocketState> {
+    // Results are only ever appended, so they remain usable if a session
+    // panicked while holding the lock
+    match state.lock() {
+        Ok(state) => state,
+        Err(err) => err.into_inner()
+    }
+}
+
+fn next_frame<S: Read + Write + ?Sized>(stream: &mut S, client: bool) -> Option<WebSocketFrame> {
+    loop {
+        match read_frame(stream, client) {
+            Ok(WebSocketFrame::Ping(data)) => {
+                if write_frame(stream, &WebSocketFrame::Pong(data), client).is_err() {
+                    return None;
+                }
+            },
+            Ok(WebSocketFrame::Pong(_)) => {},
+            Ok(frame) => return Some(frame),
+            Err(_) => return None
+        }
+    }
+}
+
+fn validate_frame(
+    index: usize,
+    expected: &WebSocketExpectation,
+    actual: &WebSocketFrame,
+    compare_depth: usize
+
+) -> Option<String> {
+
+    let context = format!(
+        "{} {}",
+        "Frame".yellow(),
+        format!("{}", index).blue().bold()
+    );
+
+    match (expected, actual) {
+        (&WebSocketExpectation::Text(ref expected), &WebSocketFrame::Text(ref actual)) => {
+            if expected != actual {
+                Some(format!(
+                    "{} {}",
+                    context,
+                    format_mismatch("text", expected.as_str(), actual.as_str())
+                ))
+
+            } else {
+                None
+            }
+        },
+        (&WebSocketExpectation::Json(ref expected), &WebSocketFrame::Text(ref actual)) => {
+            match util::json::parse(actual.as_bytes(), "JSON") {
+                Ok(actual) => match util::json::compare(
+                    expected,
+                    &actual,
+                    compare_depth,
+                    true
+                ) {
+                    Ok(_) => None,
+                    Err(errors) => Some(format!(
+                        "{} {}\n\n        {}",
+                        context,
+                        "JSON does not match:".yellow(),
+                        util::json::format(errors)
+                    ))
+                },
+                Err(err) => Some(format!("{} {}", context, err))
+            }
+        },
+        (&WebSocketExpectation::Binary(ref expected), &WebSocketFrame::Binary(ref actual)) => {
+            if expected != actual {
+                Some(format!(
+                    "{} {}\n\n       [{}]\n\n    {}\n\n       [{}]",
+                    context,
+                    "binary data does not match, expected:".yellow(),
+                    util::raw::format_green(&expected[..]),
+                    "but got:".yellow(),
+                    util::raw::format_red(&actual[..])
+                ))
+
+            } else {
+                None
+            }
+        },
+        (&WebSocketExpectation::Close(expected), &WebSocketFrame::Close(actual)) => {
+            if Some(expected) != actual {
+                Some(format!(
+                    "{} {}",
+                    context,
+                    format_mismatch(
+                        "close code",
+                        format!("{}", expected).as_str(),
+                        actual.map_or("none".to_string(), |code| {
+                            format!("{}", code)
+
+                        }).as_str()
+                    )
+                ))
+
+            } else {
+                None
+            }
+        },
+        (expected, actual) => Some(format!(
+            "{} {} {}{} {}{}",
+            context,
+            "was expected to be a".yellow(),
+            expected.kind().green().bold(),
+            ", but got a".yellow(),
+            actual.kind().red().bold(),
+            ".".yellow()
+        ))
+    }
+
+}
+
@@ -6,10 +6,41 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+// STD Dependencies -----------------------------------------------------------
+use std::time::Duration;
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+
 
 // Modules --------------------------------------------------------------------
 pub mod diff;
 pub mod form;
 pub mod json;
 pub mod raw;
+pub mod xml;
+
+
+// Formatting Utilities -------------------------------------------------------
+pub fn duration_millis(duration: Duration) -> u64 {
+    duration.as_secs().saturating_mul(1000).saturating_add(
+        (duration.subsec_nanos() / 1_000_000) as u64
+    )
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{}ms", duration_millis(duration))
+}
 
+pub fn format_mismatch(name: &str, expected: &str, actual: &str) -> String {
+    format!(
+        "{} {} \"{}\" {} \"{}\"{}",
+        name.yellow(),
+        "does not match, expected".yellow(),
+        expected.green().bold(),
+        "but got".yellow(),
+        actual.red().bold(),
+        ".".yellow()
+    )
+}
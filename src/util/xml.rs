@@ -0,0 +1,747 @@
+// Copyright (c) 2016 Ivo Wetzel
+
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// STD Dependencies -----------------------------------------------------------
+use std::str;
+use std::char;
+
+
+// External Dependencies ------------------------------------------------------
+use colored::*;
+
+
+// Internal Dependencies ------------------------------------------------------
+use util;
+
+
+// XML Structure --------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub struct XmlName {
+    namespace: Option<String>,
+    local: String
+}
+
+/// A parsed XML element with all namespace prefixes resolved.
+///
+/// The text of an element is the trimmed concatenation of all its direct
+/// text and CDATA nodes, comments and processing instructions are dropped.
+#[derive(Debug, Clone)]
+pub struct XmlElement {
+    name: XmlName,
+    attributes: Vec<(XmlName, String)>,
+    text: String,
+    children: Vec<XmlElement>
+}
+
+
+// Parsing Utilities ----------------------------------------------------------
+pub fn parse(data: &[u8], err_prefix: &'static str) -> Result<XmlElement, String> {
+    match str::from_utf8(data) {
+        Ok(text) => match parse_document(text) {
+            Ok(element) => Ok(element),
+            Err(err) => Err(format!(
+                "{}\n\n        {}",
+                format!("{} is invalid:", err_prefix).yellow(),
+                err.red().bold()
+            ))
+        },
+        Err(err) => Err(format!(
+            "{}\n\n        {}",
+            format!("{} contains invalid UTF-8:", err_prefix).yellow(),
+            format!("{:?}", err).red().bold()
+        ))
+    }
+}
+
+
+// Structural XML Compare -----------------------------------------------------
+pub fn compare(
+    expected: &XmlElement,
+    actual: &XmlElement,
+    check_additional: bool
+
+) -> Result<(), Vec<(Vec<String>, String)>> {
+
+    let errors = if expected.name != actual.name {
+        vec![(
+            vec![],
+            format!(
+                "{} {} {} {}",
+                "Expected element".yellow(),
+                format_name(&expected.name).green().bold(),
+                "but found".yellow(),
+                format_name(&actual.name).red().bold()
+            )
+        )]
+
+    } else {
+        compare_element(
+            check_additional,
+            vec![format!("/{}", expected.name.local.blue().bold())],
+            expected,
+            actual
+        )
+    };
+
+    if errors.is_empty() {
+        Ok(())
+
+    } else {
+        Err(errors)
+    }
+
+}
+
+pub fn format(errors: Vec<(Vec<String>, String)>) -> String {
+    errors.into_iter().map(|(path, message)| {
+        format!("- {}{}: {}", "xml".blue().bold(), path.join(""), message)
+
+    }).collect::<Vec<String>>().join("\n\n        ")
+}
+
+
+// Recursive Compare Function -------------------------------------------------
+fn compare_element(
+    check_additional: bool,
+    path: Vec<String>,
+    a: &XmlElement,
+    b: &XmlElement
+
+) -> Vec<(Vec<String>, String)> {
+
+    let mut errors = vec![];
+
+    // Attributes are compared regardless of their order
+    let mut b_attributes = b.attributes.iter().collect::<Vec<&(XmlName, String)>>();
+    let mut missing_attributes = Vec::new();
+
+    for &(ref a_name, ref a_value) in &a.attributes {
+        match b_attributes.iter().position(|&&(ref b_name, _)| b_name == a_name) {
+            Some(index) => {
+                let &(_, ref b_value) = b_attributes.remove(index);
+                if a_value != b_value {
+                    let mut attribute_path = path.clone();
+                    attribute_path.push(format!("/@{}", a_name.local.blue().bold()));
+                    errors.push((
+                        attribute_path,
+                        text_mismatch("Attribute", a_value, b_value)
+                    ));
+                }
+            },
+            None => missing_attributes.push(a_name)
+        }
+    }
+
+    if !missing_attributes.is_empty() {
+        errors.push((
+            path.clone(),
+            format!(
+                "{} {} {} {} ({})",
+                "Element".green().bold(),
+                "is missing".yellow(),
+                format!("{}", missing_attributes.len()).red().bold(),
+                "attribute(s)".yellow(),
+                format_names(&missing_attributes[..])
+            )
+        ));
+    }
+
+    if check_additional && !b_attributes.is_empty() {
+        let names = b_attributes.iter().map(|&&(ref name, _)| name).collect::<Vec<&XmlName>>();
+        errors.push((
+            path.clone(),
+            format!(
+                "{} {} {} {} ({})",
+                "Element".green().bold(),
+                "has".yellow(),
+                format!("{}", names.len()).red().bold(),
+                "additional unexpected attribute(s)".yellow(),
+                format_names(&names[..])
+            )
+        ));
+    }
+
+    // Empty elements only match empty text in exact mode
+    if (check_additional || !a.text.is_empty()) && a.text != b.text {
+        errors.push((path.clone(), text_mismatch("Text", &a.text, &b.text)));
+    }
+
+    // Child elements are matched by their name and their order among the
+    // siblings with the same name
+    let mut b_used = vec![false; b.children.len()];
+    let mut missing_children = Vec::new();
+
+    for (index, a_child) in a.children.iter().enumerate() {
+
+        let occurrence = a.children[..index].iter().filter(|child| {
+            child.name == a_child.name
+
+        }).count();
+
+        let siblings = b.children.iter().enumerate().filter(|&(_, child)| {
+            child.name == a_child.name
+
+        }).collect::<Vec<(usize, &XmlElement)>>();
+
+        match siblings.get(occurrence) {
+            Some(&(b_index, b_child)) => {
+
+                b_used[b_index] = true;
+
+                let mut child_path = path.clone();
+                child_path.push(format!("/{}", a_child.name.local.blue().bold()));
+                if siblings.len() > 1 {
+                    child_path.push(format!("[{}]", occurrence).purple().bold().to_string());
+                }
+
+                errors.append(&mut compare_element(
+                    check_additional,
+                    child_path,
+                    a_child,
+                    b_child
+                ));
+
+            },
+            None => missing_children.push(&a_child.name)
+        }
+
+    }
+
+    if !missing_children.is_empty() {
+        errors.push((
+            path.clone(),
+            format!(
+                "{} {} {} {} ({})",
+                "Element".green().bold(),
+                "is missing".yellow(),
+                format!("{}", missing_children.len()).red().bold(),
+                "child element(s)".yellow(),
+                format_names(&missing_children[..])
+            )
+        ));
+    }
+
+    let additional_children = b.children.iter().zip(b_used.iter()).filter(|&(_, used)| {
+        !*used
+
+    }).map(|(child, _)| &child.name).collect::<Vec<&XmlName>>();
+
+    if check_additional && !additional_children.is_empty() {
+        errors.push((
+            path,
+            format!(
+                "{} {} {} {} ({})",
+                "Element".green().bold(),
+                "has".yellow(),
+                format!("{}", additional_children.len()).red().bold(),
+                "additional unexpected child element(s)".yellow(),
+                format_names(&additional_children[..])
+            )
+        ));
+    }
+
+    errors
+
+}
+
+fn text_mismatch(kind: &str, expected: &str, actual: &str) -> String {
+    let (expected, actual, diff) = util::diff::text(expected, actual);
+    format!(
+        "{} {}\n\n              \"{}\"\n\n          {}\n\n              \"{}\"\n\n          {}\n\n              \"{}\"",
+        kind.green().bold(),
+        "does not match, expected:".yellow(),
+        expected.green().bold(),
+        "but got:".yellow(),
+        actual.red().bold(),
+        "difference:".yellow(),
+        diff
+    )
+}
+
+fn format_name(name: &XmlName) -> String {
+    match name.namespace {
+        Some(ref namespace) => format!("{{{}}}{}", namespace, name.local),
+        None => name.local.clone()
+    }
+}
+
+fn format_names(names: &[&XmlName]) -> String {
+    names.iter().map(|name| {
+        format_name(name).red().bold().to_string()
+
+    }).collect::<Vec<String>>().join(", ")
+}
+
+
+// XML Parser -----------------------------------------------------------------
+const XML_NAMESPACE: &'static str = "http://www.w3.org/XML/1998/namespace";
+
+type NamespaceScope = Vec<(String, String)>;
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}.", message, self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_left().len();
+    }
+
+    fn skip_past(&mut self, end: &str, what: &str) -> Result<(), String> {
+        match self.rest().find(end) {
+            Some(index) => {
+                self.pos += index + end.len();
+                Ok(())
+            },
+            None => Err(self.error(&format!("Unterminated {}", what)))
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+
+        } else {
+            Err(self.error(&format!("Expected \"{}\"", token)))
+        }
+    }
+
+    // Skips the prolog and anything else which may surround the root element
+    fn skip_misc(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                try!(self.skip_past("?>", "processing instruction"));
+
+            } else if self.rest().starts_with("<!--") {
+                try!(self.skip_past("-->", "comment"));
+
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                try!(self.skip_doctype());
+
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_doctype(&mut self) -> Result<(), String> {
+
+        // Internal subsets may contain additional markup declarations
+        let mut depth = 0;
+        for (index, c) in self.rest().char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '>' if depth == 0 => {
+                    self.pos += index + 1;
+                    return Ok(());
+                },
+                _ => {}
+            }
+        }
+
+        Err(self.error("Unterminated document type declaration"))
+
+    }
+
+    fn name(&mut self) -> Result<&'a str, String> {
+
+        let rest = self.rest();
+        let end = rest.find(|c: char| {
+            c.is_whitespace() || "/>=<\"'".contains(c)
+
+        }).unwrap_or(rest.len());
+
+        if end == 0 {
+            Err(self.error("Expected a name"))
+
+        } else {
+            self.pos += end;
+            Ok(&rest[..end])
+        }
+
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+
+        let rest = self.rest();
+        let quote = match rest.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => return Err(self.error("Expected a quoted attribute value"))
+        };
+
+        match rest[1..].find(quote) {
+            Some(end) => {
+                let value = try!(unescape(&rest[1..end + 1]).map_err(|err| self.error(&err)));
+                self.pos += end + 2;
+                Ok(value)
+            },
+            None => Err(self.error("Unterminated attribute value"))
+        }
+
+    }
+
+    fn element(&mut self, parent_scope: &NamespaceScope) -> Result<XmlElement, String> {
+
+        try!(self.expect("<"));
+
+        let raw_name = try!(self.name());
+        let mut scope = parent_scope.clone();
+        let mut raw_attributes = Vec::new();
+        let mut empty = false;
+
+        loop {
+
+            self.skip_whitespace();
+
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                empty = true;
+                break;
+
+            } else if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+
+            } else if self.rest().is_empty() {
+                return Err(self.error("Unexpected end of document"));
+            }
+
+            let name = try!(self.name());
+            self.skip_whitespace();
+            try!(self.expect("="));
+            self.skip_whitespace();
+            let value = try!(self.quoted());
+
+            // Namespace declarations are only used to resolve names
+            if name == "xmlns" {
+                scope.push((String::new(), value));
+
+            } else if name.starts_with("xmlns:") {
+                scope.push((name[6..].to_string(), value));
+
+            } else {
+                raw_attributes.push((name, value));
+            }
+
+        }
+
+        let mut attributes = Vec::new();
+        for (name, value) in raw_attributes {
+            attributes.push((try!(self.resolve(&scope, name, false)), value));
+        }
+
+        let mut element = XmlElement {
+            name: try!(self.resolve(&scope, raw_name, true)),
+            attributes: attributes,
+            text: String::new(),
+            children: Vec::new()
+        };
+
+        if empty {
+            return Ok(element);
+        }
+
+        let mut text = String::new();
+        loop {
+
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&format!(
+                    "Unexpected end of document, expected closing tag </{}>",
+                    raw_name
+                )));
+
+            } else if rest.starts_with("</") {
+
+                self.pos += 2;
+
+                let closing_name = try!(self.name());
+                if closing_name != raw_name {
+                    return Err(self.error(&format!(
+                        "Expected closing tag </{}> but found </{}>",
+                        raw_name,
+                        closing_name
+                    )));
+                }
+
+                self.skip_whitespace();
+                try!(self.expect(">"));
+                break;
+
+            } else if rest.starts_with("<!--") {
+                try!(self.skip_past("-->", "comment"));
+
+            } else if rest.starts_with("<![CDATA[") {
+                match rest.find("]]>") {
+                    Some(end) => {
+                        text.push_str(&rest[9..end]);
+                        self.pos += end + 3;
+                    },
+                    None => return Err(self.error("Unterminated CDATA section"))
+                }
+
+            } else if rest.starts_with("<?") {
+                try!(self.skip_past("?>", "processing instruction"));
+
+            } else if rest.starts_with('<') {
+                element.children.push(try!(self.element(&scope)));
+
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                text.push_str(&try!(unescape(&rest[..end]).map_err(|err| self.error(&err))));
+                self.pos += end;
+            }
+
+        }
+
+        element.text = text.trim().to_string();
+        Ok(element)
+
+    }
+
+    fn resolve(&self, scope: &NamespaceScope, raw: &str, is_element: bool) -> Result<XmlName, String> {
+
+        let (prefix, local) = match raw.find(':') {
+            Some(index) => (&raw[..index], &raw[index + 1..]),
+            None => ("", raw)
+        };
+
+        // Unprefixed attributes are never part of the default namespace
+        let namespace = if prefix.is_empty() && !is_element {
+            None
+
+        } else if prefix == "xml" {
+            Some(XML_NAMESPACE.to_string())
+
+        } else {
+            match scope.iter().rev().find(|&&(ref p, _)| p == prefix) {
+                Some(&(_, ref uri)) if uri.is_empty() => None,
+                Some(&(_, ref uri)) => Some(uri.clone()),
+                None if prefix.is_empty() => None,
+                None => return Err(self.error(&format!(
+                    "Undeclared namespace prefix \"{}\"",
+                    prefix
+                )))
+            }
+        };
+
+        Ok(XmlName {
+            namespace: namespace,
+            local: local.to_string()
+        })
+
+    }
+
+}
+
+fn parse_document(text: &str) -> Result<XmlElement, String> {
+
+    let mut parser = Parser {
+        text: text.trim_left_matches('\u{feff}'),
+        pos: 0
+    };
+
+    try!(parser.skip_misc());
+
+    if !parser.rest().starts_with('<') {
+        return Err(parser.error("Expected a root element"));
+    }
+
+    let root = try!(parser.element(&Vec::new()));
+    try!(parser.skip_misc());
+
+    if parser.rest().is_empty() {
+        Ok(root)
+
+    } else {
+        Err(parser.error("Unexpected content after the root element"))
+    }
+
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+
+        result.push_str(&rest[..start]);
+
+        let end = match rest[start..].find(';') {
+            Some(end) => start + end,
+            None => return Err("Unterminated entity reference".to_string())
+        };
+
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => {
+                u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+            },
+            _ if entity.starts_with('#') => {
+                entity[1..].parse::<u32>().ok().and_then(char::from_u32)
+            },
+            _ => None
+        };
+
+        match c {
+            Some(c) => result.push(c),
+            None => return Err(format!("Unknown entity reference \"&{};\"", entity))
+        }
+
+        rest = &rest[end + 1..];
+
+    }
+
+    result.push_str(rest);
+    Ok(result)
+
+}
+
+
+// Tests ----------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+
+    use super::{compare, parse_document};
+
+    fn uncolor(mut text: String) -> String {
+        text = text.replace("\u{1b}", "");
+        text = text.replace("[1;31m", "<br>");
+        text = text.replace("[1;32m", "<bg>");
+        text = text.replace("[33m", "<by>");
+        text = text.replace("[1;34m", "<bb>");
+        text = text.replace("[1;35m", "<bp>");
+        text = text.replace("[36m", "<bn>");
+        text = text.replace("[1;36m", "<bc>");
+        text = text.replace("[1;42;37m", "<gbg>");
+        text = text.replace("[1;41;37m", "<gbr>");
+        text.replace("[0m", "")
+    }
+
+    fn cmp(expected: &str, actual: &str, errors: Vec<(&str, &str)>) {
+        cmp_base(expected, actual, errors, false);
+    }
+
+    fn cmp_base(expected: &str, actual: &str, errors: Vec<(&str, &str)>, exact: bool) {
+        let expected = parse_document(expected).unwrap();
+        let actual = parse_document(actual).unwrap();
+        match compare(&expected, &actual, exact) {
+            Ok(()) => {
+                assert!(errors.is_empty());
+            },
+            Err(e) => {
+                let formatted = e.into_iter().map(|e| {
+                    (uncolor(e.0.join("")), uncolor(e.1))
+
+                }).collect::<Vec<(String, String)>>();
+
+                let err = formatted.iter().map(|e| {
+                    (e.0.as_str(), e.1.as_str())
+
+                }).collect::<Vec<(&str, &str)>>();
+
+                assert_eq!(err, errors);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_document("").unwrap_err(), "Expected a root element at position 0.");
+        assert_eq!(parse_document("<a>").unwrap_err(), "Unexpected end of document, expected closing tag </a> at position 3.");
+        assert_eq!(parse_document("<a></b>").unwrap_err(), "Expected closing tag </a> but found </b> at position 6.");
+        assert_eq!(parse_document("<a/><b/>").unwrap_err(), "Unexpected content after the root element at position 4.");
+        assert_eq!(parse_document("<x:a/>").unwrap_err(), "Undeclared namespace prefix \"x\" at position 6.");
+        assert_eq!(parse_document("<a>&nbsp;</a>").unwrap_err(), "Unknown entity reference \"&nbsp;\" at position 3.");
+    }
+
+    #[test]
+    fn test_compare_prolog_and_entities() {
+        cmp(
+            "<a>&lt;&#65;&#x42;&amp;</a>",
+            "<?xml version=\"1.0\"?><!DOCTYPE a [<!ELEMENT a (#PCDATA)>]><!-- c --><a> <![CDATA[<AB&]]> </a>",
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_compare_root_mismatch() {
+        cmp("<a/>", "<b/>", vec![
+            ("", "<by>Expected element <bg>a <by>but found <br>b")
+        ]);
+        cmp("<a xmlns=\"urn:x\"/>", "<a xmlns=\"urn:y\"/>", vec![
+            ("", "<by>Expected element <bg>{urn:x}a <by>but found <br>{urn:y}a")
+        ]);
+    }
+
+    #[test]
+    fn test_compare_namespace_prefixes() {
+        cmp(
+            "<x:a xmlns:x=\"urn:x\" x:id=\"1\"><x:b>Text</x:b></x:a>",
+            "<a xmlns=\"urn:x\" xmlns:y=\"urn:x\" y:id=\"1\"><b>Text</b></a>",
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_compare_attributes() {
+        cmp("<a x=\"1\" y=\"2\"/>", "<a y=\"2\" z=\"3\" x=\"1\"/>", vec![]);
+        cmp("<a x=\"1\" y=\"2\"/>", "<a y=\"3\"/>", vec![
+            ("/<bb>a/@<bb>y", "<bg>Attribute <by>does not match, expected:\n\n              \"<bg>2\"\n\n          <by>but got:\n\n              \"<br>3\"\n\n          <by>difference:\n\n              \"<gbr>2 <gbg>3\""),
+            ("/<bb>a", "<bg>Element <by>is missing <br>1 <by>attribute(s) (<br>x)")
+        ]);
+        cmp_base("<a x=\"1\"/>", "<a z=\"3\" x=\"1\"/>", vec![
+            ("/<bb>a", "<bg>Element <by>has <br>1 <by>additional unexpected attribute(s) (<br>z)")
+        ], true);
+    }
+
+    #[test]
+    fn test_compare_text() {
+        cmp("<a/>", "<a>Text</a>", vec![]);
+        cmp("<a>Foo</a>", "<a>\n    Bar\n</a>", vec![
+            ("/<bb>a", "<bg>Text <by>does not match, expected:\n\n              \"<bg>Foo\"\n\n          <by>but got:\n\n              \"<br>Bar\"\n\n          <by>difference:\n\n              \"<gbr>Foo <gbg>Bar\"")
+        ]);
+        cmp_base("<a/>", "<a>Text</a>", vec![
+            ("/<bb>a", "<bg>Text <by>does not match, expected:\n\n              \"<bg>\"\n\n          <by>but got:\n\n              \"<br>Text\"\n\n          <by>difference:\n\n              \"<gbg>Text\"")
+        ], true);
+    }
+
+    #[test]
+    fn test_compare_children() {
+        cmp("<a><c/><b/></a>", "<a><b/><d/><c/></a>", vec![]);
+        cmp("<a><b>1</b><b>3</b><c/></a>", "<a><b>1</b><b>2</b></a>", vec![
+            ("/<bb>a/<bb>b<bp>[1]", "<bg>Text <by>does not match, expected:\n\n              \"<bg>3\"\n\n          <by>but got:\n\n              \"<br>2\"\n\n          <by>difference:\n\n              \"<gbr>3 <gbg>2\""),
+            ("/<bb>a", "<bg>Element <by>is missing <br>1 <by>child element(s) (<br>c)")
+        ]);
+        cmp_base("<a><b/></a>", "<a><b/><d/><b/></a>", vec![
+            ("/<bb>a", "<bg>Element <by>has <br>2 <by>additional unexpected child element(s) (<br>d, <br>b)")
+        ], true);
+    }
+
+}
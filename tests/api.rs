@@ -4,6 +4,9 @@
 mod base_test;
 test!();
 
+use hyper::net::HttpListener;
+use noir::HttpApiProbe;
+
 #[derive(Copy, Clone, Default)]
 pub struct TimeoutAPI;
 impl HttpApi for TimeoutAPI {
@@ -26,21 +29,180 @@ impl HttpApi for TimeoutAPI {
 
 }
 
+#[derive(Copy, Clone, Default)]
+pub struct ProbeAPI;
+impl HttpApi for ProbeAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4005
+    }
+
+    fn probe(&self) -> HttpApiProbe {
+        HttpApiProbe::new(Method::Get, "/get/hello")
+            .expected_status(StatusCode::Ok)
+            .expected_body(|body| body == "Hello World")
+            .with_interval(Duration::from_millis(20))
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct ProbeTimeoutAPI;
+impl HttpApi for ProbeTimeoutAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4006
+    }
+
+    fn probe(&self) -> HttpApiProbe {
+        HttpApiProbe::new(Method::Get, "/status/404")
+            .expected_status(StatusCode::Ok)
+            .with_deadline(Duration::from_millis(300))
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct ProbeApiTimeoutAPI;
+impl HttpApi for ProbeApiTimeoutAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4011
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(400)
+    }
+
+    fn probe(&self) -> HttpApiProbe {
+        HttpApiProbe::new(Method::Get, "/status/404")
+            .expected_status(StatusCode::Ok)
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct EphemeralAPI;
+impl HttpApi for EphemeralAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        Self::ephemeral_port()
+    }
+
+    fn start(&self) {
+        let listener = Self::ephemeral_listener().unwrap();
+        let _listening = hyper::Server::new(HttpListener::from(listener)).handle(handle);
+    }
+
+}
+
 #[test]
 fn test_api_start_timeout() {
 
     let actual = {
-        TimeoutAPI::get("/").collect()
+        multiline(TimeoutAPI::get("/").collect().unwrap_err())
+    };
+
+    // The description of the connection error differs between platforms
+    assert!(actual.starts_with(r#"
+<br>API Failure: <by>Server for \"<bn>http://localhost:4001\" <by>did not respond within <bg>500ms<by>.
+
+    <by>Last readiness probe error: <br>"#), "{}", actual);
+
+}
+
+
+#[test]
+fn test_api_start_probe() {
+
+    let actual = {
+        ProbeAPI::get("/get/hello").expected_status(StatusCode::Ok).collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+
+#[test]
+fn test_api_start_probe_timeout() {
+
+    let actual = {
+        ProbeTimeoutAPI::get("/").collect()
     };
 
     assert_fail!(r#"
-<br>API Failure: <by>Server for \"<bn>http://localhost:4001\" <by>did not respond within <bg>1000ms<by>.
+<br>API Failure: <by>Server for \"<bn>http://localhost:4006\" <by>did not respond within <bg>300ms<by>.
+
+    <by>Last readiness probe error: <br>Expected status \"200 OK\" but got \"404 Not Found\".
 
 "#, actual);
 
 }
 
 
+#[test]
+fn test_api_start_probe_api_timeout() {
+
+    let actual = {
+        ProbeApiTimeoutAPI::get("/").collect()
+    };
+
+    assert_fail!(r#"
+<br>API Failure: <by>Server for \"<bn>http://localhost:4011\" <by>did not respond within <bg>400ms<by>.
+
+    <by>Last readiness probe error: <br>Expected status \"200 OK\" but got \"404 Not Found\".
+
+"#, actual);
+
+}
+
+
+#[test]
+fn test_api_start_ephemeral_port() {
+
+    let port = EphemeralAPI.port();
+    assert!(port != 0);
+    assert_eq!(EphemeralAPI.port(), port);
+    assert_eq!(EphemeralAPI.url(), format!("http://localhost:{}", port));
+
+    let actual = {
+        EphemeralAPI::get("/get/hello").expected_status(StatusCode::Ok).collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+
 #[test]
 fn test_api_start_multiple() {
 
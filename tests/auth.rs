@@ -0,0 +1,188 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+use noir::HttpSignature;
+use hyper::header::{Authorization, Basic, Bearer};
+
+
+// Credentials ----------------------------------------------------------------
+#[test]
+fn test_auth_basic() {
+
+    let actual = {
+        API::get("/headers/echo")
+            .with_basic_auth("user", Some("password"))
+            .expected_header(Authorization(Basic {
+                username: "user".to_string(),
+                password: Some("password".to_string())
+            }))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_auth_bearer() {
+
+    let actual = {
+        API::get("/headers/echo")
+            .with_bearer_token("token")
+            .expected_header(Authorization(Bearer {
+                token: "token".to_string()
+            }))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_auth_api_key_query() {
+
+    let actual = {
+        API::get("/auth/key?page=1")
+            .with_api_key_query("api_key", "secret")
+            .expected_body("Route not found: GET /auth/key?page=1&api_key=secret")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+
+// Signatures -----------------------------------------------------------------
+#[test]
+fn test_auth_signature() {
+
+    let actual = {
+        API::get("/headers/echo")
+            .with_signature(
+                HttpSignature::hmac_sha256("secret")
+                    .with_header("Authorization")
+                    .with_prefix("HMAC ")
+            )
+            .expected_header(Authorization(
+                "HMAC 13718dd44a570aa35b9b6e390de11cdb22ee48ad14b9dcd8fea34a76533068f6".to_string()
+            ))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_auth_signature_canonicalization() {
+
+    let actual = {
+        API::get("/headers/echo")
+            .with_signature(
+                HttpSignature::hmac_sha256("secret")
+                    .with_header("Authorization")
+                    .with_canonicalization(|method, path, _| {
+                        format!("{} {}", method, path)
+                    })
+            )
+            .expected_header(Authorization(
+                "df0fae592fe87b493e31d70f5c88449017a239e52af4f4d82046f13f5d2771e8".to_string()
+            ))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+
+// Challenges -----------------------------------------------------------------
+#[test]
+fn test_auth_challenge() {
+
+    let actual = {
+        API::get("/auth/challenge")
+            .expected_auth_challenge("Bearer", Some("api"))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_auth_challenge_multiple() {
+
+    let actual = {
+        API::get("/auth/challenges")
+            .expected_auth_challenge("Basic", Some("a, b"))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+    let actual = {
+        API::get("/auth/challenges")
+            .expected_auth_challenge("Bearer", Some("api"))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_auth_challenge_multiple_mismatch() {
+
+    let actual = {
+        API::get("/auth/challenges")
+            .expected_auth_challenge("Basic", Some("api"))
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/auth/challenges\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>header \"<bb>WWW-Authenticate\" <by>does not contain the expected challenge:
+
+        \"<bg>Basic realm=\"api\"\"
+
+    <by>but got:
+
+        \"<br>Basic realm=\"a, b\", Bearer realm=\"api\", error=\"invalid_token\"\"
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_auth_challenge_mismatch() {
+
+    let actual = {
+        API::get("/auth/challenge")
+            .expected_auth_challenge("Basic", Some("api"))
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/auth/challenge\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>header \"<bb>WWW-Authenticate\" <by>does not contain the expected challenge:
+
+        \"<bg>Basic realm=\"api\"\"
+
+    <by>but got:
+
+        \"<br>Bearer realm=\"api\"\"
+
+
+"#, actual);
+
+}
+
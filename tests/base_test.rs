@@ -2,12 +2,14 @@ macro_rules! test { () => {
 
 // Crates ---------------------------------------------------------------------
 extern crate colored;
+extern crate flate2;
 extern crate hyper;
+extern crate openssl;
 
 
 // STD Dependencies -----------------------------------------------------------
 use std::thread;
-use std::io::Read;
+use std::io::{ErrorKind, Read};
 use std::time::Duration;
 use std::net::ToSocketAddrs;
 
@@ -15,7 +17,10 @@ use std::net::ToSocketAddrs;
 // External Dependencies ------------------------------------------------------
 use hyper::method::Method;
 use hyper::status::StatusCode;
-use hyper::header::{Accept, Connection, ContentType, ContentLength, Server, qitem};
+use hyper::header::{
+    Accept, Connection, ContentType, ContentLength, ContentEncoding,
+    Encoding, Server, TransferEncoding, qitem
+};
 use hyper::mime::{Mime, TopLevel, SubLevel, Attr, Value};
 use hyper::server::{Request, Response};
 use hyper::uri::RequestUri;
@@ -58,6 +63,24 @@ fn handle(mut req: Request, mut res: Response) {
                 "".to_string()
             },
 
+            (Method::Get, "/auth/challenge") => {
+                *res.status_mut() = StatusCode::Unauthorized;
+                res.headers_mut().set_raw(
+                    "WWW-Authenticate",
+                    vec![b"Bearer realm=\"api\"".to_vec()]
+                );
+                "".to_string()
+            },
+
+            (Method::Get, "/auth/challenges") => {
+                *res.status_mut() = StatusCode::Unauthorized;
+                res.headers_mut().set_raw(
+                    "WWW-Authenticate",
+                    vec![b"Basic realm=\"a, b\", Bearer realm=\"api\", error=\"invalid_token\"".to_vec()]
+                );
+                "".to_string()
+            },
+
             (Method::Get, "/headers/echo") => {
                 *res.headers_mut() = req.headers.clone();
                 "".to_string()
@@ -126,6 +149,93 @@ fn handle(mut req: Request, mut res: Response) {
 
             },
 
+            (Method::Post, "/graphql") => {
+
+                let mut body = String::new();
+                req.read_to_string(&mut body).unwrap();
+
+                res.headers_mut().set(
+                    ContentType(Mime(TopLevel::Application, SubLevel::Json, vec![]))
+                );
+
+                if body.contains("deleteUser") {
+                    r#"{"data":null,"errors":[{"message":"Not allowed","extensions":{"code":"FORBIDDEN"}}]}"#.to_string()
+
+                } else {
+                    r#"{"data":{"user":{"id":"1","name":"Alice","roles":["admin","editor"]}}}"#.to_string()
+                }
+
+            },
+
+            (Method::Post, "/graphql/forward") => {
+
+                let mut body = Vec::new();
+                req.read_to_end(&mut body).unwrap();
+
+                let _ext = hyper_client!().post(
+                    "https://example.com/graphql"
+
+                ).header(
+                    ContentType(Mime(TopLevel::Application, SubLevel::Json, vec![]))
+
+                ).body(&body[..]).send();
+
+                "".to_string()
+
+            },
+
+            (Method::Post, "/rpc") => {
+
+                let mut body = String::new();
+                req.read_to_string(&mut body).unwrap();
+
+                res.headers_mut().set(
+                    ContentType(Mime(TopLevel::Application, SubLevel::Json, vec![]))
+                );
+
+                rpc_response(body.as_str())
+
+            },
+
+            (Method::Post, "/rpc/forward") => {
+
+                let mut body = Vec::new();
+                req.read_to_end(&mut body).unwrap();
+
+                res.headers_mut().set(
+                    ContentType(Mime(TopLevel::Application, SubLevel::Json, vec![]))
+                );
+
+                match hyper_client!().post(
+                    "https://example.com/rpc"
+
+                ).header(
+                    ContentType(Mime(TopLevel::Application, SubLevel::Json, vec![]))
+
+                ).body(&body[..]).send() {
+                    Ok(mut r) => {
+                        let mut body = String::new();
+                        r.read_to_string(&mut body).ok();
+                        body
+                    },
+                    Err(_) => "".to_string()
+                }
+
+            },
+
+            (Method::Get, "/events") => {
+                return stream_events(res, true);
+            },
+
+            (Method::Get, "/events/short") => {
+                return stream_events(res, false);
+            },
+
+            (Method::Get, "/events/delayed") => {
+                thread::sleep(Duration::from_millis(300));
+                return stream_events(res, false);
+            },
+
             (Method::Get, "/responses/none") => {
                 "".to_string()
             },
@@ -144,6 +254,205 @@ fn handle(mut req: Request, mut res: Response) {
                 "".to_string()
             },
 
+            (Method::Get, "/responses/repeat") => {
+                external_request(&mut res, "/one");
+                external_request(&mut res, "/one");
+                external_request(&mut res, "/one");
+                "".to_string()
+            },
+
+            (Method::Get, "/responses/fault") => {
+
+                let mut client = hyper_client!();
+                client.set_read_timeout(Some(Duration::from_millis(100)));
+
+                match client.get("https://example.com/one").send() {
+                    Ok(mut r) => {
+                        let mut body = String::new();
+                        match r.read_to_string(&mut body) {
+                            Ok(_) => body,
+                            Err(e) => {
+                                *res.status_mut() = StatusCode::BadGateway;
+                                format!("{:?}", e.kind())
+                            }
+                        }
+                    },
+                    Err(hyper::Error::Io(ref e)) if e.kind() == ErrorKind::WouldBlock => {
+                        *res.status_mut() = StatusCode::GatewayTimeout;
+                        format!("{:?}", e.kind())
+                    },
+                    Err(_) => {
+                        *res.status_mut() = StatusCode::BadGateway;
+                        "".to_string()
+                    }
+                }
+
+            },
+
+            (Method::Get, "/responses/decode") => {
+
+                let mut r = hyper_client!().get("https://example.com/one").send().unwrap();
+
+                let mut data = Vec::new();
+                r.read_to_end(&mut data).unwrap();
+
+                let encoding = r.headers.get::<ContentEncoding>().and_then(|e| e.get(0)).cloned();
+                let mut body = String::new();
+                match encoding {
+                    Some(Encoding::Gzip) => {
+                        flate2::read::GzDecoder::new(&data[..]).unwrap().read_to_string(&mut body).unwrap();
+                    },
+                    Some(Encoding::Deflate) => {
+                        flate2::read::ZlibDecoder::new(&data[..]).read_to_string(&mut body).unwrap();
+                    },
+                    _ => {
+                        body = String::from_utf8(data).unwrap();
+                    }
+                }
+
+                if r.headers.has::<TransferEncoding>() {
+                    res.headers_mut().set(Server("Chunked".to_string()));
+                }
+
+                body
+
+            },
+
+            (Method::Get, "/responses/headers") => {
+
+                let mut headers = hyper::header::Headers::new();
+                for i in 0..32 {
+                    headers.set_raw(format!("X-Header-{}", i), vec![format!("{}", i).into_bytes()]);
+                }
+                headers.set(Server("Headers".to_string()));
+
+                let _ext = hyper_client!().get(
+                    "https://example.com/one"
+
+                ).headers(headers).send();
+
+                "".to_string()
+
+            },
+
+            (Method::Get, "/responses/chunked") => {
+
+                let mut body: &[u8] = b"Hello World";
+                let _ext = hyper_client!().post(
+                    "https://example.com/forward"
+
+                ).header(
+                    ContentType(Mime(TopLevel::Text, SubLevel::Plain, vec![]))
+
+                ).body(hyper::client::Body::ChunkedBody(&mut body)).send();
+
+                "".to_string()
+
+            },
+
+            (Method::Get, "/responses/loopback") => {
+
+                // Use a plain client which is not intercepted by noir
+                let url = format!("{}/one", EXAMPLE.loopback_url());
+                match hyper::Client::new().get(url.as_str()).send() {
+                    Ok(mut r) => {
+                        let mut body = String::new();
+                        r.read_to_string(&mut body).ok();
+                        *res.status_mut() = r.status;
+                        body
+                    },
+                    Err(_) => {
+                        *res.status_mut() = StatusCode::BadGateway;
+                        "".to_string()
+                    }
+                }
+
+            },
+
+            (Method::Get, "/responses/proxy") => {
+                proxy_request(b"GET https://example.com/one HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n")
+            },
+
+            (Method::Get, "/responses/proxy/connect") => {
+                proxy_request(b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\nGET /one HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n")
+            },
+
+            (Method::Get, "/responses/proxy/tls") => {
+                proxy_tls_request()
+            },
+
+            (Method::Get, "/responses/loopback/tls") => {
+                loopback_tls_request()
+            },
+
+            (Method::Get, "/responses/websocket") => {
+                websocket_request()
+            },
+
+            (Method::Get, "/mails/reset") => {
+                send_mail(
+                    "user@example.com",
+                    "Reset your password",
+                    "Follow the link below:\r\n\r\nhttps://example.com/reset/12345\r\n"
+                )
+            },
+
+            (Method::Get, "/mails/twice") => {
+                send_mail("user@example.com", "First", "Hello");
+                send_mail("user@example.com", "Second", "World")
+            },
+
+            (Method::Get, "/mails/encoded") => {
+                send_raw_mail(
+                    vec!["user@example.com"],
+                    "Subject: =?UTF-8?B?R3LDvMOfZQ==?= =?UTF-8?Q?_aus_K=C3=B6ln?=\r\n\
+                     Content-Type: text/plain; charset=utf-8\r\n\
+                     Content-Transfer-Encoding: quoted-printable\r\n\r\n\
+                     Follow the link f=C3=BCr Sie: https://example.com/reset/=\r\n\
+                     12345\r\n"
+                )
+            },
+
+            (Method::Get, "/mails/base64") => {
+                send_raw_mail(
+                    vec!["user@example.com"],
+                    "Subject: Welcome\r\n\
+                     Content-Type: text/plain; charset=\"utf-8\"\r\n\
+                     Content-Transfer-Encoding: base64\r\n\r\n\
+                     SGFsbG8gSsO8cmdlbiwKaHR0cHM6Ly9leGFt\r\n\
+                     cGxlLmNvbS9jb25maXJtLzcK\r\n"
+                )
+            },
+
+            (Method::Get, "/mails/team") => {
+                send_raw_mail(
+                    vec!["alice@example.com", "bob@example.com"],
+                    "Subject: Team update\r\n\r\nHello team\r\n"
+                )
+            },
+
+            (Method::Get, "/cache/user") => {
+                cached_user()
+            },
+
+            (Method::Get, "/mocks/env") => {
+                std::env::var("NOIR_MOCK_VAR").unwrap_or_else(|_| "unset".to_string())
+            },
+
+            (Method::Get, "/mocks/file") => {
+                let mut body = String::new();
+                std::env::var("NOIR_MOCK_DIR").ok().and_then(|dir| {
+                    std::fs::File::open(format!("{}/data/file.txt", dir)).ok()
+
+                }).map(|mut file| file.read_to_string(&mut body));
+                body
+            },
+
+            (Method::Get, "/mocks/clock") => {
+                let time = system_time!().duration_since(std::time::UNIX_EPOCH).unwrap();
+                format!("{}", time.as_secs())
+            },
+
             (Method::Post, "/form") => {
 
                 let mut body = String::new();
@@ -238,6 +547,257 @@ fn external_request(res: &mut Response, path: &'static str) -> String {
 
 }
 
+fn rpc_response(body: &str) -> String {
+    let request = json::parse(body).unwrap();
+    if request.is_array() {
+        // Answer batches in reverse order
+        let mut responses = request.members().filter_map(rpc_call).collect::<Vec<json::JsonValue>>();
+        responses.reverse();
+        json::stringify(json::JsonValue::Array(responses))
+
+    } else {
+        rpc_call(&request).map_or(String::new(), json::stringify)
+    }
+}
+
+fn rpc_call(call: &json::JsonValue) -> Option<json::JsonValue> {
+
+    // Notifications are not answered
+    if call["id"].is_null() {
+        return None;
+    }
+
+    let mut response = object! {
+        "jsonrpc" => "2.0",
+        "id" => call["id"].clone()
+    };
+
+    match call["method"].as_str() {
+        Some("sum") => {
+            let sum = call["params"].members().filter_map(|v| v.as_f64()).fold(0.0, |a, b| a + b);
+            response["result"] = sum.into();
+        },
+        Some("echo") => {
+            response["result"] = call["params"].clone();
+        },
+        _ => {
+            response["error"] = object! {
+                "code" => -32601,
+                "message" => "Method not found"
+            };
+        }
+    }
+
+    Some(response)
+
+}
+
+fn stream_events(mut res: Response, keep_open: bool) {
+
+    use std::io::Write;
+
+    res.headers_mut().set_raw("Content-Type", vec![b"text/event-stream".to_vec()]);
+
+    let mut res = res.start().unwrap();
+    res.write_all(b": connected\n\nevent: update\nid: 1\ndata: {\"count\": 1}\n\n").unwrap();
+    res.flush().unwrap();
+
+    if keep_open {
+
+        thread::sleep(Duration::from_millis(50));
+        res.write_all(b"event: update\r\nid: 2\r\ndata: {\"count\":\r\ndata: 2}\r\n\r\n").unwrap();
+        res.flush().unwrap();
+
+        // Never complete the stream, keep it alive until the client disconnects
+        for _ in 0..50 {
+            thread::sleep(Duration::from_millis(100));
+            if res.write_all(b": keep-alive\n\n").and_then(|_| res.flush()).is_err() {
+                return;
+            }
+        }
+
+    }
+
+    res.end().ok();
+
+}
+
+fn websocket_request() -> String {
+
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    let url = EXAMPLE.loopback_url();
+    let port = url.rsplit(':').next().unwrap().parse::<u16>().unwrap();
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+    stream.write_all(b"GET /socket HTTP/1.1\r\nHost: example.com\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n").unwrap();
+
+    // Skip the handshake response
+    let mut head = Vec::new();
+    let mut byte = [0; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+
+    // Send a masked text frame, using an empty mask
+    stream.write_all(b"\x81\x85\x00\x00\x00\x00hello").unwrap();
+
+    // Return the text of the reply frame
+    let mut frame = [0; 2];
+    let mut text = String::new();
+    if stream.read_exact(&mut frame).is_ok() {
+        let mut payload = vec![0; (frame[1] & 0x7F) as usize];
+        stream.read_exact(&mut payload[..]).unwrap();
+        text = String::from_utf8_lossy(&payload[..]).to_string();
+    }
+
+    stream.write_all(b"\x88\x82\x00\x00\x00\x00\x03\xE8").ok();
+    text
+
+}
+
+fn send_mail(to: &str, subject: &str, body: &str) -> String {
+    send_raw_mail(vec![to], format!("Subject: {}\r\n\r\n{}", subject, body).as_str())
+}
+
+fn send_raw_mail(recipients: Vec<&str>, data: &str) -> String {
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", noir::SmtpServer::port())).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut commands = vec![
+        "HELO localhost\r\n".to_string(),
+        "MAIL FROM:<noreply@example.com>\r\n".to_string()
+    ];
+
+    for to in recipients {
+        commands.push(format!("RCPT TO:<{}>\r\n", to));
+    }
+
+    commands.push("DATA\r\n".to_string());
+    commands.push(format!("{}\r\n.\r\n", data));
+    commands.push("QUIT\r\n".to_string());
+
+    let mut reply = String::new();
+    for command in commands {
+        reply.clear();
+        reader.read_line(&mut reply).unwrap();
+        stream.write_all(command.as_bytes()).unwrap();
+    }
+
+    // Return the reply to the submitted message
+    reply.trim().to_string()
+
+}
+
+fn cached_user() -> String {
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", noir::RedisServer::port())).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut command = |args: Vec<&str>| {
+        let mut data = format!("*{}\r\n", args.len());
+        for arg in args {
+            data.push_str(format!("${}\r\n{}\r\n", arg.len(), arg).as_str());
+        }
+        stream.write_all(data.as_bytes()).unwrap();
+
+        let mut reply = String::new();
+        reader.read_line(&mut reply).unwrap();
+        if reply.starts_with("$") && reply.trim() != "$-1" {
+            reply.clear();
+            reader.read_line(&mut reply).unwrap();
+        }
+        reply.trim().to_string()
+    };
+
+    // Fill the cache on misses
+    let user = command(vec!["GET", "user:1"]);
+    if user == "$-1" {
+        command(vec!["SET", "user:1", "Bob", "EX", "60"]);
+        "Bob".to_string()
+
+    } else {
+        user
+    }
+
+}
+
+fn proxy_request(request: &[u8]) -> String {
+
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", noir::HttpProxy::port())).unwrap();
+    stream.write_all(request).unwrap();
+
+    // Return the body of the last response
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok();
+    response.rsplit("\r\n\r\n").next().unwrap_or("").to_string()
+
+}
+
+fn proxy_tls_request() -> String {
+
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    let mut stream = TcpStream::connect(("127.0.0.1", noir::HttpProxy::port())).unwrap();
+    stream.write_all(b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n").unwrap();
+
+    // Skip the response to the CONNECT request
+    let mut head = Vec::new();
+    let mut byte = [0; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+
+    tls_request(stream)
+
+}
+
+fn loopback_tls_request() -> String {
+
+    use std::net::TcpStream;
+
+    let url = EXAMPLE.loopback_tls_url();
+    assert!(url.starts_with("https://127.0.0.1:"));
+
+    let port = url.rsplit(':').next().unwrap().parse::<u16>().unwrap();
+    tls_request(TcpStream::connect(("127.0.0.1", port)).unwrap())
+
+}
+
+fn tls_request(stream: ::std::net::TcpStream) -> String {
+
+    use std::io::Write;
+    use openssl::ssl::{SslContext, SslMethod, SslStream, SSL_VERIFY_PEER};
+
+    let mut context = SslContext::new(SslMethod::Sslv23).unwrap();
+    context.set_CA_file(noir::HttpProxy::ca_certificate_path()).unwrap();
+    context.set_verify(SSL_VERIFY_PEER, None);
+
+    let mut stream = SslStream::connect(&context, stream).unwrap();
+    stream.write_all(b"GET /one HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n").unwrap();
+
+    // Return the body of the response
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok();
+    response.rsplit("\r\n\r\n").next().unwrap_or("").to_string()
+
+}
+
 fn test_server<T: ToSocketAddrs>(addr: T) {
     let _listening = hyper::Server::http(addr).unwrap().handle(handle);
 }
@@ -560,6 +560,140 @@ fn test_body_with_expected_json_compare_depth_zero() {
 }
 
 
+// XML Bodies -----------------------------------------------------------------
+#[test]
+fn test_body_with_expected_xml() {
+
+    let actual = {
+        API::post("/echo")
+            .with_header(ContentType(
+                Mime(TopLevel::Application, SubLevel::Xml, vec![])
+            ))
+            .with_body("<?xml version=\"1.0\"?><u:user xmlns:u=\"urn:users\" id=\"1\" role=\"admin\"><u:name>Alice</u:name><u:email>alice@example.com</u:email></u:user>")
+            .expected_body("<user xmlns=\"urn:users\" role=\"admin\" id=\"1\"><name>Alice</name></user>")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_body_with_expected_xml_mismatch() {
+
+    let actual = {
+        API::post("/echo")
+            .with_header(ContentType(
+                Mime(TopLevel::Text, SubLevel::Xml, vec![])
+            ))
+            .with_body("<user id=\"1\" role=\"admin\"><name>Alice</name><tag>a</tag><tag>b</tag></user>")
+            .expected_body("<user id=\"2\" active=\"true\"><name>Bob</name><tag>a</tag><tag>c</tag><email/></user>")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>body XML does not match:
+
+        - <bb>xml/<bb>user/@<bb>id: <bg>Attribute <by>does not match, expected:
+
+              \"<bg>2\"
+
+          <by>but got:
+
+              \"<br>1\"
+
+          <by>difference:
+
+              \"<gbr>2 <gbg>1\"
+
+        - <bb>xml/<bb>user: <bg>Element <by>is missing <br>1 <by>attribute(s) (<br>active)
+
+        - <bb>xml/<bb>user/<bb>name: <bg>Text <by>does not match, expected:
+
+              \"<bg>Bob\"
+
+          <by>but got:
+
+              \"<br>Alice\"
+
+          <by>difference:
+
+              \"<gbr>Bob <gbg>Alice\"
+
+        - <bb>xml/<bb>user/<bb>tag<bp>[1]: <bg>Text <by>does not match, expected:
+
+              \"<bg>c\"
+
+          <by>but got:
+
+              \"<br>b\"
+
+          <by>difference:
+
+              \"<gbr>c <gbg>b\"
+
+        - <bb>xml/<bb>user: <bg>Element <by>is missing <br>1 <by>child element(s) (<br>email)
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_body_with_expected_xml_mismatch_exact() {
+
+    let actual = {
+        API::post("/echo")
+            .with_header(ContentType(
+                Mime(TopLevel::Application, SubLevel::Xml, vec![])
+            ))
+            .with_body("<user id=\"1\" role=\"admin\"><name>Alice</name><email>alice@example.com</email></user>")
+            .expected_exact_body("<user id=\"1\"><name>Alice</name></user>")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>body XML does not match:
+
+        - <bb>xml/<bb>user: <bg>Element <by>has <br>1 <by>additional unexpected attribute(s) (<br>role)
+
+        - <bb>xml/<bb>user: <bg>Element <by>has <br>1 <by>additional unexpected child element(s) (<br>email)
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_body_with_expected_xml_invalid() {
+
+    let actual = {
+        API::post("/echo")
+            .with_header(ContentType(
+                Mime(TopLevel::Application, SubLevel::Xml, vec![])
+            ))
+            .with_body("<user><name>Alice</user>")
+            .expected_body("<user/>")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>body XML is invalid:
+
+        <br>Expected closing tag </name> but found </user> at position 23.
+
+
+"#, actual);
+
+}
+
+
 // Set Headers from Body ------------------------------------------------------
 #[test]
 fn test_body_set_header_from_body_raw() {
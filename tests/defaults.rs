@@ -0,0 +1,120 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+use noir::{HttpHeader, HttpQueryString};
+
+#[derive(Copy, Clone, Default)]
+pub struct DefaultsAPI;
+impl HttpApi for DefaultsAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4007
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+    fn default_headers(&self) -> Vec<HttpHeader> {
+        headers![Server("Default".to_string())]
+    }
+
+    fn default_query(&self) -> HttpQueryString {
+        query! {
+            "token" => "abc"
+        }
+    }
+
+}
+
+#[test]
+fn test_defaults_headers() {
+
+    let actual = {
+        DefaultsAPI::get("/headers/echo")
+            .expected_header(Server("Default".to_string()))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_defaults_headers_override() {
+
+    let actual = {
+        DefaultsAPI::get("/headers/echo")
+            .with_header(Server("Override".to_string()))
+            .expected_header(Server("Override".to_string()))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_defaults_headers_removed() {
+
+    let actual = {
+        DefaultsAPI::get("/headers/echo")
+            .without_header::<Server>()
+            .unexpected_header::<Server>()
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_defaults_query() {
+
+    let actual = {
+        DefaultsAPI::get("/query")
+            .with_query(query! {
+                "page" => 2
+            })
+            .expected_body("Route not found: GET /query?page=2&token=abc")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_defaults_query_override() {
+
+    let actual = {
+        DefaultsAPI::get("/query?token=xyz")
+            .expected_body("Route not found: GET /query?token=xyz")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_defaults_query_removed() {
+
+    let actual = {
+        DefaultsAPI::get("/query")
+            .without_query_param("token")
+            .expected_body("Route not found: GET /query")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
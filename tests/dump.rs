@@ -31,7 +31,7 @@ fn test_dump_response_with_raw_body() {
     assert_fail!(r#"
 <br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
 
-<bb> 1) <by>Response <by>headers dump:
+<bb> 1) <by>Response <by>headers dump (<bn>HTTP/1.1<by>):
 
         <bn>        Accept: <bp>application/json
         <bn>Content-Length: <bp>64
@@ -68,7 +68,7 @@ fn test_dump_response_with_text_body() {
     assert_fail!(r#"
 <br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
 
-<bb> 1) <by>Response <by>headers dump:
+<bb> 1) <by>Response <by>headers dump (<bn>HTTP/1.1<by>):
 
         <bn>        Accept: <bp>application/json
         <bn>Content-Length: <bp>39
@@ -102,7 +102,7 @@ fn test_dump_response_with_text_body_invalid_utf8() {
     assert_fail!(r#"
 <br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
 
-<bb> 1) <by>Response <by>headers dump:
+<bb> 1) <by>Response <by>headers dump (<bn>HTTP/1.1<by>):
 
         <bn>        Accept: <bp>application/json
         <bn>Content-Length: <bp>5
@@ -141,7 +141,7 @@ fn test_dump_response_with_json_body() {
     assert_fail!(r#"
 <br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
 
-<bb> 1) <by>Response <by>headers dump:
+<bb> 1) <by>Response <by>headers dump (<bn>HTTP/1.1<by>):
 
         <bn>        Accept: <bp>application/json
         <bn>Content-Length: <bp>58
@@ -182,7 +182,7 @@ fn test_dump_response_with_json_body_invalid() {
     assert_fail!(r#"
 <br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/echo\" <by>returned <br>1 <by>error(s)
 
-<bb> 1) <by>Response <by>headers dump:
+<bb> 1) <by>Response <by>headers dump (<bn>HTTP/1.1<by>):
 
         <bn>Content-Length: <bp>9
         <bn>  Content-Type: <bp>application/json
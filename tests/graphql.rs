@@ -0,0 +1,193 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+// GraphQL Requests -----------------------------------------------------------
+#[test]
+fn test_graphql_data() {
+
+    let actual = {
+        API::graphql("query GetUser($id: ID!) { user(id: $id) { id name roles } }", object! {
+                "id" => "1"
+            })
+            .expected_status(StatusCode::Ok)
+            .expected_no_graphql_errors()
+            .expected_graphql_data("user.name", "Alice".into())
+            .expected_graphql_data("user.roles.1", "editor".into())
+            .expected_graphql_data("user", object! {
+                "id" => "1",
+                "roles" => array!["admin", "editor"]
+            })
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_graphql_data_mismatch() {
+
+    let actual = {
+        API::graphql("query GetUser { user { name email } }", object! {})
+            .expected_graphql_data("user.roles", array!["admin", "editor", "viewer"])
+            .expected_graphql_data("user.email", "alice@example.com".into())
+            .expected_graphql_error_code("FORBIDDEN")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/graphql\" <by>returned <br>3 <by>error(s)
+
+<bb> 1) <by>Response <by>GraphQL data at \"<bb>user.roles\" <by>does not match:
+
+        - <bb>json: <bg>Array <by>with <br>2 <by>item(s) does not match expected length of <bg>3
+
+<bb> 2) <by>Response <by>GraphQL data at \"<bb>user.email\" <by>is <br>missing<by>.
+
+<bb> 3) <by>Response <by>GraphQL error with code \"<bg>FORBIDDEN\" <by>was expected, but <br>none was returned<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_graphql_errors() {
+
+    let actual = {
+        API::graphql("mutation DeleteUser { deleteUser(id: 1) }", object! {})
+            .expected_graphql_error("Not allowed")
+            .expected_graphql_error_code("FORBIDDEN")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_graphql_errors_mismatch() {
+
+    let actual = {
+        API::graphql("mutation DeleteUser { deleteUser(id: 1) }", object! {})
+            .expected_no_graphql_errors()
+            .expected_graphql_error("Not found")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/graphql\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <by>Response <by>GraphQL errors were expected to be absent, but got <br>1 <by>error(s):
+
+        - \"<br>Not allowed\"
+
+<bb> 2) <by>Response <by>GraphQL error with message \"<bg>Not found\" <by>was expected, but got:
+
+        - \"<br>Not allowed\"
+
+
+"#, actual);
+
+}
+
+
+// GraphQL Responses ----------------------------------------------------------
+#[test]
+fn test_graphql_provided_response() {
+
+    let actual = {
+        API::post("/graphql/forward")
+            .with_body(object! {
+                "query" => "# Fetch the current user\nquery GetUser { user { name } }"
+            })
+            .provide(responses![
+                EXAMPLE.graphql("GetUser").with_body(object! {
+                    "data" => object! {
+                        "user" => object! {
+                            "name" => "Alice"
+                        }
+                    }
+                })
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_graphql_provided_response_operation_name() {
+
+    let actual = {
+        API::post("/graphql/forward")
+            .with_body(object! {
+                "query" => "query ListUsers { users { name } } query GetUser { user { name } }",
+                "operationName" => "GetUser"
+            })
+            .provide(responses![
+                EXAMPLE.graphql("GetUser")
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_graphql_provided_response_fragment() {
+
+    let actual = {
+        API::post("/graphql/forward")
+            .with_body(object! {
+                "query" => "fragment UserFields on User { name }\nquery GetUser($role: String = \"#admin\") { user(role: $role) { ...UserFields } }"
+            })
+            .provide(responses![
+                EXAMPLE.graphql("GetUser")
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_graphql_provided_response_operation_mismatch() {
+
+    let actual = {
+        API::post("/graphql/forward")
+            .with_body(object! {
+                "query" => "query GetUser { user { name } }"
+            })
+            .provide(responses![
+                EXAMPLE.graphql("ListUsers")
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/graphql/forward\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>POST <by>response provided for \"<bn>https://example.com<bn>/graphql\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a request <by>for the response, but got <br>none<by>.
+
+<bb> 2) <br>Request Failure: <by>Unexpected <bn>POST <by>request to \"<bn>https://example.com<bn>/graphql\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>POST <by>response provided for \"<bn>https://example.com<bn>/graphql\"
+
+        - <by>GraphQL operation <by>does not match, expected \"<bg>ListUsers\" <by>but got \"<br>GetUser\"<by>.
+
+
+"#, actual);
+
+}
@@ -0,0 +1,155 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use noir::MockProvider;
+
+static SETUP_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+static TEARDOWN_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+static PANIC_TEARDOWN_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+static MOCK_TEARDOWN_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+#[derive(Copy, Clone, Default)]
+pub struct HookAPI;
+impl HttpApi for HookAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4002
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+    fn setup(&self) {
+        SETUP_COUNT.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn teardown(&self) {
+        TEARDOWN_COUNT.fetch_add(1, Ordering::SeqCst);
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct PanicAPI;
+impl HttpApi for PanicAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4010
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+    fn teardown(&self) {
+        PANIC_TEARDOWN_COUNT.fetch_add(1, Ordering::SeqCst);
+    }
+
+}
+
+struct PanicMock;
+impl MockProvider for PanicMock {
+
+    fn setup(&mut self) {}
+
+    fn teardown(&mut self) {
+        MOCK_TEARDOWN_COUNT.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn validate(&mut self) -> Vec<String> {
+        panic!("Mock validation panicked.");
+    }
+
+}
+
+struct PanicSetupMock;
+impl MockProvider for PanicSetupMock {
+
+    fn setup(&mut self) {
+        panic!("Mock setup panicked.");
+    }
+
+    fn teardown(&mut self) {}
+
+}
+
+#[test]
+fn test_api_setup_and_teardown() {
+
+    let actual = {
+        HookAPI::get("/get/hello")
+            .expected_status(StatusCode::Ok)
+            .collect()
+    };
+
+    assert_pass!(actual);
+    assert_eq!(SETUP_COUNT.load(Ordering::SeqCst), 1);
+    assert_eq!(TEARDOWN_COUNT.load(Ordering::SeqCst), 1);
+
+    let actual = {
+        HookAPI::get("/status/404")
+            .expected_status(StatusCode::Ok)
+            .collect()
+    };
+
+    assert!(actual.is_err());
+    assert_eq!(SETUP_COUNT.load(Ordering::SeqCst), 2);
+    assert_eq!(TEARDOWN_COUNT.load(Ordering::SeqCst), 2);
+
+}
+
+
+#[test]
+fn test_api_teardown_on_panic() {
+
+    let result = panic::catch_unwind(|| {
+        PanicAPI::get("/get/hello")
+            .mocks(mocks![PanicMock])
+            .expected_status(StatusCode::Ok)
+            .collect()
+    });
+
+    assert!(result.is_err());
+    assert_eq!(MOCK_TEARDOWN_COUNT.load(Ordering::SeqCst), 1);
+    assert_eq!(PANIC_TEARDOWN_COUNT.load(Ordering::SeqCst), 1);
+
+}
+
+#[test]
+fn test_api_reset_responses_on_panic() {
+
+    let result = panic::catch_unwind(|| {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").with_body("Hello World")
+            ])
+            .mocks(mocks![PanicSetupMock])
+            .collect()
+    });
+
+    assert!(result.is_err());
+
+    // Responses of the panicked request must not leak into the next one
+    let actual = {
+        API::get("/get/hello")
+            .expected_status(StatusCode::Ok)
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
@@ -0,0 +1,251 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use hyper::version::HttpVersion;
+
+
+// HTTP/2 Cleartext Server ----------------------------------------------------
+#[derive(Copy, Clone, Default)]
+pub struct Http2API;
+impl HttpApi for Http2API {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4009
+    }
+
+    fn http_version(&self) -> HttpVersion {
+        HttpVersion::Http20
+    }
+
+    fn start(&self) {
+        let listener = TcpListener::bind(self.host().as_str()).unwrap();
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                thread::spawn(move || {
+                    serve_http2(stream).ok();
+                });
+            }
+        }
+    }
+
+}
+
+fn read_frame(stream: &mut TcpStream) -> std::io::Result<(u8, u8, u32, Vec<u8>)> {
+    let mut head = [0; 9];
+    try!(stream.read_exact(&mut head));
+    let length = ((head[0] as usize) << 16) | ((head[1] as usize) << 8) | head[2] as usize;
+    let stream_id = ((head[5] as u32 & 0x7F) << 24) | ((head[6] as u32) << 16) | ((head[7] as u32) << 8) | head[8] as u32;
+    let mut payload = vec![0; length];
+    try!(stream.read_exact(&mut payload[..]));
+    Ok((head[3], head[4], stream_id, payload))
+}
+
+fn write_frame(stream: &mut TcpStream, kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> std::io::Result<()> {
+    let length = payload.len();
+    let mut frame = vec![
+        (length >> 16) as u8, (length >> 8) as u8, length as u8, kind, flags,
+        (stream_id >> 24) as u8, (stream_id >> 16) as u8, (stream_id >> 8) as u8, stream_id as u8
+    ];
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame[..])
+}
+
+// Parses the literal, non huffman encoded header fields send by noir
+fn parse_fields(block: &[u8]) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut offset = 0;
+    while offset < block.len() {
+        offset += 1;
+        let mut strings = Vec::new();
+        for _ in 0..2 {
+            let length = block[offset] as usize;
+            strings.push(String::from_utf8_lossy(&block[offset + 1..offset + 1 + length]).to_string());
+            offset += 1 + length;
+        }
+        fields.push((strings[0].clone(), strings[1].clone()));
+    }
+    fields
+}
+
+fn serve_http2(mut stream: TcpStream) -> std::io::Result<()> {
+
+    let mut preface = [0; 24];
+    try!(stream.read_exact(&mut preface));
+    try!(write_frame(&mut stream, 0x4, 0x0, 0, &[]));
+
+    let mut block = Vec::new();
+    let mut body = Vec::new();
+    loop {
+        let (kind, flags, stream_id, payload) = try!(read_frame(&mut stream));
+        match kind {
+            // SETTINGS
+            0x4 if flags & 0x1 == 0 => try!(write_frame(&mut stream, 0x4, 0x1, 0, &[])),
+            // HEADERS
+            0x1 if stream_id == 1 => {
+                block.extend_from_slice(&payload[..]);
+                if flags & 0x1 == 0x1 {
+                    break;
+                }
+            },
+            // DATA
+            0x0 if stream_id == 1 => {
+                body.extend_from_slice(&payload[..]);
+                if flags & 0x1 == 0x1 {
+                    break;
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let fields = parse_fields(&block[..]);
+    let field = |name: &str| fields.iter().find(|f| f.0 == name).map_or(String::new(), |f| f.1.clone());
+
+    let mut headers = Vec::new();
+    let data = match (field(":method").as_str(), field(":path").as_str()) {
+        ("GET", "/hello") => {
+            // :status 200 from the static table
+            headers.push(0x88);
+            // content-type with incremental indexing
+            headers.push(0x5F);
+            headers.push(10);
+            headers.extend_from_slice(b"text/plain");
+            // x-host with a huffman encoded value
+            headers.push(0x00);
+            headers.push(6);
+            headers.extend_from_slice(b"x-host");
+            headers.push(0x80 | 12);
+            headers.extend_from_slice(&[
+                0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0, 0xab, 0x90, 0xf4, 0xff
+            ]);
+            b"Hello World".to_vec()
+        },
+        ("POST", "/echo") => {
+            headers.push(0x88);
+            // content-type without indexing, the name index overflows the
+            // 4 bit prefix
+            headers.push(0x0F);
+            headers.push(31 - 15);
+            let content_type = field("content-type");
+            headers.push(content_type.len() as u8);
+            headers.extend_from_slice(content_type.as_bytes());
+            body
+        },
+        _ => {
+            // :status 404 with an indexed name
+            headers.push(0x08);
+            headers.push(3);
+            headers.extend_from_slice(b"404");
+            Vec::new()
+        }
+    };
+
+    try!(write_frame(&mut stream, 0x1, 0x4, 1, &headers[..]));
+    try!(write_frame(&mut stream, 0x0, 0x1, 1, &data[..]));
+
+    // Wait for the client to go away
+    while let Ok((kind, _, _, _)) = read_frame(&mut stream) {
+        if kind == 0x7 {
+            break;
+        }
+    }
+
+    Ok(())
+
+}
+
+
+// HTTP/2 Tests ---------------------------------------------------------------
+#[test]
+fn test_http2_request() {
+
+    let actual = {
+        Http2API::get("/hello")
+            .expected_status(StatusCode::Ok)
+            .expected_header(ContentType(Mime(TopLevel::Text, SubLevel::Plain, vec![])))
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_http2_request_body() {
+
+    let actual = {
+        Http2API::post("/echo")
+            .with_body(object! {
+                "key" => "value"
+            })
+            .expected_status(StatusCode::Ok)
+            .expected_header(ContentType(Mime(TopLevel::Application, SubLevel::Json, vec![])))
+            .expected_body(object! {
+                "key" => "value"
+            })
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_http2_status_mismatch() {
+
+    let actual = {
+        Http2API::get("/missing")
+            .expected_status(StatusCode::Ok)
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4009<bn>/missing\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>status code does not match value, expected:
+
+        \"<bg>200 OK\"
+
+    <by>but got:
+
+        \"<br>404 Not Found\"
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_http2_dump() {
+
+    let actual = {
+        Http2API::get("/hello").dump().collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4009<bn>/hello\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>headers dump (<bn>HTTP/2.0<by>):
+
+        <bn>content-type: <bp>text/plain
+        <bn>      x-host: <bp>www.example.com
+
+    <by>Response <by>body dump:
+
+        \"<bp>Hello World\"
+
+
+"#, actual);
+
+}
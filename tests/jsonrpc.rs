@@ -0,0 +1,226 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+use json::JsonValue;
+use noir::JsonRpcCall;
+
+
+// JSON-RPC Requests ----------------------------------------------------------
+#[test]
+fn test_jsonrpc_call() {
+
+    let actual = {
+        API::jsonrpc("sum", array![1, 2])
+            .expected_status(StatusCode::Ok)
+            .expected_jsonrpc_result(1, 3.into())
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_jsonrpc_batch() {
+
+    let actual = {
+        API::jsonrpc_batch(vec![
+                JsonRpcCall::new(1, "sum", array![1, 2]),
+                JsonRpcCall::new("echo-1", "echo", object! {
+                    "key" => "value"
+                }),
+                JsonRpcCall::notification("sum", array![3, 4]),
+                JsonRpcCall::new(3, "missing", JsonValue::Null)
+            ])
+            .expected_status(StatusCode::Ok)
+            .expected_jsonrpc_result(1, 3.into())
+            .expected_jsonrpc_result("echo-1", object! {
+                "key" => "value"
+            })
+            .expected_jsonrpc_error(3, object! {
+                "code" => -32601
+            })
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_jsonrpc_batch_mismatch() {
+
+    let actual = {
+        API::jsonrpc_batch(vec![
+                JsonRpcCall::new(1, "sum", array![1, 2]),
+                JsonRpcCall::new(2, "missing", JsonValue::Null)
+            ])
+            .expected_jsonrpc_result(1, 4.into())
+            .expected_jsonrpc_result(2, true.into())
+            .expected_jsonrpc_error(1, object! {
+                "code" => -32601
+            })
+            .expected_jsonrpc_result(5, JsonValue::Null)
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/rpc\" <by>returned <br>4 <by>error(s)
+
+<bb> 1) <by>Response <by>JSON-RPC result for id <bb>1 <by>does not match:
+
+        - <bb>json: <bg>Number (<br>3) <by>does not match expected value (<bg>4)
+
+<bb> 2) <by>Response <by>JSON-RPC result for id <bb>2 <by>was expected, but got an error:
+
+        <br>{\"code\":-32601,\"message\":\"Method not found\"}
+
+<bb> 3) <by>Response <by>JSON-RPC error for id <bb>1 <by>was expected, but got a result:
+
+        <br>3
+
+<bb> 4) <by>Response <by>JSON-RPC response with id <bb>5 <by>is <br>missing<by>.
+
+
+"#, actual);
+
+}
+
+
+// JSON-RPC Responses ---------------------------------------------------------
+#[test]
+fn test_jsonrpc_provided_result() {
+
+    let actual = {
+        API::post("/rpc/forward")
+            .with_body(object! {
+                "jsonrpc" => "2.0",
+                "method" => "getBalance",
+                "params" => array!["alice"],
+                "id" => 7
+            })
+            .provide(responses![
+                EXAMPLE.jsonrpc("getBalance").with_jsonrpc_result(object! {
+                    "balance" => 42
+                })
+            ])
+            .expected_jsonrpc_result(7, object! {
+                "balance" => 42
+            })
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_jsonrpc_provided_error() {
+
+    let actual = {
+        API::post("/rpc/forward")
+            .with_body(array![
+                object! {
+                    "jsonrpc" => "2.0",
+                    "method" => "transfer",
+                    "params" => array!["alice", 100],
+                    "id" => 1
+                },
+                object! {
+                    "jsonrpc" => "2.0",
+                    "method" => "transfer",
+                    "params" => array!["bob", 200],
+                    "id" => 2
+                }
+            ])
+            .provide(responses![
+                EXAMPLE.jsonrpc("transfer").with_jsonrpc_error(-32000, "Insufficient funds")
+            ])
+            .expected_jsonrpc_error(1, object! {
+                "code" => -32000
+            })
+            .expected_jsonrpc_error(2, object! {
+                "message" => "Insufficient funds"
+            })
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_jsonrpc_provided_mixed_batch() {
+
+    let actual = {
+        API::post("/rpc/forward")
+            .with_body(array![
+                object! {
+                    "jsonrpc" => "2.0",
+                    "method" => "getBalance",
+                    "id" => 1
+                },
+                object! {
+                    "jsonrpc" => "2.0",
+                    "method" => "transfer",
+                    "id" => 2
+                }
+            ])
+            .provide(responses![
+                EXAMPLE.post("/rpc").with_jsonrpc_result(true.into())
+            ])
+            .expected_jsonrpc_result(1, true.into())
+            .expected_jsonrpc_result(2, true.into())
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_jsonrpc_provided_method_mismatch() {
+
+    let actual = {
+        API::post("/rpc/forward")
+            .with_body(array![
+                object! {
+                    "jsonrpc" => "2.0",
+                    "method" => "getBalance",
+                    "id" => 1
+                },
+                object! {
+                    "jsonrpc" => "2.0",
+                    "method" => "transfer",
+                    "id" => 2
+                }
+            ])
+            .provide(responses![
+                EXAMPLE.jsonrpc("transfer")
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/rpc/forward\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>POST <by>response provided for \"<bn>https://example.com<bn>/rpc\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a request <by>for the response, but got <br>none<by>.
+
+<bb> 2) <br>Request Failure: <by>Unexpected <bn>POST <by>request to \"<bn>https://example.com<bn>/rpc\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>POST <by>response provided for \"<bn>https://example.com<bn>/rpc\"
+
+        - <by>JSON-RPC method <by>does not match, expected \"<bg>transfer\" <by>but got \"<br>getBalance, transfer\"<by>.
+
+
+"#, actual);
+
+}
@@ -0,0 +1,253 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use] extern crate lazy_static;
+#[macro_use]
+mod base_test;
+test!();
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use noir::HttpApiProcess;
+
+static START_COUNT: AtomicUsize = ATOMIC_USIZE_INIT;
+
+lazy_static! {
+    static ref LISTENING: Mutex<Option<hyper::server::Listening>> = Mutex::new(None);
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct LifecycleAPI;
+impl HttpApi for LifecycleAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4003
+    }
+
+    fn start(&self) {
+        START_COUNT.fetch_add(1, Ordering::SeqCst);
+        let listening = hyper::Server::http(self.host().as_str()).unwrap().handle(handle).unwrap();
+        *LISTENING.lock().unwrap() = Some(listening);
+    }
+
+    fn stop(&self) {
+        if let Some(mut listening) = LISTENING.lock().unwrap().take() {
+            listening.close().ok();
+        }
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct DefaultStopAPI;
+impl HttpApi for DefaultStopAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4012
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct StuckAPI;
+impl HttpApi for StuckAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4013
+    }
+
+    fn start(&self) {
+        test_server(self.host().as_str());
+    }
+
+    fn stop(&self) {
+        // Never stops the server
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct ProcessAPI;
+impl HttpApi for ProcessAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4004
+    }
+
+    fn process(&self) -> Option<HttpApiProcess> {
+        Some(HttpApiProcess::new("noir-missing-binary").with_env("PORT", "4004"))
+    }
+
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct ExternalProcessAPI;
+impl HttpApi for ExternalProcessAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4014
+    }
+
+    fn process(&self) -> Option<HttpApiProcess> {
+        // The test binary itself serves the API via `external_process_server`
+        let program = std::env::current_exe().unwrap();
+        Some(HttpApiProcess::new(program.to_string_lossy().to_string())
+            .with_args(vec!["external_process_server", "--exact"])
+            .with_arg("--nocapture")
+            .with_env("NOIR_EXTERNAL_PROCESS", "1")
+            .with_env("PORT", "4014")
+            .with_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests")))
+    }
+
+}
+
+#[test]
+fn external_process_server() {
+
+    use std::io::Write;
+    use std::path::Path;
+
+    // Only serves the API when spawned by ExternalProcessAPI
+    if std::env::var("NOIR_EXTERNAL_PROCESS").is_err() {
+        return;
+    }
+
+    let port = std::env::var("PORT").unwrap();
+    println!("PORT={}", port);
+    println!("Current directory contains tests: {}", Path::new("lifecycle.rs").exists());
+    writeln!(std::io::stderr(), "Listening on localhost:{}", port).unwrap();
+
+    test_server(format!("localhost:{}", port).as_str());
+
+}
+
+fn wait_for_output<F: Fn() -> String>(output: F, expected: &str) -> String {
+    // Output is captured in the background
+    for _ in 0..100 {
+        if output().contains(expected) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    output()
+}
+
+#[test]
+fn test_api_process() {
+
+    use std::net::TcpStream;
+
+    assert_pass!(ExternalProcessAPI::get("/get/hello").expected_status(StatusCode::Ok).collect());
+
+    let stdout = wait_for_output(ExternalProcessAPI::stdout, "Current directory");
+    assert!(stdout.contains("PORT=4014"), "{}", stdout);
+    assert!(stdout.contains("Current directory contains tests: true"), "{}", stdout);
+
+    let stderr = wait_for_output(ExternalProcessAPI::stderr, "Listening");
+    assert!(stderr.contains("Listening on localhost:4014"), "{}", stderr);
+
+    // The process is killed on shutdown
+    assert_pass!(ExternalProcessAPI::shutdown());
+    assert!(TcpStream::connect(("localhost", 4014)).is_err());
+    assert_eq!(ExternalProcessAPI::stdout(), "");
+
+}
+
+#[test]
+fn test_api_shutdown_and_restart() {
+
+    assert_pass!(LifecycleAPI::get("/get/hello").expected_status(StatusCode::Ok).collect());
+    assert_eq!(START_COUNT.load(Ordering::SeqCst), 1);
+
+    assert_pass!(LifecycleAPI::shutdown());
+
+    assert_pass!(LifecycleAPI::get("/get/hello").expected_status(StatusCode::Ok).collect());
+    assert_eq!(START_COUNT.load(Ordering::SeqCst), 2);
+
+    assert_pass!(LifecycleAPI::restart());
+    assert_eq!(START_COUNT.load(Ordering::SeqCst), 3);
+
+    assert_pass!(LifecycleAPI::get("/get/hello").expected_status(StatusCode::Ok).collect());
+    assert_eq!(START_COUNT.load(Ordering::SeqCst), 3);
+    assert_eq!(LifecycleAPI::stdout(), "");
+
+    assert_pass!(LifecycleAPI::shutdown());
+
+}
+
+#[test]
+fn test_api_shutdown_default_stop() {
+
+    use std::time::{Duration, Instant};
+
+    assert_pass!(DefaultStopAPI::get("/get/hello").expected_status(StatusCode::Ok).collect());
+
+    // APIs without a stop callback are not waited for
+    let start = Instant::now();
+    assert_pass!(DefaultStopAPI::shutdown());
+    assert!(start.elapsed() < Duration::from_millis(500));
+
+}
+
+#[test]
+fn test_api_shutdown_failure() {
+
+    assert_pass!(StuckAPI::get("/get/hello").expected_status(StatusCode::Ok).collect());
+
+    assert_fail!(r#"
+<br>API Failure: <by>Server for \"<bn>http://localhost:4013\" <by>did not stop within <bg>1000ms<by>.
+
+"#, StuckAPI::shutdown());
+
+}
+
+#[test]
+fn test_api_process_spawn_failure() {
+
+    let actual = {
+        multiline(ProcessAPI::get("/").collect().unwrap_err())
+    };
+
+    // The description of the spawn error differs between platforms
+    assert!(actual.starts_with(r#"
+<br>API Failure: <by>Server for \"<bn>http://localhost:4004\" <by>could not be started.
+
+    <by>Process could not be spawned: <br>"#), "{}", actual);
+
+}
+
+#[test]
+fn test_api_process_restart_failure() {
+
+    let actual = multiline(ProcessAPI::restart().unwrap_err());
+
+    // The description of the spawn error differs between platforms
+    assert!(actual.starts_with(r#"
+<br>API Failure: <by>Server for \"<bn>http://localhost:4004\" <by>could not be started.
+
+    <by>Process could not be spawned: <br>"#), "{}", actual);
+
+}
@@ -5,10 +5,108 @@ mod base_test;
 test!();
 
 
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use noir::{MockProvider, MockEnv, MockTempDir, MockClock};
+
+
+struct CustomMock {
+    calls: Arc<Mutex<Vec<&'static str>>>
+}
+
+impl MockProvider for CustomMock {
+
+    fn setup(&mut self) {
+        self.calls.lock().unwrap().push("setup");
+    }
+
+    fn teardown(&mut self) {
+        self.calls.lock().unwrap().push("teardown");
+    }
+
+}
+
 #[test]
 fn test_mock_custom_implementation() {
 
+    let calls = Arc::new(Mutex::new(Vec::new()));
 
+    let actual = {
+        API::get("/get/hello")
+            .mocks(mocks![CustomMock {
+                calls: calls.clone()
+            }])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+    assert_eq!(*calls.lock().unwrap(), vec!["setup", "teardown"]);
 
 }
 
+#[test]
+fn test_mock_env() {
+
+    std::env::set_var("NOIR_MOCK_VAR", "original");
+
+    let actual = {
+        API::get("/mocks/env")
+            .mocks(mocks![MockEnv::new().with_var("NOIR_MOCK_VAR", "mocked")])
+            .expected_body("mocked")
+            .collect()
+    };
+
+    assert_pass!(actual);
+    assert_eq!(std::env::var("NOIR_MOCK_VAR").unwrap(), "original");
+
+    let actual = {
+        API::get("/mocks/env")
+            .mocks(mocks![MockEnv::new().without_var("NOIR_MOCK_VAR")])
+            .expected_body("unset")
+            .collect()
+    };
+
+    assert_pass!(actual);
+    assert_eq!(std::env::var("NOIR_MOCK_VAR").unwrap(), "original");
+
+}
+
+#[test]
+fn test_mock_temp_dir() {
+
+    let dir = MockTempDir::new()
+        .with_file("data/file.txt", "Hello World")
+        .with_env("NOIR_MOCK_DIR");
+
+    let path = dir.path();
+
+    let actual = {
+        API::get("/mocks/file")
+            .mocks(mocks![dir])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+    assert!(!path.exists());
+    assert!(std::env::var("NOIR_MOCK_DIR").is_err());
+
+}
+
+#[test]
+fn test_mock_clock() {
+
+    let clock = MockClock::from_unix_timestamp(1000);
+    clock.advance(Duration::from_secs(60));
+
+    let actual = {
+        API::get("/mocks/clock")
+            .mocks(mocks![clock.clone()])
+            .expected_body("1060")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
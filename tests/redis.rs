@@ -0,0 +1,102 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+use std::time::Duration;
+use noir::MockRedis;
+
+
+#[test]
+fn test_redis_seeded_key() {
+
+    let actual = {
+        API::get("/cache/user")
+            .mocks(mocks![
+                MockRedis::new()
+                    .with_key("user:1", "Alice")
+                    .expected_command(vec!["get", "user:1"])
+                    .expected_key("user:1", "Alice")
+            ])
+            .expected_body("Alice")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_redis_missing_key() {
+
+    let actual = {
+        API::get("/cache/user")
+            .mocks(mocks![
+                MockRedis::new()
+                    .expected_command(vec!["SET", "user:1", "Bob", "EX", "60"])
+                    .expected_key("user:1", "Bob")
+                    .expected_ttl("user:1", Duration::from_secs(60))
+            ])
+            .expected_body("Bob")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_redis_expired_key() {
+
+    let actual = {
+        API::get("/cache/user")
+            .mocks(mocks![
+                MockRedis::new()
+                    .with_expiring_key("user:1", "Alice", Duration::from_secs(0))
+                    .expected_command(vec!["SET", "user:1", "Bob", "EX", "60"])
+                    .expected_key("user:1", "Bob")
+                    .expected_ttl("user:1", Duration::from_secs(60))
+            ])
+            .expected_body("Bob")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_redis_expectations_failure() {
+
+    let actual = {
+        API::get("/cache/user")
+            .mocks(mocks![
+                MockRedis::new()
+                    .expected_command(vec!["SET", "user:1", "Bob", "EX", "3600"])
+                    .expected_key("user:1", "Alice")
+                    .expected_key("user:2", "Carol")
+                    .expected_ttl("user:1", Duration::from_secs(3600))
+            ])
+            .expected_body("Bob")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/cache/user\" <by>returned <br>4 <by>error(s)
+
+<bb> 1) <br>Mock Failure: <bn>Redis <by>mock <by>returned <br>4 <by>error(s)
+
+    <bb> 1.1) <by>Command \"<bb>SET user:1 Bob EX 3600\" <by>was expected to be issued, but <br>was not<by>.
+
+    <bb> 1.2) <by>Key \"<bb>user:1\" <by>value <by>does not match, expected \"<bg>Alice\" <by>but got \"<br>Bob\"<by>.
+
+    <bb> 1.3) <by>Key \"<bb>user:2\" <by>was expected <bg>to be present<by>, but <br>is missing<by>.
+
+    <bb> 1.4) <by>Key \"<bb>user:1\" <by>TTL <by>does not match, expected \"<bg>3600000ms\" <by>but got \"<br>60000ms\"<by>.
+
+
+"#, actual);
+
+}
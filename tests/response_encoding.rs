@@ -0,0 +1,106 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+#[test]
+fn test_responses_provided_with_gzip_encoding() {
+
+    let actual = {
+        API::get("/responses/decode")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_content_encoding(Encoding::Gzip)
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_deflate_encoding() {
+
+    let actual = {
+        API::get("/responses/decode")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_content_encoding(Encoding::Deflate)
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_chunks() {
+
+    let actual = {
+        API::get("/responses/decode")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_chunks(vec![1, 4, 2])
+            ])
+            .expected_header(Server("Chunked".to_string()))
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_chunks_and_trailer() {
+
+    let actual = {
+        API::get("/responses/decode")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_trailer(Server("Foo".to_string()))
+            ])
+            .expected_header(Server("Chunked".to_string()))
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_chunks_and_gzip_encoding() {
+
+    let actual = {
+        API::get("/responses/decode")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_content_encoding(Encoding::Gzip)
+                       .with_chunks(vec![8, 8])
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+
+#[test]
+#[should_panic(expected = "noir: Unsupported content encoding \"br\".")]
+fn test_responses_provided_with_unsupported_encoding() {
+    EXAMPLE.get("/one").with_content_encoding(Encoding::EncodingExt("br".to_string()));
+}
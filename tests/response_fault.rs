@@ -0,0 +1,80 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+#[test]
+fn test_responses_provided_with_delay() {
+
+    let actual = {
+        API::get("/responses/fault")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_delay(Duration::from_millis(20))
+            ])
+            .expected_status(StatusCode::Ok)
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_delay_exceeding_read_timeout() {
+
+    let actual = {
+        API::get("/responses/fault")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_delay(Duration::from_millis(250))
+            ])
+            .expected_status(StatusCode::GatewayTimeout)
+            .expected_body("WouldBlock")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_reset_after() {
+
+    let actual = {
+        API::get("/responses/fault")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_body("Hello World")
+                       .with_reset_after(5)
+            ])
+            .expected_status(StatusCode::BadGateway)
+            .expected_body("ConnectionReset")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_malformed_response() {
+
+    let actual = {
+        API::get("/responses/fault")
+            .provide(responses![
+                EXAMPLE.get("/one").with_malformed_response()
+            ])
+            .expected_status(StatusCode::BadGateway)
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
@@ -0,0 +1,158 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+use noir::HttpReply;
+
+
+#[test]
+fn test_responses_provided_with_handler() {
+
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").with_handler(|req| {
+                    HttpReply::new().with_body(format!("{} {}", req.method, req.path))
+                })
+            ])
+            .expected_status(StatusCode::Ok)
+            .expected_body("GET /one")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_handler_status_override() {
+
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").with_status(StatusCode::Ok).with_handler(|_| {
+                    HttpReply::new().with_status(StatusCode::NotFound)
+                })
+            ])
+            .expected_status(StatusCode::NotFound)
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_handler_header() {
+
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").with_handler(|req| {
+                    let accept = req.header::<Accept>().map_or(0, |a| a.len());
+                    HttpReply::new().with_header(Server(format!("Accept {}", accept)))
+                })
+            ])
+            .expected_header(Server("Accept 1".to_string()))
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_handler_json_body() {
+
+    let actual = {
+        API::post("/response/forward")
+            .with_body(object! {
+                "id" => 42
+            })
+            .provide(responses![
+                EXAMPLE.post("/forward").with_handler(|req| {
+                    let id = req.json().map_or(0.0, |json| json["id"].as_f64().unwrap_or(0.0));
+                    HttpReply::new().with_body(object! {
+                        "id" => id
+                    })
+
+                }).expected_body(object! {
+                    "id" => 42
+                })
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_with_handler_expected_body_mismatch() {
+
+    let actual = {
+        API::post("/response/forward")
+            .with_header(ContentType(Mime(TopLevel::Text, SubLevel::Plain, vec![])))
+            .provide(responses![
+                EXAMPLE.post("/forward").with_handler(|req| {
+                    HttpReply::new().with_body(req.text().unwrap_or("").to_string())
+
+                }).expected_body("Response")
+            ])
+            .with_body("Response Body")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/response/forward\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>POST <by>response provided for \"<bn>https://example.com<bn>/forward\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Request <by>text body does not match, expected:
+
+              \"<bg>Response\"
+
+          <by>but got:
+
+              \"<br>Response Body\"
+
+          <by>difference:
+
+              \"Response <gbg>Body\"
+
+
+"#, actual);
+
+}
+
+
+#[test]
+fn test_responses_provided_with_handler_panic() {
+
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").with_handler(|_| {
+                    panic!("Handler failed.");
+                })
+            ])
+            .collect()
+    };
+
+    assert!(actual.is_err());
+
+    // Later requests are not affected by the panicked handler
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").with_body("Hello World")
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
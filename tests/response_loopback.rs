@@ -0,0 +1,83 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+#[test]
+fn test_responses_provided_via_loopback() {
+
+    let actual = {
+        API::get("/responses/loopback")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_status(StatusCode::Accepted)
+                       .with_body("Hello World")
+            ])
+            .expected_status(StatusCode::Accepted)
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_via_loopback_tls() {
+
+    let actual = {
+        API::get("/responses/loopback/tls")
+            .provide(responses![
+                EXAMPLE.get("/one").with_body("Hello World")
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_via_loopback_expected_header() {
+
+    let actual = {
+        API::get("/responses/loopback")
+            .provide(responses![
+                EXAMPLE.get("/one").expected_header(Server("Foo".to_string()))
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/loopback\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Request <by>header \"<bb>Server\" <by>was expected <bg>to be present<by>, but <br>is missing<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_responses_provided_via_loopback_unexpected_request() {
+
+    let actual = {
+        API::get("/responses/loopback")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/loopback\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one\"<by>, no response was provided.
+
+
+"#, actual);
+
+}
+
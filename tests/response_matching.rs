@@ -0,0 +1,154 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+// Headers --------------------------------------------------------------------
+#[test]
+fn test_responses_matching_header() {
+
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .matching_header(Connection::close())
+                       .with_status(StatusCode::Accepted)
+            ])
+            .expected_status(StatusCode::Accepted)
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_matching_header_mismatch() {
+
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").matching_header(Connection::keep_alive())
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/one\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a request <by>for the response, but got <br>none<by>.
+
+<bb> 2) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\"
+
+        - <by>Header \"Connection\" <by>does not match, expected \"<bg>keep-alive\" <by>but got \"<br>close\"<by>.
+
+
+"#, actual);
+
+}
+
+
+// Bodies ---------------------------------------------------------------------
+#[test]
+fn test_responses_matching_json_body() {
+
+    let actual = {
+        API::post("/response/forward")
+            .with_body(object! {
+                "id" => 42,
+                "name" => "noir"
+            })
+            .provide(responses![
+                EXAMPLE.post("/forward").matching_body(object! {
+                    "id" => 42
+                })
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_matching_json_body_mismatch() {
+
+    let actual = {
+        API::post("/response/forward")
+            .with_body(object! {
+                "id" => 42,
+                "name" => "noir"
+            })
+            .provide(responses![
+                EXAMPLE.post("/forward").matching_body(object! {
+                    "id" => 43
+                })
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>POST <by>request to \"<bn>http://localhost:4000<bn>/response/forward\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>POST <by>response provided for \"<bn>https://example.com<bn>/forward\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a request <by>for the response, but got <br>none<by>.
+
+<bb> 2) <br>Request Failure: <by>Unexpected <bn>POST <by>request to \"<bn>https://example.com<bn>/forward\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>POST <by>response provided for \"<bn>https://example.com<bn>/forward\"
+
+        - <by>Body does not match.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_responses_matching_form_body() {
+
+    let actual = {
+        API::post("/response/forward")
+            .with_body(form! {
+                "id" => 42,
+                "name" => "noir"
+            })
+            .provide(responses![
+                EXAMPLE.post("/forward").matching_body(form! {
+                    "name" => "noir"
+                })
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_matching_body_regex() {
+
+    let actual = {
+        API::post("/response/forward")
+            .with_body("Hello World 42")
+            .provide(responses![
+                EXAMPLE.post("/forward").matching_body_regex(r"World \d+$")
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
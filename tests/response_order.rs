@@ -32,3 +32,140 @@ fn test_responses_provided_out_of_order() {
 
 }
 
+
+#[test]
+fn test_responses_provided_unordered() {
+
+    let actual = {
+        API::get("/responses/two")
+            .provide(responses![
+                EXAMPLE.get("/two").unordered(),
+                EXAMPLE.get("/one").unordered()
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_in_different_groups() {
+
+    let actual = {
+        API::get("/responses/two")
+            .provide(responses![
+                EXAMPLE.get("/two").in_group("b"),
+                EXAMPLE.get("/one").in_group("a")
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_in_same_group_out_of_order() {
+
+    let actual = {
+        API::get("/responses/two")
+            .provide(responses![
+                EXAMPLE.get("/two").in_group("a"),
+                EXAMPLE.get("/one").in_group("a")
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/two\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/two\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Response fetched out of order, <bg>provided for request <bb>1<by>, <br>fetched by request <bb>2<by>.
+
+<bb> 2) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\" <by>returned <br>1 <by>error(s)
+
+    <bb> 2.1) <by>Response fetched out of order, <bg>provided for request <bb>2<by>, <br>fetched by request <bb>1<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_responses_provided_in_multiple_groups_numbering() {
+
+    let actual = {
+        API::get("/responses/two")
+            .provide(responses![
+                EXAMPLE.get("/zero").in_group("b"),
+                EXAMPLE.get("/two").in_group("a"),
+                EXAMPLE.get("/one").in_group("a")
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/two\" <by>returned <br>3 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/zero\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a request <by>for the response, but got <br>none<by>.
+
+<bb> 2) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/two\" <by>returned <br>1 <by>error(s)
+
+    <bb> 2.1) <by>Response fetched out of order, <bg>provided for request <bb>1<by>, <br>fetched by request <bb>2<by>.
+
+<bb> 3) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\" <by>returned <br>1 <by>error(s)
+
+    <bb> 3.1) <by>Response fetched out of order, <bg>provided for request <bb>2<by>, <br>fetched by request <bb>1<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_responses_provided_optional() {
+
+    let actual = {
+        API::get("/responses/two")
+            .provide(responses![
+                EXAMPLE.get("/zero").optional(),
+                EXAMPLE.get("/one"),
+                EXAMPLE.get("/two")
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_optional_fetched() {
+
+    let actual = {
+        API::get("/responses/repeat")
+            .provide(responses![
+                EXAMPLE.get("/one").times(2).optional()
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/repeat\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\"
+
+        - <by>Response was already fetched by <br>2 <by>previous request(s).
+
+
+"#, actual);
+
+}
@@ -0,0 +1,108 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+#[test]
+fn test_responses_provided_via_proxy() {
+
+    let actual = {
+        API::get("/responses/proxy")
+            .provide(responses![
+                EXAMPLE.get("/one").with_body("Hello World")
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_via_proxy_connect() {
+
+    let actual = {
+        API::get("/responses/proxy/connect")
+            .provide(responses![
+                EXAMPLE.get("/one").with_body("Hello World")
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_via_proxy_unexpected_request() {
+
+    let actual = {
+        API::get("/responses/proxy")
+            .provide(responses![
+                EXAMPLE.get("/two")
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/proxy\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/two\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a request <by>for the response, but got <br>none<by>.
+
+<bb> 2) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>GET <by>response provided for \"<bn>https://example.com<bn>/two\"
+
+        - <by>Path <by>does not match, expected \"<bg>/two\" <by>but got \"<br>/one\"<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_responses_provided_via_proxy_tls() {
+
+    let actual = {
+        API::get("/responses/proxy/tls")
+            .provide(responses![
+                EXAMPLE.get("/one").with_body("Hello World")
+            ])
+            .expected_body("Hello World")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_via_proxy_certificate() {
+    let certificate = noir::HttpProxy::certificate("example.com");
+    assert!(certificate.starts_with("-----BEGIN CERTIFICATE-----"));
+    assert_eq!(certificate, noir::HttpProxy::certificate("example.com"));
+}
+
+#[test]
+fn test_responses_provided_via_proxy_ca_certificate() {
+
+    use std::fs::File;
+
+    let path = noir::HttpProxy::ca_certificate_path();
+    assert_eq!(path, noir::HttpProxy::ca_certificate_path());
+
+    let mut contents = String::new();
+    File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, noir::HttpProxy::ca_certificate());
+    assert!(contents.starts_with("-----BEGIN CERTIFICATE-----"));
+
+}
+
@@ -26,6 +26,27 @@ fn test_responses_provided_with_query_string() {
 
 }
 
+#[test]
+fn test_responses_provided_with_query_string_reordered() {
+
+    let actual = {
+        API::get("/responses/query")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_query(query!{
+                           "single" => vec!["item"],
+                           "foo" => "bar",
+                           "array[]" => vec!["item1", "item2", "item3"],
+                           "key" => "value"
+                       })
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
 #[test]
 fn test_responses_provided_with_query_string_mismatch() {
 
@@ -52,8 +73,51 @@ fn test_responses_provided_with_query_string_mismatch() {
 
 <bb> 2) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one?key=value&array%5B%5D=item1&array%5B%5D=item2&array%5B%5D=item3&foo=bar&single=item\"<by>, no response was provided.
 
+    <by>Closest provided response(s):
+
+    - <bn>GET <by>response provided for \"<bn>https://example.com<bn>/two?key=value&array%5B%5D=item1&array%5B%5D=item2&array%5B%5D=item3&foo=bar&single=item\"
+
+        - <by>Path <by>does not match, expected \"<bg>/two\" <by>but got \"<br>/one\"<by>.
+
 
 "#, actual);
 
 }
 
+
+#[test]
+fn test_responses_provided_with_query_string_array_order_mismatch() {
+
+    let actual = {
+        API::get("/responses/query")
+            .provide(responses![
+                EXAMPLE.get("/one")
+                       .with_query(query!{
+                           "key" => "value",
+                           "array[]" => vec!["item3", "item2", "item1"],
+                           "foo" => "bar",
+                           "single" => vec!["item"]
+                       })
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/query\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one?key=value&array%5B%5D=item3&array%5B%5D=item2&array%5B%5D=item1&foo=bar&single=item\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a request <by>for the response, but got <br>none<by>.
+
+<bb> 2) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one?key=value&array%5B%5D=item1&array%5B%5D=item2&array%5B%5D=item3&foo=bar&single=item\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one?key=value&array%5B%5D=item3&array%5B%5D=item2&array%5B%5D=item1&foo=bar&single=item\"
+
+        - <by>Query string <by>does not match, expected \"<bg>key=value&array%5B%5D=item3&array%5B%5D=item2&array%5B%5D=item1&foo=bar&single=item\" <by>but got \"<br>key=value&array%5B%5D=item1&array%5B%5D=item2&array%5B%5D=item3&foo=bar&single=item\"<by>.
+
+
+"#, actual);
+
+}
@@ -0,0 +1,144 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+#[test]
+fn test_responses_provided_times() {
+
+    let actual = {
+        API::get("/responses/repeat")
+            .provide(responses![
+                EXAMPLE.get("/one").times(3)
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_times_exceeded() {
+
+    let actual = {
+        API::get("/responses/repeat")
+            .provide(responses![
+                EXAMPLE.get("/one").times(2)
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/repeat\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\"
+
+        - <by>Response was already fetched by <br>2 <by>previous request(s).
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_responses_provided_at_least() {
+
+    let actual = {
+        API::get("/responses/repeat")
+            .provide(responses![
+                EXAMPLE.get("/one").at_least(4)
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/repeat\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>at least 4 request(s) <by>for the response, but got <br>3<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_responses_provided_at_most() {
+
+    let actual = {
+        API::get("/responses/none")
+            .provide(responses![
+                EXAMPLE.get("/one").at_most(3)
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_any_number_of_times() {
+
+    let actual = {
+        API::get("/responses/repeat")
+            .provide(responses![
+                EXAMPLE.get("/one").any_number_of_times()
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_never() {
+
+    let actual = {
+        API::get("/responses/none")
+            .provide(responses![
+                EXAMPLE.get("/one").never()
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_never_requested() {
+
+    let actual = {
+        API::get("/responses/one")
+            .provide(responses![
+                EXAMPLE.get("/one").never()
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/one\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <by>Unexpected <bn>GET <by>request to \"<bn>https://example.com<bn>/one\"<by>, no response was provided.
+
+    <by>Closest provided response(s):
+
+    - <bn>GET <by>response provided for \"<bn>https://example.com<bn>/one\"
+
+        - <by>Response was expected to never be fetched.
+
+
+"#, actual);
+
+}
+
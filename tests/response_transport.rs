@@ -0,0 +1,70 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+#[test]
+fn test_responses_provided_request_with_many_headers() {
+
+    let actual = {
+        API::get("/responses/headers")
+            .provide(responses![
+                EXAMPLE.get("/one").expected_header(Server("Headers".to_string()))
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_request_with_chunked_body() {
+
+    let actual = {
+        API::get("/responses/chunked")
+            .provide(responses![
+                EXAMPLE.post("/forward").expected_body("Hello World")
+            ])
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_responses_provided_request_with_chunked_body_mismatch() {
+
+    let actual = {
+        API::get("/responses/chunked")
+            .provide(responses![
+                EXAMPLE.post("/forward").expected_body("Hello")
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/chunked\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>POST <by>response provided for \"<bn>https://example.com<bn>/forward\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Request <by>text body does not match, expected:
+
+              \"<bg>Hello\"
+
+          <by>but got:
+
+              \"<br>Hello World\"
+
+          <by>difference:
+
+              \"Hello <gbg>World\"
+
+
+"#, actual);
+
+}
+
@@ -0,0 +1,189 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+use noir::SmtpMail;
+
+
+#[test]
+fn test_smtp_expected_mail() {
+
+    let actual = {
+        API::get("/mails/reset")
+            .expected_mail(
+                SmtpMail::new("user@example.com")
+                    .expected_from("noreply@example.com")
+                    .expected_subject("Reset your password")
+                    .expected_body_containing("https://example.com/reset/")
+            )
+            .expected_body("250 OK: Message accepted")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_smtp_expected_mail_regex() {
+
+    let actual = {
+        API::get("/mails/reset")
+            .expected_mail(
+                SmtpMail::new("user@example.com")
+                    .expected_subject_regex("^Reset")
+                    .expected_body_regex("/reset/[0-9]+")
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_smtp_expected_mail_mismatch() {
+
+    let actual = {
+        API::get("/mails/reset")
+            .expected_mail(
+                SmtpMail::new("user@example.com")
+                    .expected_subject("Welcome")
+                    .expected_body_containing("/confirm/")
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/mails/reset\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>SMTP <by>mail expected for \"<bn>user@example.com\" <by>returned <br>2 <by>error(s)
+
+    <bb> 1.1) <by>Mail subject <by>does not match, expected \"<bg>Welcome\" <by>but got \"<br>Reset your password\"<by>.
+
+    <bb> 1.2) <by>Mail body <by>was expected to contain \"<bg>/confirm/\"<by>, but <br>does not<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_smtp_expected_mail_regex_mismatch() {
+
+    let actual = {
+        API::get("/mails/reset")
+            .expected_mail(
+                SmtpMail::new("user@example.com")
+                    .expected_subject_regex("^Welcome")
+                    .expected_body_regex("/confirm/[0-9]+")
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/mails/reset\" <by>returned <br>2 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>SMTP <by>mail expected for \"<bn>user@example.com\" <by>returned <br>2 <by>error(s)
+
+    <bb> 1.1) <by>Mail subject <by>does not match the pattern \"<bg>^Welcome\"<by>, got \"<br>Reset your password\"<by>.
+
+    <bb> 1.2) <by>Mail body <by>does not match the pattern \"<bg>/confirm/[0-9]+\"<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_smtp_expected_mail_missing() {
+
+    let actual = {
+        API::get("/get/hello")
+            .expected_mail(SmtpMail::new("user@example.com"))
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/get/hello\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>SMTP <by>mail expected for \"<bn>user@example.com\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Expected <bg>a mail <by>for the recipient, but got <br>none<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_smtp_unexpected_mail() {
+
+    let actual = {
+        API::get("/mails/twice")
+            .expected_mail(SmtpMail::new("user@example.com"))
+            .expected_body("554 No mail expected in test")
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/mails/twice\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <by>Unexpected <bn>SMTP <by>mail to \"<bn>user@example.com\" <by>with subject \"<bn>Second\"<by>, no mail was expected.
+
+    <by>Closest provided response(s):
+
+    - <bn>SMTP <by>mail expected for \"<bn>user@example.com\"
+
+        - <by>Mail was <br>already received<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_smtp_expected_mail_encoded() {
+
+    let actual = {
+        API::get("/mails/encoded")
+            .expected_mail(
+                SmtpMail::new("user@example.com")
+                    .expected_subject("Grüße aus Köln")
+                    .expected_body_containing("Follow the link für Sie: https://example.com/reset/12345")
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+    let actual = {
+        API::get("/mails/base64")
+            .expected_mail(
+                SmtpMail::new("user@example.com")
+                    .expected_body_containing("Hallo Jürgen,\nhttps://example.com/confirm/7")
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_smtp_expected_mail_multiple_recipients() {
+
+    let actual = {
+        API::get("/mails/team")
+            .expected_mail(SmtpMail::new("alice@example.com").expected_subject("Team update"))
+            .expected_mail(SmtpMail::new("bob@example.com").expected_subject("Team update"))
+            .expected_body("250 OK: Message accepted")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
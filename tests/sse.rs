@@ -0,0 +1,204 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+use noir::{EventStream, ServerEvent};
+
+
+// Event Stream Tests ---------------------------------------------------------
+#[test]
+fn test_event_stream() {
+
+    let actual = {
+        API::get("/events")
+            .expected_status(StatusCode::Ok)
+            .expected_header(ContentType(
+                Mime(TopLevel::Text, SubLevel::Ext("event-stream".to_string()), vec![])
+            ))
+            .with_event_stream(
+                EventStream::new()
+                    .expected_event(
+                        ServerEvent::new()
+                            .with_name("update")
+                            .with_id("1")
+                            .with_json(object! {
+                                "count" => 1
+                            })
+                    )
+                    .expected_event(
+                        ServerEvent::new()
+                            .with_name("update")
+                            .with_id("2")
+                            .with_data("{\"count\":\n2}")
+                    )
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_event_stream_request_options() {
+
+    use noir::Options;
+    let actual = {
+        API::get("/events")
+            .with_options(Options {
+                // The JSON data of the events is not compared
+                json_compare_depth: 0,
+                .. Default::default()
+            })
+            .with_event_stream(
+                EventStream::new()
+                    .expected_event(
+                        ServerEvent::new()
+                            .with_name("update")
+                            .with_json(object! {
+                                "count" => 2
+                            })
+                    )
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_event_stream_mismatch() {
+
+    let actual = {
+        API::get("/events")
+            .with_event_stream(
+                EventStream::new()
+                    .expected_event(
+                        ServerEvent::new()
+                            .with_name("created")
+                            .with_id("2")
+                            .with_json(object! {
+                                "count" => 2
+                            })
+                    )
+                    .expected_event(ServerEvent::new().with_data("2"))
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/events\" <by>returned <br>4 <by>error(s)
+
+<bb> 1) <by>Event <bb>1 <by>name <by>does not match, expected \"<bg>created\" <by>but got \"<br>update\"<by>.
+
+<bb> 2) <by>Event <bb>1 <by>id <by>does not match, expected \"<bg>2\" <by>but got \"<br>1\"<by>.
+
+<bb> 3) <by>Event <bb>1 <by>JSON data does not match:
+
+        - <bb>json.<bb>count: <bg>Number (<br>1) <by>does not match expected value (<bg>2)
+
+<bb> 4) <by>Event <bb>2 <by>data <by>does not match, expected \"<bg>2\" <by>but got \"<br>{\"count\":
+2}\"<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_event_stream_timeout() {
+
+    let actual = {
+        API::get("/events")
+            .with_event_stream(
+                EventStream::new()
+                    .with_event_timeout(Duration::from_millis(250))
+                    .expected_event(ServerEvent::new().with_id("1"))
+                    .expected_event(ServerEvent::new().with_id("2"))
+                    .expected_event(ServerEvent::new().with_id("3"))
+                    .expected_event(ServerEvent::new().with_id("4"))
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/events\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Event <bb>3 <by>was expected <by>within <bg>250ms<by>, but <br>none was received<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_event_stream_timeout_after_headers() {
+
+    let actual = {
+        API::get("/events/delayed")
+            .with_event_stream(
+                EventStream::new()
+                    .with_event_timeout(Duration::from_millis(100))
+                    .expected_event(ServerEvent::new().with_id("1"))
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_event_stream_ended() {
+
+    let actual = {
+        API::get("/events/short")
+            .with_event_stream(
+                EventStream::new()
+                    .expected_event(ServerEvent::new().with_name("update"))
+                    .expected_event(ServerEvent::new().with_name("update"))
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/events/short\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Event <bb>2 <by>was expected <by>before the stream ended<by>, but <br>none was received<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_event_stream_status_mismatch() {
+
+    let actual = {
+        API::get("/status/404")
+            .expected_status(StatusCode::Ok)
+            .with_event_stream(
+                EventStream::new().expected_event(ServerEvent::new())
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/status/404\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>status code does not match value, expected:
+
+        \"<bg>200 OK\"
+
+    <by>but got:
+
+        \"<br>404 Not Found\"
+
+
+"#, actual);
+
+}
@@ -0,0 +1,353 @@
+#[macro_use] extern crate json;
+#[macro_use] extern crate noir;
+#[macro_use]
+mod base_test;
+test!();
+
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use openssl::crypto::hash::{hash, Type};
+use noir::WebSocket;
+
+
+// WebSocket Echo Server ------------------------------------------------------
+#[derive(Copy, Clone, Default)]
+pub struct WebSocketAPI;
+impl HttpApi for WebSocketAPI {
+
+    fn hostname(&self) -> &'static str {
+        "localhost"
+    }
+
+    fn port(&self) -> u16 {
+        4008
+    }
+
+    fn start(&self) {
+        let listener = TcpListener::bind(self.host().as_str()).unwrap();
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                thread::spawn(move || {
+                    serve_websocket(stream);
+                });
+            }
+        }
+    }
+
+}
+
+fn serve_websocket(mut stream: TcpStream) {
+
+    let mut head = Vec::new();
+    let mut byte = [0; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if stream.read_exact(&mut byte).is_err() {
+            return;
+        }
+        head.push(byte[0]);
+    }
+
+    let head = String::from_utf8_lossy(&head[..]).to_string();
+    let key = head.lines().find(|line| {
+        line.to_lowercase().starts_with("sec-websocket-key:")
+
+    }).map(|line| line[18..].trim().to_string());
+
+    // Malformed status lines fail the handshake
+    if head.starts_with("GET /invalid ") {
+        stream.write_all(b"HTTP/1.1 OK\r\n\r\n").ok();
+        return;
+    }
+
+    // Everything but the echo route is not found
+    let key = match key {
+        Some(ref key) if head.starts_with("GET /echo ") => key.to_string(),
+        _ => {
+            stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").ok();
+            return;
+        }
+    };
+
+    let accept = base64(&hash(
+        Type::SHA1,
+        format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key).as_bytes()
+
+    )[..]);
+
+    stream.write_all(format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+
+    ).as_bytes()).unwrap();
+
+    // Echo all frames back to the client
+    loop {
+
+        let mut head = [0; 2];
+        if stream.read_exact(&mut head).is_err() {
+            return;
+        }
+
+        let opcode = head[0] & 0x0F;
+        let mut length = (head[1] & 0x7F) as usize;
+        if length == 126 {
+            let mut bytes = [0; 2];
+            stream.read_exact(&mut bytes).unwrap();
+            length = ((bytes[0] as usize) << 8) | bytes[1] as usize;
+        }
+
+        let mut mask = [0; 4];
+        stream.read_exact(&mut mask).unwrap();
+
+        let mut payload = vec![0; length];
+        stream.read_exact(&mut payload[..]).unwrap();
+        for (index, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[index % 4];
+        }
+
+        let mut frame = vec![0x80 | opcode];
+        if length < 126 {
+            frame.push(length as u8);
+
+        } else {
+            frame.push(126);
+            frame.push((length >> 8) as u8);
+            frame.push(length as u8);
+        }
+
+        frame.append(&mut payload);
+        stream.write_all(&frame[..]).ok();
+
+        if opcode == 0x8 {
+            return;
+        }
+
+    }
+
+}
+
+fn base64(data: &[u8]) -> String {
+    let chars = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    data.chunks(3).map(|chunk| {
+        let n = chunk.iter().enumerate().fold(0, |n, (i, b)| n | ((*b as usize) << (16 - i * 8)));
+        (0..4).map(|i| if i <= chunk.len() {
+            chars[(n >> (18 - i * 6)) & 0x3F] as char
+
+        } else {
+            '='
+
+        }).collect::<String>()
+
+    }).collect()
+}
+
+
+// API WebSocket Tests --------------------------------------------------------
+#[test]
+fn test_websocket_frames() {
+
+    let actual = {
+        WebSocketAPI::get("/echo")
+            .with_websocket(
+                WebSocket::new()
+                    .send_text("Hello World")
+                    .expected_text("Hello World")
+                    .send_json(object! {
+                        "key" => "value"
+                    })
+                    .expected_json(object! {
+                        "key" => "value"
+                    })
+                    .send_binary(vec![1, 2, 3, 4])
+                    .expected_binary(vec![1, 2, 3, 4])
+                    .send_close(1000)
+                    .expected_close(1000)
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_websocket_frames_request_options() {
+
+    use noir::Options;
+    let actual = {
+        WebSocketAPI::get("/echo")
+            .with_options(Options {
+                // Nested values of the frames are not compared
+                json_compare_depth: 1,
+                .. Default::default()
+            })
+            .with_websocket(
+                WebSocket::new()
+                    .send_json(object! {
+                        "key" => "value",
+                        "deep" => object! {
+                            "compare" => "foo"
+                        }
+                    })
+                    .expected_json(object! {
+                        "key" => "value",
+                        "deep" => object! {
+                            "compare" => "bar"
+                        }
+                    })
+            )
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_websocket_frames_mismatch() {
+
+    let actual = {
+        WebSocketAPI::get("/echo")
+            .with_websocket(
+                WebSocket::new()
+                    .send_text("Hello World")
+                    .expected_text("Goodbye World")
+                    .send_binary(vec![1, 2, 3, 4])
+                    .expected_text("Hello World")
+                    .send_close(1000)
+                    .expected_close(1001)
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4008<bn>/echo\" <by>returned <br>3 <by>error(s)
+
+<bb> 1) <by>Frame <bb>1 <by>text <by>does not match, expected \"<bg>Goodbye World\" <by>but got \"<br>Hello World\"<by>.
+
+<bb> 2) <by>Frame <bb>2 <by>was expected to be a <bg>text frame<by>, but got a <br>binary frame<by>.
+
+<bb> 3) <by>Frame <bb>3 <by>close code <by>does not match, expected \"<bg>1001\" <by>but got \"<br>1000\"<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_websocket_frames_missing() {
+
+    let actual = {
+        WebSocketAPI::get("/echo")
+            .with_websocket(
+                WebSocket::new()
+                    .send_close(1000)
+                    .expected_close(1000)
+                    .expected_text("Hello World")
+            )
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4008<bn>/echo\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Frame <bb>2 <by>was expected to be a <bg>text frame<by>, but <br>none was received<by>.
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_websocket_handshake_failure() {
+
+    let actual = {
+        WebSocketAPI::get("/missing")
+            .with_websocket(WebSocket::new().expected_text("Hello World"))
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4008<bn>/missing\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <by>Response <by>status code does not match value, expected:
+
+        \"<bg>101 Switching Protocols\"
+
+    <by>but got:
+
+        \"<br>404 Not Found\"
+
+
+"#, actual);
+
+}
+
+#[test]
+fn test_websocket_handshake_invalid() {
+
+    let actual = {
+        WebSocketAPI::get("/invalid")
+            .with_websocket(WebSocket::new().expected_text("Hello World"))
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4008<bn>/invalid\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>API Failure: <by>WebSocket handshake failed: <br>noir: Invalid WebSocket handshake response.
+
+
+"#, actual);
+
+}
+
+
+// Upstream WebSocket Tests ---------------------------------------------------
+#[test]
+fn test_websocket_provided_response() {
+
+    let actual = {
+        API::get("/responses/websocket")
+            .provide(responses![
+                EXAMPLE.get("/socket").with_websocket(
+                    WebSocket::new()
+                        .expected_text("hello")
+                        .send_text("world")
+                )
+            ])
+            .expected_body("world")
+            .collect()
+    };
+
+    assert_pass!(actual);
+
+}
+
+#[test]
+fn test_websocket_provided_response_mismatch() {
+
+    let actual = {
+        API::get("/responses/websocket")
+            .provide(responses![
+                EXAMPLE.get("/socket").with_websocket(
+                    WebSocket::new()
+                        .expected_text("bye")
+                        .send_text("world")
+                )
+            ])
+            .collect()
+    };
+
+    assert_fail!(r#"
+<br>Response Failure: <bn>GET <by>request to \"<bn>http://localhost:4000<bn>/responses/websocket\" <by>returned <br>1 <by>error(s)
+
+<bb> 1) <br>Request Failure: <bn>GET <by>response provided for \"<bn>https://example.com<bn>/socket\" <by>returned <br>1 <by>error(s)
+
+    <bb> 1.1) <by>Frame <bb>1 <by>text <by>does not match, expected \"<bg>bye\" <by>but got \"<br>hello\"<by>.
+
+
+"#, actual);
+
+}